@@ -0,0 +1,1042 @@
+import builtins
+from datetime import datetime
+from os import PathLike
+from collections.abc import Iterator
+from typing import TYPE_CHECKING, Any, Literal
+
+if TYPE_CHECKING:
+    import pandas
+    import polars
+
+JSON_SCHEMA_VERSION: int
+
+class PreludeXmlParserError(Exception):
+    """Base class for all errors raised by prelude_xml_parser."""
+
+class InvalidFileTypeError(PreludeXmlParserError):
+    """The file is not a XML file."""
+
+class FileNotFoundError(PreludeXmlParserError, builtins.FileNotFoundError):  # noqa: A001
+    """The file was not found at the specified path."""
+
+class XmlIOError(PreludeXmlParserError):
+    """An io error occurred while reading the XML."""
+
+class ParseError(PreludeXmlParserError):
+    """The XML could not be parsed."""
+
+    position: int | None
+    """Byte offset of the error in the input, when known."""
+
+class UnknownError(PreludeXmlParserError):
+    """An unknown error occurred."""
+
+class XmlElement:
+    """An element the parser does not know, kept when parsing with `keep_unknown=True`."""
+
+    def __init__(
+        self,
+        name: str,
+        attributes: dict[str, str] | None = None,
+        text: str = "",
+        children: list[XmlElement] | None = None,
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+    @property
+    def name(self) -> str: ...
+    @name.setter
+    def name(self, value: str) -> None: ...
+    @property
+    def attributes(self) -> dict[str, str]:
+        """A copy of the element's attributes; assign a changed copy back to update the element."""
+    @attributes.setter
+    def attributes(self, value: dict[str, str]) -> None: ...
+    @property
+    def text(self) -> str: ...
+    @text.setter
+    def text(self, value: str) -> None: ...
+    @property
+    def children(self) -> list[XmlElement]:
+        """A copy of the element's children; assign a changed copy back to update the element."""
+    @children.setter
+    def children(self, value: list[XmlElement]) -> None: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    @classmethod
+    def from_dict(cls, data: dict[str, Any]) -> XmlElement: ...
+    @classmethod
+    def from_json(cls, json: str) -> XmlElement: ...
+
+class Value:
+    def __init__(
+        self,
+        by: str,
+        role: str,
+        value: str,
+        by_unique_id: str | None = None,
+        when: datetime | None = None,
+        extra: dict[str, str] | None = None,
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+    @property
+    def by(self) -> str: ...
+    @by.setter
+    def by(self, value: str) -> None: ...
+    @property
+    def by_unique_id(self) -> str | None: ...
+    @by_unique_id.setter
+    def by_unique_id(self, value: str | None) -> None: ...
+    @property
+    def role(self) -> str: ...
+    @role.setter
+    def role(self, value: str) -> None: ...
+    @property
+    def when(self) -> datetime | None: ...
+    @when.setter
+    def when(self, value: datetime | None) -> None: ...
+    @property
+    def value(self) -> str: ...
+    @value.setter
+    def value(self, value: str) -> None: ...
+    @property
+    def extra(self) -> dict[str, str]:
+        """A copy of the value's unknown attributes; assign a changed copy back to update the
+        value."""
+    @extra.setter
+    def extra(self, value: dict[str, str]) -> None: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    @classmethod
+    def from_dict(cls, data: dict[str, Any]) -> Value: ...
+    @classmethod
+    def from_json(cls, json: str) -> Value: ...
+
+class Reason:
+    def __init__(
+        self,
+        by: str,
+        role: str,
+        value: str,
+        by_unique_id: str | None = None,
+        when: datetime | None = None,
+        extra: dict[str, str] | None = None,
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+    @property
+    def by(self) -> str: ...
+    @by.setter
+    def by(self, value: str) -> None: ...
+    @property
+    def by_unique_id(self) -> str | None: ...
+    @by_unique_id.setter
+    def by_unique_id(self, value: str | None) -> None: ...
+    @property
+    def role(self) -> str: ...
+    @role.setter
+    def role(self, value: str) -> None: ...
+    @property
+    def when(self) -> datetime | None: ...
+    @when.setter
+    def when(self, value: datetime | None) -> None: ...
+    @property
+    def value(self) -> str: ...
+    @value.setter
+    def value(self, value: str) -> None: ...
+    @property
+    def extra(self) -> dict[str, str]:
+        """A copy of the reason's unknown attributes; assign a changed copy back to update the
+        reason."""
+    @extra.setter
+    def extra(self, value: dict[str, str]) -> None: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    @classmethod
+    def from_dict(cls, data: dict[str, Any]) -> Reason: ...
+    @classmethod
+    def from_json(cls, json: str) -> Reason: ...
+
+class Entry:
+    def __init__(
+        self,
+        entry_id: str,
+        reviewed_by: str | None = None,
+        reviewed_by_unique_id: str | None = None,
+        reviewed_by_when: datetime | None = None,
+        value: Value | None = None,
+        reason: Reason | None = None,
+        extra: dict[str, str] | None = None,
+        extra_elements: list[XmlElement] | None = None,
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+    @property
+    def entry_id(self) -> str: ...
+    @entry_id.setter
+    def entry_id(self, value: str) -> None: ...
+    @property
+    def reviewed_by(self) -> str | None: ...
+    @reviewed_by.setter
+    def reviewed_by(self, value: str | None) -> None: ...
+    @property
+    def reviewed_by_unique_id(self) -> str | None: ...
+    @reviewed_by_unique_id.setter
+    def reviewed_by_unique_id(self, value: str | None) -> None: ...
+    @property
+    def reviewed_by_when(self) -> datetime | None: ...
+    @reviewed_by_when.setter
+    def reviewed_by_when(self, value: datetime | None) -> None: ...
+    @property
+    def value(self) -> Value | None:
+        """A copy of the entry's value; assign a changed copy back to update the entry."""
+    @value.setter
+    def value(self, value: Value | None) -> None: ...
+    @property
+    def reason(self) -> Reason | None:
+        """A copy of the entry's reason; assign a changed copy back to update the entry."""
+    @reason.setter
+    def reason(self, value: Reason | None) -> None: ...
+    @property
+    def extra(self) -> dict[str, str]:
+        """A copy of the entry's unknown attributes; assign a changed copy back to update the
+        entry."""
+    @extra.setter
+    def extra(self, value: dict[str, str]) -> None: ...
+    @property
+    def extra_elements(self) -> list[XmlElement]:
+        """A copy of the entry's unknown child elements; assign a changed copy back to update the
+        entry."""
+    @extra_elements.setter
+    def extra_elements(self, value: list[XmlElement]) -> None: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    @classmethod
+    def from_dict(cls, data: dict[str, Any]) -> Entry: ...
+    @classmethod
+    def from_json(cls, json: str) -> Entry: ...
+
+class Comment:
+    def __init__(
+        self,
+        comment_id: str,
+        value: Value | None = None,
+        replies: list[Value] | None = None,
+        reason: Reason | None = None,
+        extra: dict[str, str] | None = None,
+        extra_elements: list[XmlElement] | None = None,
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+    @property
+    def comment_id(self) -> str: ...
+    @comment_id.setter
+    def comment_id(self, value: str) -> None: ...
+    @property
+    def value(self) -> Value | None:
+        """A copy of the comment's value; assign a changed copy back to update the comment."""
+    @value.setter
+    def value(self, value: Value | None) -> None: ...
+    @property
+    def replies(self) -> list[Value] | None:
+        """A copy of the comment's replies; assign a changed copy back to update the comment."""
+    @replies.setter
+    def replies(self, value: list[Value] | None) -> None: ...
+    @property
+    def reason(self) -> Reason | None:
+        """A copy of the comment's reason; assign a changed copy back to update the comment."""
+    @reason.setter
+    def reason(self, value: Reason | None) -> None: ...
+    @property
+    def extra(self) -> dict[str, str]:
+        """A copy of the comment's unknown attributes; assign a changed copy back to update the
+        comment."""
+    @extra.setter
+    def extra(self, value: dict[str, str]) -> None: ...
+    @property
+    def extra_elements(self) -> list[XmlElement]:
+        """A copy of the comment's unknown child elements; assign a changed copy back to update the
+        comment."""
+    @extra_elements.setter
+    def extra_elements(self, value: list[XmlElement]) -> None: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    @classmethod
+    def from_dict(cls, data: dict[str, Any]) -> Comment: ...
+    @classmethod
+    def from_json(cls, json: str) -> Comment: ...
+
+class Field:
+    def __init__(
+        self,
+        name: str,
+        field_type: str,
+        error_code: str,
+        data_type: str | None = None,
+        when_created: datetime | None = None,
+        keep_history: bool = False,
+        entries: list[Entry] | None = None,
+        comments: list[Comment] | None = None,
+        extra: dict[str, str] | None = None,
+        extra_elements: list[XmlElement] | None = None,
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+    @property
+    def name(self) -> str: ...
+    @name.setter
+    def name(self, value: str) -> None: ...
+    @property
+    def field_type(self) -> str: ...
+    @field_type.setter
+    def field_type(self, value: str) -> None: ...
+    @property
+    def data_type(self) -> str | None: ...
+    @data_type.setter
+    def data_type(self, value: str | None) -> None: ...
+    @property
+    def error_code(self) -> str: ...
+    @error_code.setter
+    def error_code(self, value: str) -> None: ...
+    @property
+    def when_created(self) -> datetime | None: ...
+    @when_created.setter
+    def when_created(self, value: datetime | None) -> None: ...
+    @property
+    def keep_history(self) -> bool: ...
+    @keep_history.setter
+    def keep_history(self, value: bool) -> None: ...
+    @property
+    def entries(self) -> list[Entry] | None:
+        """A copy of the field's entries; assign a changed copy back to update the field."""
+    @entries.setter
+    def entries(self, value: list[Entry] | None) -> None: ...
+    @property
+    def comments(self) -> list[Comment] | None:
+        """A copy of the field's comments; assign a changed copy back to update the field."""
+    @comments.setter
+    def comments(self, value: list[Comment] | None) -> None: ...
+    @property
+    def repeat_index(self) -> int | None: ...
+    @property
+    def base_name(self) -> str: ...
+    @property
+    def extra(self) -> dict[str, str]:
+        """A copy of the field's unknown attributes; assign a changed copy back to update the
+        field."""
+    @extra.setter
+    def extra(self, value: dict[str, str]) -> None: ...
+    @property
+    def extra_elements(self) -> list[XmlElement]:
+        """A copy of the field's unknown child elements; assign a changed copy back to update the
+        field."""
+    @extra_elements.setter
+    def extra_elements(self, value: list[XmlElement]) -> None: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    @classmethod
+    def from_dict(cls, data: dict[str, Any]) -> Field: ...
+    @classmethod
+    def from_json(cls, json: str) -> Field: ...
+
+class Category:
+    def __init__(
+        self,
+        name: str,
+        category_type: str,
+        highest_index: int,
+        fields: list[Field] | None = None,
+        extra: dict[str, str] | None = None,
+        extra_elements: list[XmlElement] | None = None,
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+    @property
+    def name(self) -> str: ...
+    @name.setter
+    def name(self, value: str) -> None: ...
+    @property
+    def category_type(self) -> str: ...
+    @category_type.setter
+    def category_type(self, value: str) -> None: ...
+    @property
+    def highest_index(self) -> int: ...
+    @highest_index.setter
+    def highest_index(self, value: int) -> None: ...
+    @property
+    def fields(self) -> list[Field] | None:
+        """A copy of the category's fields; assign a changed copy back to update the category."""
+    @fields.setter
+    def fields(self, value: list[Field] | None) -> None: ...
+    def is_repeating(self) -> bool: ...
+    def instances(self) -> list[tuple[int | None, list[Field]]]: ...
+    @property
+    def extra(self) -> dict[str, str]:
+        """A copy of the category's unknown attributes; assign a changed copy back to update the
+        category."""
+    @extra.setter
+    def extra(self, value: dict[str, str]) -> None: ...
+    @property
+    def extra_elements(self) -> list[XmlElement]:
+        """A copy of the category's unknown child elements; assign a changed copy back to update the
+        category."""
+    @extra_elements.setter
+    def extra_elements(self, value: list[XmlElement]) -> None: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    @classmethod
+    def from_dict(cls, data: dict[str, Any]) -> Category: ...
+    @classmethod
+    def from_json(cls, json: str) -> Category: ...
+
+class State:
+    def __init__(
+        self,
+        value: str,
+        signer: str,
+        signer_unique_id: str,
+        date_signed: datetime | None = None,
+        extra: dict[str, str] | None = None,
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+    @property
+    def value(self) -> str: ...
+    @value.setter
+    def value(self, value: str) -> None: ...
+    @property
+    def signer(self) -> str: ...
+    @signer.setter
+    def signer(self, value: str) -> None: ...
+    @property
+    def signer_unique_id(self) -> str: ...
+    @signer_unique_id.setter
+    def signer_unique_id(self, value: str) -> None: ...
+    @property
+    def date_signed(self) -> datetime | None: ...
+    @date_signed.setter
+    def date_signed(self, value: datetime | None) -> None: ...
+    @property
+    def extra(self) -> dict[str, str]:
+        """A copy of the state's unknown attributes; assign a changed copy back to update the
+        state."""
+    @extra.setter
+    def extra(self, value: dict[str, str]) -> None: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    @classmethod
+    def from_dict(cls, data: dict[str, Any]) -> State: ...
+    @classmethod
+    def from_json(cls, json: str) -> State: ...
+
+class LockState:
+    def __init__(
+        self,
+        locked: bool = False,
+        user: str | None = None,
+        user_unique_id: str | None = None,
+        date_time_changed: datetime | None = None,
+        extra: dict[str, str] | None = None,
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+    @property
+    def locked(self) -> bool: ...
+    @locked.setter
+    def locked(self, value: bool) -> None: ...
+    @property
+    def user(self) -> str | None: ...
+    @user.setter
+    def user(self, value: str | None) -> None: ...
+    @property
+    def user_unique_id(self) -> str | None: ...
+    @user_unique_id.setter
+    def user_unique_id(self, value: str | None) -> None: ...
+    @property
+    def date_time_changed(self) -> datetime | None: ...
+    @date_time_changed.setter
+    def date_time_changed(self, value: datetime | None) -> None: ...
+    @property
+    def extra(self) -> dict[str, str]:
+        """A copy of the lock state's unknown attributes; assign a changed copy back to update the
+        lock state."""
+    @extra.setter
+    def extra(self, value: dict[str, str]) -> None: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    @classmethod
+    def from_dict(cls, data: dict[str, Any]) -> LockState: ...
+    @classmethod
+    def from_json(cls, json: str) -> LockState: ...
+
+class ExportInfo:
+    """The attributes of the root element of a native export."""
+
+    def __init__(
+        self,
+        created_by: str,
+        role: str,
+        number_subjects_processed: int = 0,
+        date: datetime | None = None,
+        extra: dict[str, str] | None = None,
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+    @property
+    def date(self) -> datetime | None: ...
+    @date.setter
+    def date(self, value: datetime | None) -> None: ...
+    @property
+    def created_by(self) -> str: ...
+    @created_by.setter
+    def created_by(self, value: str) -> None: ...
+    @property
+    def role(self) -> str: ...
+    @role.setter
+    def role(self, value: str) -> None: ...
+    @property
+    def number_subjects_processed(self) -> int: ...
+    @number_subjects_processed.setter
+    def number_subjects_processed(self, value: int) -> None: ...
+    @property
+    def extra(self) -> dict[str, str]:
+        """A copy of the export's unknown attributes; assign a changed copy back to update the
+        export."""
+    @extra.setter
+    def extra(self, value: dict[str, str]) -> None: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    @classmethod
+    def from_dict(cls, data: dict[str, Any]) -> ExportInfo: ...
+    @classmethod
+    def from_json(cls, json: str) -> ExportInfo: ...
+
+class Form:
+    def __init__(
+        self,
+        name: str,
+        when_created: int,
+        form_title: str,
+        form_index: int,
+        form_state: str,
+        last_modified: datetime | None = None,
+        who_last_modified_name: str | None = None,
+        who_last_modified_role: str | None = None,
+        has_errors: bool = False,
+        has_warnings: bool = False,
+        locked: bool = False,
+        user: str | None = None,
+        date_time_changed: datetime | None = None,
+        form_group: str | None = None,
+        states: list[State] | None = None,
+        lock_state: LockState | None = None,
+        categories: list[Category] | None = None,
+        extra: dict[str, str] | None = None,
+        extra_elements: list[XmlElement] | None = None,
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+    @property
+    def name(self) -> str: ...
+    @name.setter
+    def name(self, value: str) -> None: ...
+    @property
+    def last_modified(self) -> datetime | None: ...
+    @last_modified.setter
+    def last_modified(self, value: datetime | None) -> None: ...
+    @property
+    def who_last_modified_name(self) -> str | None: ...
+    @who_last_modified_name.setter
+    def who_last_modified_name(self, value: str | None) -> None: ...
+    @property
+    def who_last_modified_role(self) -> str | None: ...
+    @who_last_modified_role.setter
+    def who_last_modified_role(self, value: str | None) -> None: ...
+    @property
+    def when_created(self) -> int: ...
+    @when_created.setter
+    def when_created(self, value: int) -> None: ...
+    @property
+    def has_errors(self) -> bool: ...
+    @has_errors.setter
+    def has_errors(self, value: bool) -> None: ...
+    @property
+    def has_warnings(self) -> bool: ...
+    @has_warnings.setter
+    def has_warnings(self, value: bool) -> None: ...
+    @property
+    def locked(self) -> bool: ...
+    @locked.setter
+    def locked(self, value: bool) -> None: ...
+    @property
+    def user(self) -> str | None: ...
+    @user.setter
+    def user(self, value: str | None) -> None: ...
+    @property
+    def date_time_changed(self) -> datetime | None: ...
+    @date_time_changed.setter
+    def date_time_changed(self, value: datetime | None) -> None: ...
+    @property
+    def form_title(self) -> str: ...
+    @form_title.setter
+    def form_title(self, value: str) -> None: ...
+    @property
+    def form_index(self) -> int: ...
+    @form_index.setter
+    def form_index(self, value: int) -> None: ...
+    @property
+    def form_group(self) -> str | None: ...
+    @form_group.setter
+    def form_group(self, value: str | None) -> None: ...
+    @property
+    def form_state(self) -> str: ...
+    @form_state.setter
+    def form_state(self, value: str) -> None: ...
+    @property
+    def states(self) -> list[State] | None:
+        """A copy of the form's states; assign a changed copy back to update the form."""
+    @states.setter
+    def states(self, value: list[State] | None) -> None: ...
+    @property
+    def lock_state(self) -> LockState | None:
+        """A copy of the form's lock state; assign a changed copy back to update the form."""
+    @lock_state.setter
+    def lock_state(self, value: LockState | None) -> None: ...
+    @property
+    def categories(self) -> list[Category] | None:
+        """A copy of the form's categories; assign a changed copy back to update the form."""
+    @categories.setter
+    def categories(self, value: list[Category] | None) -> None: ...
+    @property
+    def extra(self) -> dict[str, str]:
+        """A copy of the form's unknown attributes; assign a changed copy back to update the
+        form."""
+    @extra.setter
+    def extra(self, value: dict[str, str]) -> None: ...
+    @property
+    def extra_elements(self) -> list[XmlElement]:
+        """A copy of the form's unknown child elements; assign a changed copy back to update the
+        form."""
+    @extra_elements.setter
+    def extra_elements(self, value: list[XmlElement]) -> None: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    @classmethod
+    def from_dict(cls, data: dict[str, Any]) -> Form: ...
+    @classmethod
+    def from_json(cls, json: str) -> Form: ...
+
+class Patient:
+    def __init__(
+        self,
+        patient_id: str,
+        unique_id: str,
+        creator: str,
+        site_name: str,
+        site_unique_id: str,
+        number_of_forms: int,
+        when_created: datetime | None = None,
+        last_language: str | None = None,
+        forms: list[Form] | None = None,
+        extra: dict[str, str] | None = None,
+        extra_elements: list[XmlElement] | None = None,
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+    @property
+    def patient_id(self) -> str: ...
+    @patient_id.setter
+    def patient_id(self, value: str) -> None: ...
+    @property
+    def unique_id(self) -> str: ...
+    @unique_id.setter
+    def unique_id(self, value: str) -> None: ...
+    @property
+    def when_created(self) -> datetime | None: ...
+    @when_created.setter
+    def when_created(self, value: datetime | None) -> None: ...
+    @property
+    def creator(self) -> str: ...
+    @creator.setter
+    def creator(self, value: str) -> None: ...
+    @property
+    def site_name(self) -> str: ...
+    @site_name.setter
+    def site_name(self, value: str) -> None: ...
+    @property
+    def site_unique_id(self) -> str: ...
+    @site_unique_id.setter
+    def site_unique_id(self, value: str) -> None: ...
+    @property
+    def last_language(self) -> str | None: ...
+    @last_language.setter
+    def last_language(self, value: str | None) -> None: ...
+    @property
+    def number_of_forms(self) -> int: ...
+    @number_of_forms.setter
+    def number_of_forms(self, value: int) -> None: ...
+    @property
+    def forms(self) -> list[Form] | None:
+        """A copy of the patient's forms; assign a changed copy back to update the patient."""
+    @forms.setter
+    def forms(self, value: list[Form] | None) -> None: ...
+    @property
+    def extra(self) -> dict[str, str]:
+        """A copy of the patient's unknown attributes; assign a changed copy back to update the
+        patient."""
+    @extra.setter
+    def extra(self, value: dict[str, str]) -> None: ...
+    @property
+    def extra_elements(self) -> list[XmlElement]:
+        """A copy of the patient's unknown child elements; assign a changed copy back to update the
+        patient."""
+    @extra_elements.setter
+    def extra_elements(self, value: list[XmlElement]) -> None: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    @classmethod
+    def from_dict(cls, data: dict[str, Any]) -> Patient: ...
+    @classmethod
+    def from_json(cls, json: str) -> Patient: ...
+
+class SubjectNative:
+    def __init__(
+        self,
+        patients: list[Patient],
+        export: ExportInfo | None = None,
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+    @property
+    def patients(self) -> list[Patient]:
+        """A copy of the native's patients; assign a changed copy back to update the native."""
+    @patients.setter
+    def patients(self, value: list[Patient]) -> None: ...
+    @property
+    def export(self) -> ExportInfo | None:
+        """A copy of the native's export metadata; assign a changed copy back to update the
+        native."""
+    @export.setter
+    def export(self, value: ExportInfo | None) -> None: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    @classmethod
+    def from_dict(cls, data: dict[str, Any]) -> SubjectNative: ...
+    @classmethod
+    def from_json(cls, json: str) -> SubjectNative: ...
+    def to_json(self) -> str: ...
+    def diff(self, newer: SubjectNative) -> list[dict[str, Any]]: ...
+    def to_xml(self) -> str: ...
+    def form_lifecycles(
+        self,
+        required_signatures: list[str] | None = None,
+        exempt_states: list[str] | None = None,
+        as_of: datetime | None = None,
+    ) -> list[dict[str, Any]]: ...
+    def lifecycle_summary(
+        self,
+        by: Literal["record", "site"] = "record",
+        required_signatures: list[str] | None = None,
+        exempt_states: list[str] | None = None,
+        as_of: datetime | None = None,
+    ) -> dict[str, dict[str, Any]]: ...
+    def quality_issues(self) -> list[dict[str, Any]]: ...
+    def quality_summary(
+        self, by: Literal["record", "site", "form", "field"] = "record"
+    ) -> dict[str, dict[str, Any]]: ...
+    def comments(self) -> list[dict[str, Any]]: ...
+    def metrics(self) -> dict[str, Any]: ...
+    def anonymize(self, anonymizer: Anonymizer) -> SubjectNative: ...
+    @staticmethod
+    def merge(natives: list[SubjectNative]) -> tuple[SubjectNative, list[dict[str, Any]]]: ...
+    def __arrow_c_stream__(self, requested_schema: object | None = None) -> object: ...
+    def to_pandas(self) -> pandas.DataFrame: ...
+    def to_polars(self) -> polars.DataFrame: ...
+
+class Site:
+    def __init__(
+        self,
+        name: str,
+        unique_id: str,
+        number_of_patients: int,
+        count_of_randomized_patients: int,
+        creator: str,
+        number_of_forms: int,
+        when_created: datetime | None = None,
+        forms: list[Form] | None = None,
+        extra: dict[str, str] | None = None,
+        extra_elements: list[XmlElement] | None = None,
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+    @property
+    def name(self) -> str: ...
+    @name.setter
+    def name(self, value: str) -> None: ...
+    @property
+    def unique_id(self) -> str: ...
+    @unique_id.setter
+    def unique_id(self, value: str) -> None: ...
+    @property
+    def number_of_patients(self) -> int: ...
+    @number_of_patients.setter
+    def number_of_patients(self, value: int) -> None: ...
+    @property
+    def count_of_randomized_patients(self) -> int: ...
+    @count_of_randomized_patients.setter
+    def count_of_randomized_patients(self, value: int) -> None: ...
+    @property
+    def when_created(self) -> datetime | None: ...
+    @when_created.setter
+    def when_created(self, value: datetime | None) -> None: ...
+    @property
+    def creator(self) -> str: ...
+    @creator.setter
+    def creator(self, value: str) -> None: ...
+    @property
+    def number_of_forms(self) -> int: ...
+    @number_of_forms.setter
+    def number_of_forms(self, value: int) -> None: ...
+    @property
+    def forms(self) -> list[Form] | None:
+        """A copy of the site's forms; assign a changed copy back to update the site."""
+    @forms.setter
+    def forms(self, value: list[Form] | None) -> None: ...
+    @property
+    def extra(self) -> dict[str, str]:
+        """A copy of the site's unknown attributes; assign a changed copy back to update the
+        site."""
+    @extra.setter
+    def extra(self, value: dict[str, str]) -> None: ...
+    @property
+    def extra_elements(self) -> list[XmlElement]:
+        """A copy of the site's unknown child elements; assign a changed copy back to update the
+        site."""
+    @extra_elements.setter
+    def extra_elements(self, value: list[XmlElement]) -> None: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    @classmethod
+    def from_dict(cls, data: dict[str, Any]) -> Site: ...
+    @classmethod
+    def from_json(cls, json: str) -> Site: ...
+
+class SiteNative:
+    def __init__(
+        self,
+        sites: list[Site],
+        export: ExportInfo | None = None,
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+    @property
+    def sites(self) -> list[Site]:
+        """A copy of the native's sites; assign a changed copy back to update the native."""
+    @sites.setter
+    def sites(self, value: list[Site]) -> None: ...
+    @property
+    def export(self) -> ExportInfo | None:
+        """A copy of the native's export metadata; assign a changed copy back to update the
+        native."""
+    @export.setter
+    def export(self, value: ExportInfo | None) -> None: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    @classmethod
+    def from_dict(cls, data: dict[str, Any]) -> SiteNative: ...
+    @classmethod
+    def from_json(cls, json: str) -> SiteNative: ...
+    def to_json(self) -> str: ...
+    def diff(self, newer: SiteNative) -> list[dict[str, Any]]: ...
+    def to_xml(self) -> str: ...
+    def form_lifecycles(
+        self,
+        required_signatures: list[str] | None = None,
+        exempt_states: list[str] | None = None,
+        as_of: datetime | None = None,
+    ) -> list[dict[str, Any]]: ...
+    def lifecycle_summary(
+        self,
+        by: Literal["record", "site"] = "record",
+        required_signatures: list[str] | None = None,
+        exempt_states: list[str] | None = None,
+        as_of: datetime | None = None,
+    ) -> dict[str, dict[str, Any]]: ...
+    def quality_issues(self) -> list[dict[str, Any]]: ...
+    def quality_summary(
+        self, by: Literal["record", "site", "form", "field"] = "record"
+    ) -> dict[str, dict[str, Any]]: ...
+    def comments(self) -> list[dict[str, Any]]: ...
+    @staticmethod
+    def merge(natives: list[SiteNative]) -> tuple[SiteNative, list[dict[str, Any]]]: ...
+    def __arrow_c_stream__(self, requested_schema: object | None = None) -> object: ...
+    def to_pandas(self) -> pandas.DataFrame: ...
+    def to_polars(self) -> polars.DataFrame: ...
+
+class User:
+    def __init__(
+        self,
+        unique_id: str,
+        creator: str,
+        number_of_forms: int,
+        last_language: str | None = None,
+        forms: list[Form] | None = None,
+        extra: dict[str, str] | None = None,
+        extra_elements: list[XmlElement] | None = None,
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+    @property
+    def unique_id(self) -> str: ...
+    @unique_id.setter
+    def unique_id(self, value: str) -> None: ...
+    @property
+    def last_language(self) -> str | None: ...
+    @last_language.setter
+    def last_language(self, value: str | None) -> None: ...
+    @property
+    def creator(self) -> str: ...
+    @creator.setter
+    def creator(self, value: str) -> None: ...
+    @property
+    def number_of_forms(self) -> int: ...
+    @number_of_forms.setter
+    def number_of_forms(self, value: int) -> None: ...
+    @property
+    def forms(self) -> list[Form] | None:
+        """A copy of the user's forms; assign a changed copy back to update the user."""
+    @forms.setter
+    def forms(self, value: list[Form] | None) -> None: ...
+    @property
+    def extra(self) -> dict[str, str]:
+        """A copy of the user's unknown attributes; assign a changed copy back to update the
+        user."""
+    @extra.setter
+    def extra(self, value: dict[str, str]) -> None: ...
+    @property
+    def extra_elements(self) -> list[XmlElement]:
+        """A copy of the user's unknown child elements; assign a changed copy back to update the
+        user."""
+    @extra_elements.setter
+    def extra_elements(self, value: list[XmlElement]) -> None: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    @classmethod
+    def from_dict(cls, data: dict[str, Any]) -> User: ...
+    @classmethod
+    def from_json(cls, json: str) -> User: ...
+
+class UserNative:
+    def __init__(
+        self,
+        users: list[User],
+        export: ExportInfo | None = None,
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+    @property
+    def users(self) -> list[User]:
+        """A copy of the native's users; assign a changed copy back to update the native."""
+    @users.setter
+    def users(self, value: list[User]) -> None: ...
+    @property
+    def export(self) -> ExportInfo | None:
+        """A copy of the native's export metadata; assign a changed copy back to update the
+        native."""
+    @export.setter
+    def export(self, value: ExportInfo | None) -> None: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    @classmethod
+    def from_dict(cls, data: dict[str, Any]) -> UserNative: ...
+    @classmethod
+    def from_json(cls, json: str) -> UserNative: ...
+    def to_json(self) -> str: ...
+    def diff(self, newer: UserNative) -> list[dict[str, Any]]: ...
+    def to_xml(self) -> str: ...
+    def form_lifecycles(
+        self,
+        required_signatures: list[str] | None = None,
+        exempt_states: list[str] | None = None,
+        as_of: datetime | None = None,
+    ) -> list[dict[str, Any]]: ...
+    def lifecycle_summary(
+        self,
+        by: Literal["record", "site"] = "record",
+        required_signatures: list[str] | None = None,
+        exempt_states: list[str] | None = None,
+        as_of: datetime | None = None,
+    ) -> dict[str, dict[str, Any]]: ...
+    def quality_issues(self) -> list[dict[str, Any]]: ...
+    def quality_summary(
+        self, by: Literal["record", "site", "form", "field"] = "record"
+    ) -> dict[str, dict[str, Any]]: ...
+    def comments(self) -> list[dict[str, Any]]: ...
+    def anonymize(self, anonymizer: Anonymizer) -> UserNative: ...
+    @staticmethod
+    def merge(natives: list[UserNative]) -> tuple[UserNative, list[dict[str, Any]]]: ...
+    def __arrow_c_stream__(self, requested_schema: object | None = None) -> object: ...
+    def to_pandas(self) -> pandas.DataFrame: ...
+    def to_polars(self) -> polars.DataFrame: ...
+
+class Anonymizer:
+    """De-identifies subject and user natives with rules matched against field names."""
+
+    def __init__(
+        self, salt: str, max_date_shift: int = 365, shift_timestamps: bool = False
+    ) -> None: ...
+    def rule(
+        self,
+        pattern: str,
+        action: Literal["drop", "hash", "shift_date", "year", "number_range", "prefix"],
+        size: int | None = None,
+    ) -> None: ...
+    def pseudonym(self, text: str) -> str: ...
+    def date_offset(self, unique_id: str) -> int: ...
+
+class Study:
+    """The subject and site natives of a study, joined on `Patient.site_unique_id`."""
+
+    def __init__(self, subject_native: SubjectNative, site_native: SiteNative) -> None: ...
+    @property
+    def subject_native(self) -> SubjectNative:
+        """A copy of the study's subject native; changing it does not change the study."""
+    @property
+    def site_native(self) -> SiteNative:
+        """A copy of the study's site native; changing it does not change the study."""
+    def sites(self) -> list[tuple[Site, list[Patient]]]: ...
+    def unassigned_patients(self) -> list[Patient]: ...
+    def check_enrollment(self) -> list[dict[str, Any]]: ...
+    def link_users(self, user_native: UserNative) -> dict[str, Any]: ...
+
+class PatientIterator(Iterator[Patient]):
+    """Lazily parses one patient at a time, releasing the GIL while parsing."""
+
+    def __iter__(self) -> PatientIterator: ...
+    def __next__(self) -> Patient: ...
+
+def parse_subject_native_file(
+    xml_path: str | PathLike[str], *, keep_unknown: bool = False
+) -> SubjectNative: ...
+def parse_subject_native_string(
+    xml_str: str, *, keep_unknown: bool = False
+) -> SubjectNative: ...
+def parse_subject_native_bytes(
+    xml_bytes: bytes, *, keep_unknown: bool = False
+) -> SubjectNative: ...
+def parse_site_native_file(
+    xml_path: str | PathLike[str], *, keep_unknown: bool = False
+) -> SiteNative: ...
+def parse_site_native_string(
+    xml_str: str, *, keep_unknown: bool = False
+) -> SiteNative: ...
+def parse_site_native_bytes(
+    xml_bytes: bytes, *, keep_unknown: bool = False
+) -> SiteNative: ...
+def parse_user_native_file(
+    xml_path: str | PathLike[str], *, keep_unknown: bool = False
+) -> UserNative: ...
+def parse_user_native_string(
+    xml_str: str, *, keep_unknown: bool = False
+) -> UserNative: ...
+def parse_user_native_bytes(
+    xml_bytes: bytes, *, keep_unknown: bool = False
+) -> UserNative: ...
+def iter_subject_native_patients_file(xml_path: str | PathLike[str]) -> PatientIterator: ...
+def iter_subject_native_patients(xml_str: str) -> PatientIterator: ...
+def write_subject_native_ndjson(
+    xml_path: str | PathLike[str], ndjson_path: str | PathLike[str], *, entries: bool = False
+) -> int: ...
+def write_site_native_ndjson(
+    xml_path: str | PathLike[str], ndjson_path: str | PathLike[str], *, entries: bool = False
+) -> int: ...
+def write_user_native_ndjson(
+    xml_path: str | PathLike[str], ndjson_path: str | PathLike[str], *, entries: bool = False
+) -> int: ...
+def link_users(
+    subject_native: SubjectNative, site_native: SiteNative, user_native: UserNative
+) -> dict[str, Any]: ...
@@ -0,0 +1,315 @@
+use futures_util::stream::{self, Stream};
+use quick_xml::{events::Event, Reader};
+use tokio::io::AsyncBufRead;
+
+use crate::{
+    errors::Error,
+    native::{
+        common::{ExportInfo, NativeRecord},
+        site_native::{Site, SiteNative},
+        subject_native::{Patient, SubjectNative},
+        user_native::{User, UserNative},
+    },
+    parse_export_info, parse_record, ParseOptions,
+};
+
+/// Parses Prelude native subject XML from an async reader into a `SubjectNative` struct.
+///
+/// The input is read as it arrives and each patient is parsed as soon as it is complete, so the
+/// whole document is never buffered.
+///
+/// # Example
+///
+/// ```
+/// use prelude_xml_parser::parse_subject_native_async;
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let xml = std::fs::read("tests/assets/subject_native.xml").unwrap();
+/// let native = parse_subject_native_async(xml.as_slice()).await.unwrap();
+///
+/// assert_eq!(native.patients.len(), 2);
+/// # });
+/// ```
+pub async fn parse_subject_native_async<B: AsyncBufRead + Unpin>(
+    reader: B,
+) -> Result<SubjectNative, Error> {
+    parse_subject_native_async_with_options(reader, &ParseOptions::default()).await
+}
+
+/// Parses Prelude native subject XML from an async reader like [`parse_subject_native_async`],
+/// with `options`.
+pub async fn parse_subject_native_async_with_options<B: AsyncBufRead + Unpin>(
+    reader: B,
+    options: &ParseOptions,
+) -> Result<SubjectNative, Error> {
+    let (patients, export) = read_all(AsyncRecordReader::new(reader, options)).await?;
+    Ok(SubjectNative { patients, export })
+}
+
+/// Parses Prelude native site XML from an async reader into a `SiteNative` struct.
+pub async fn parse_site_native_async<B: AsyncBufRead + Unpin>(
+    reader: B,
+) -> Result<SiteNative, Error> {
+    parse_site_native_async_with_options(reader, &ParseOptions::default()).await
+}
+
+/// Parses Prelude native site XML from an async reader like [`parse_site_native_async`], with
+/// `options`.
+pub async fn parse_site_native_async_with_options<B: AsyncBufRead + Unpin>(
+    reader: B,
+    options: &ParseOptions,
+) -> Result<SiteNative, Error> {
+    let (sites, export) = read_all(AsyncRecordReader::new(reader, options)).await?;
+    Ok(SiteNative { sites, export })
+}
+
+/// Parses Prelude native user XML from an async reader into a `UserNative` struct.
+pub async fn parse_user_native_async<B: AsyncBufRead + Unpin>(
+    reader: B,
+) -> Result<UserNative, Error> {
+    parse_user_native_async_with_options(reader, &ParseOptions::default()).await
+}
+
+/// Parses Prelude native user XML from an async reader like [`parse_user_native_async`], with
+/// `options`.
+pub async fn parse_user_native_async_with_options<B: AsyncBufRead + Unpin>(
+    reader: B,
+    options: &ParseOptions,
+) -> Result<UserNative, Error> {
+    let (users, export) = read_all(AsyncRecordReader::new(reader, options)).await?;
+    Ok(UserNative { users, export })
+}
+
+/// Streams the patients of Prelude native subject XML from an async reader, one at a time.
+///
+/// # Example
+///
+/// ```
+/// use futures_util::TryStreamExt;
+/// use prelude_xml_parser::stream_subject_native_patients;
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let xml = std::fs::read("tests/assets/subject_native.xml").unwrap();
+/// let patient_ids = stream_subject_native_patients(xml.as_slice())
+///     .map_ok(|p| p.patient_id)
+///     .try_collect::<Vec<_>>()
+///     .await
+///     .unwrap();
+///
+/// assert_eq!(patient_ids, vec!["ABC-001", "ABC-002"]);
+/// # });
+/// ```
+pub fn stream_subject_native_patients<B: AsyncBufRead + Unpin>(
+    reader: B,
+) -> impl Stream<Item = Result<Patient, Error>> {
+    stream_native_records(reader, &ParseOptions::default())
+}
+
+/// Streams the sites of Prelude native site XML from an async reader, one at a time.
+pub fn stream_site_native_sites<B: AsyncBufRead + Unpin>(
+    reader: B,
+) -> impl Stream<Item = Result<Site, Error>> {
+    stream_native_records(reader, &ParseOptions::default())
+}
+
+/// Streams the users of Prelude native user XML from an async reader, one at a time.
+pub fn stream_user_native_users<B: AsyncBufRead + Unpin>(
+    reader: B,
+) -> impl Stream<Item = Result<User, Error>> {
+    stream_native_records(reader, &ParseOptions::default())
+}
+
+/// Streams the `<R::TAG ...>` records of Prelude native XML from an async reader, one at a time.
+///
+/// This is the engine behind the `stream_*` functions. The stream ends after the first error.
+pub fn stream_native_records<B: AsyncBufRead + Unpin, R: NativeRecord>(
+    reader: B,
+    options: &ParseOptions,
+) -> impl Stream<Item = Result<R, Error>> {
+    stream::try_unfold(
+        AsyncRecordReader::new(reader, options),
+        |mut records| async move { Ok(records.next_record().await?.map(|record| (record, records))) },
+    )
+}
+
+async fn read_all<B: AsyncBufRead + Unpin, R: NativeRecord>(
+    mut records: AsyncRecordReader<B>,
+) -> Result<(Vec<R>, Option<ExportInfo>), Error> {
+    let mut all = Vec::new();
+    while let Some(record) = records.next_record().await? {
+        all.push(record);
+    }
+
+    Ok((all, records.export))
+}
+
+/// Reads the record chunks of a native from an async reader and parses each with the same engine
+/// as the string parsers.
+struct AsyncRecordReader<B> {
+    reader: Reader<B>,
+    buf: Vec<u8>,
+    options: ParseOptions,
+    /// The attributes of the `<export_from_vision_EDC>` root element, once it has been read.
+    export: Option<ExportInfo>,
+    seen_root: bool,
+}
+
+impl<B: AsyncBufRead + Unpin> AsyncRecordReader<B> {
+    fn new(reader: B, options: &ParseOptions) -> Self {
+        AsyncRecordReader {
+            reader: Reader::from_reader(reader),
+            buf: Vec::new(),
+            options: *options,
+            export: None,
+            seen_root: false,
+        }
+    }
+
+    async fn next_record<R: NativeRecord>(&mut self) -> Result<Option<R>, Error> {
+        loop {
+            let position = self.reader.buffer_position();
+            self.buf.clear();
+            let start = match self.reader.read_event_into_async(&mut self.buf).await {
+                Err(e) => return Err(self.xml_error(e)),
+                Ok(Event::Eof) => return Ok(None),
+                Ok(Event::Start(e)) => e,
+                Ok(Event::Empty(e)) if !self.seen_root => {
+                    self.seen_root = true;
+                    let root = format!("<{}/>", to_str(&e, position)?);
+                    self.export = parse_export_info(&root, &self.options)?;
+                    continue;
+                }
+                Ok(_) => continue,
+            };
+
+            let start_tag = format!("<{}>", to_str(&start, position)?);
+            if !self.seen_root {
+                self.seen_root = true;
+                self.export = parse_export_info(&start_tag, &self.options)?;
+            }
+            if start.local_name().as_ref() != R::TAG.as_bytes() {
+                continue;
+            }
+
+            // The chunk is the record as it is in the document, so positions within it only need
+            // to be moved by where it starts
+            let end = start.to_end().into_owned();
+            let mut content = Vec::new();
+            let text = self
+                .reader
+                .read_text_into_async(end.name(), &mut content)
+                .await
+                .map_err(|e| self.xml_error(e))?;
+            let chunk = format!(
+                "{start_tag}{}</{}>",
+                to_str(&text, position)?,
+                to_str(&end, position)?
+            );
+
+            return parse_record(&chunk, &self.options)
+                .map(Some)
+                .map_err(|e| e.offset_by(position as usize));
+        }
+    }
+
+    fn xml_error(&self, e: quick_xml::Error) -> Error {
+        Error::XmlError {
+            position: self.reader.error_position(),
+            message: e.to_string(),
+        }
+    }
+}
+
+/// The bytes of an event as a string, failing like reading a file to a string does.
+fn to_str(bytes: &[u8], position: u64) -> Result<&str, Error> {
+    std::str::from_utf8(bytes).map_err(|_| {
+        Error::IO(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Stream did not contain valid UTF-8 after position {position}"),
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use futures_util::TryStreamExt;
+    use tokio::io::BufReader;
+
+    use super::*;
+    use crate::{
+        parse_site_native_file, parse_subject_native_file, parse_subject_native_file_with_options,
+        parse_user_native_file,
+    };
+
+    #[tokio::test]
+    async fn parses_the_same_natives_as_the_string_parsers() {
+        let xml = std::fs::read("tests/assets/subject_native.xml").unwrap();
+        // A small buffer splits elements across reads
+        let native = parse_subject_native_async(BufReader::with_capacity(7, xml.as_slice()))
+            .await
+            .unwrap();
+        assert_eq!(
+            native,
+            parse_subject_native_file(Path::new("tests/assets/subject_native.xml")).unwrap()
+        );
+
+        let xml = std::fs::read("tests/assets/site_native.xml").unwrap();
+        assert_eq!(
+            parse_site_native_async(xml.as_slice()).await.unwrap(),
+            parse_site_native_file(Path::new("tests/assets/site_native.xml")).unwrap()
+        );
+
+        let xml = std::fs::read("tests/assets/user_native.xml").unwrap();
+        assert_eq!(
+            parse_user_native_async(xml.as_slice()).await.unwrap(),
+            parse_user_native_file(Path::new("tests/assets/user_native.xml")).unwrap()
+        );
+
+        let path = Path::new("tests/assets/subject_native_unknown.xml");
+        let xml = std::fs::read(path).unwrap();
+        let options = ParseOptions { keep_unknown: true };
+        assert_eq!(
+            parse_subject_native_async_with_options(xml.as_slice(), &options)
+                .await
+                .unwrap(),
+            parse_subject_native_file_with_options(path, &options).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn streams_records() {
+        let xml = std::fs::read("tests/assets/site_native.xml").unwrap();
+        let names = stream_site_native_sites(xml.as_slice())
+            .map_ok(|site| site.name)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+
+        assert_eq!(names, vec!["Some Site", "Artemis"]);
+    }
+
+    #[tokio::test]
+    async fn reports_errors_at_their_position_in_the_document() {
+        let xml = r#"<export_from_vision_EDC>
+  <patient patientId="ABC-001" uniqueId="1" creator="" siteName="" siteUniqueId="" numberOfForms="0"></patient>
+  <patient patientId="ABC-002" uniqueId="2" creator="" siteName="" siteUniqueId="" numberOfForms="0">
+    <form name="demographics"></category>
+  </patient>
+</export_from_vision_EDC>"#;
+
+        let mut patients = std::pin::pin!(stream_subject_native_patients(xml.as_bytes()));
+        assert_eq!(
+            patients.try_next().await.unwrap().unwrap().patient_id,
+            "ABC-001"
+        );
+        match patients.try_next().await {
+            Err(Error::XmlError { position, .. }) => {
+                assert_eq!(position as usize, xml.find("</category>").unwrap());
+            }
+            other => panic!("Expected an XmlError, got {:?}", other),
+        }
+        assert!(patients.try_next().await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,848 @@
+//! Command-line tool for converting, inspecting, validating, and querying Prelude native XML
+//! exports.
+//!
+//! Exit codes: `0` on success, `1` when the input is not a valid native, `2` for usage errors,
+//! and `3` when the input or output cannot be read or written.
+
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{error::ErrorKind, CommandFactory, Parser, Subcommand, ValueEnum};
+use parquet::{arrow::ArrowWriter, errors::ParquetError};
+use quick_xml::{events::Event, Reader};
+use serde::Serialize;
+
+use prelude_xml_parser::{
+    errors::Error,
+    iter_site_native_sites, iter_subject_native_patients, iter_user_native_users,
+    native::{
+        common::Form,
+        flat::{flat_entries_schema, flat_entries_to_record_batch, FlatEntry},
+        ndjson::{
+            write_site_native_ndjson, write_subject_native_ndjson, write_user_native_ndjson,
+            NdjsonLines,
+        },
+        site_native::Site,
+        subject_native::Patient,
+        user_native::User,
+    },
+    parse_site_native_string, parse_subject_native_string, parse_user_native_string,
+};
+
+const EXIT_INVALID: u8 = 1;
+const EXIT_IO: u8 = 3;
+
+#[derive(Debug, Parser)]
+#[command(name = "prelude-xml", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+impl Cli {
+    /// Rejects flag combinations clap cannot express, as a usage error of the subcommand.
+    fn check(&self) -> Result<(), clap::Error> {
+        let message = match self.command {
+            Command::Convert {
+                to, entries: true, ..
+            } if to != Format::Ndjson => "`--entries` can only be used with `--to ndjson`",
+            _ => return Ok(()),
+        };
+        let mut command = Cli::command();
+        command.build();
+        let subcommand = command
+            .find_subcommand_mut("convert")
+            .expect("declared above");
+        Err(subcommand.error(ErrorKind::ArgumentConflict, message))
+    }
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Convert a native to JSON, NDJSON, CSV, or Parquet.
+    Convert {
+        #[command(flatten)]
+        input: Input,
+
+        /// The output format.
+        #[arg(long, short = 't', value_enum)]
+        to: Format,
+
+        /// Write one NDJSON line per field entry instead of one per record. Only valid with
+        /// `--to ndjson`; CSV and Parquet always have one row per field entry.
+        #[arg(long)]
+        entries: bool,
+
+        /// Write to this file instead of stdout.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+
+    /// Print the number of records, forms, fields, and entries in a native.
+    Inspect {
+        #[command(flatten)]
+        input: Input,
+
+        /// Print the counts as a JSON object.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Check that every record of a native parses, reporting each invalid record.
+    Validate {
+        #[command(flatten)]
+        input: Input,
+    },
+
+    /// Print the field entries of a form or field as NDJSON or CSV.
+    Query {
+        #[command(flatten)]
+        input: Input,
+
+        /// Only print entries of forms with this name or title.
+        #[arg(long)]
+        form: Option<String>,
+
+        /// Only print entries of fields with this name.
+        #[arg(long)]
+        field: Option<String>,
+
+        /// The output format.
+        #[arg(long, value_enum, default_value_t = QueryFormat::Ndjson)]
+        format: QueryFormat,
+    },
+}
+
+#[derive(Debug, clap::Args)]
+struct Input {
+    /// The native XML file. Reads stdin when omitted or `-`.
+    input: Option<PathBuf>,
+
+    /// The type of native. Detected from the first record when omitted.
+    #[arg(long = "type", value_enum)]
+    native_type: Option<NativeType>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+enum NativeType {
+    Subject,
+    Site,
+    User,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Json,
+    Ndjson,
+    Csv,
+    Parquet,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum QueryFormat {
+    Ndjson,
+    Csv,
+}
+
+#[derive(Debug)]
+enum CliError {
+    /// The input is not a valid native.
+    Invalid(String),
+
+    /// The input or output could not be read or written.
+    Io(io::Error),
+
+    /// The output could not be encoded.
+    Output(String),
+}
+
+impl CliError {
+    fn exit_code(&self) -> ExitCode {
+        match self {
+            CliError::Invalid(_) => ExitCode::from(EXIT_INVALID),
+            CliError::Io(_) | CliError::Output(_) => ExitCode::from(EXIT_IO),
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Invalid(message) | CliError::Output(message) => f.write_str(message),
+            CliError::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(e: io::Error) -> Self {
+        CliError::Io(e)
+    }
+}
+
+impl From<Error> for CliError {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::IO(e) => CliError::Io(e),
+            e => CliError::Invalid(e.to_string()),
+        }
+    }
+}
+
+impl From<csv::Error> for CliError {
+    fn from(e: csv::Error) -> Self {
+        let message = e.to_string();
+        match e.into_kind() {
+            csv::ErrorKind::Io(e) => CliError::Io(e),
+            _ => CliError::Output(message),
+        }
+    }
+}
+
+impl From<ParquetError> for CliError {
+    fn from(e: ParquetError) -> Self {
+        match e {
+            ParquetError::External(e) => match e.downcast::<io::Error>() {
+                Ok(e) => CliError::Io(*e),
+                Err(e) => CliError::Output(e.to_string()),
+            },
+            e => CliError::Output(e.to_string()),
+        }
+    }
+}
+
+impl From<arrow_schema::ArrowError> for CliError {
+    fn from(e: arrow_schema::ArrowError) -> Self {
+        CliError::Output(e.to_string())
+    }
+}
+
+/// A record of any native, so the subcommands can be written once.
+trait Record: Serialize {
+    fn forms(&self) -> Option<&Vec<Form>>;
+
+    fn flat_entries(&self) -> Vec<FlatEntry<'_>>;
+}
+
+impl Record for Patient {
+    fn forms(&self) -> Option<&Vec<Form>> {
+        self.forms.as_ref()
+    }
+
+    fn flat_entries(&self) -> Vec<FlatEntry<'_>> {
+        Patient::flat_entries(self)
+    }
+}
+
+impl Record for Site {
+    fn forms(&self) -> Option<&Vec<Form>> {
+        self.forms.as_ref()
+    }
+
+    fn flat_entries(&self) -> Vec<FlatEntry<'_>> {
+        Site::flat_entries(self)
+    }
+}
+
+impl Record for User {
+    fn forms(&self) -> Option<&Vec<Form>> {
+        self.forms.as_ref()
+    }
+
+    fn flat_entries(&self) -> Vec<FlatEntry<'_>> {
+        User::flat_entries(self)
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    if let Err(e) = cli.check() {
+        e.exit();
+    }
+
+    match run(cli.command) {
+        Ok(code) => code,
+        // The reader went away, e.g. `prelude-xml convert ... | head`.
+        Err(CliError::Io(e)) if e.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("prelude-xml: {e}");
+            e.exit_code()
+        }
+    }
+}
+
+fn run(command: Command) -> Result<ExitCode, CliError> {
+    match command {
+        Command::Convert {
+            input,
+            to,
+            entries,
+            output,
+        } => {
+            let (xml, native_type) = input.read()?;
+            convert(
+                &xml,
+                native_type,
+                to,
+                entries,
+                open_output(output.as_deref())?,
+            )?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Inspect { input, json } => {
+            let (xml, native_type) = input.read()?;
+            let counts = match native_type {
+                NativeType::Subject => Counts::of(native_type, iter_subject_native_patients(&xml))?,
+                NativeType::Site => Counts::of(native_type, iter_site_native_sites(&xml))?,
+                NativeType::User => Counts::of(native_type, iter_user_native_users(&xml))?,
+            };
+            let mut out = io::stdout().lock();
+            if json {
+                serde_json::to_writer(&mut out, &counts).map_err(io::Error::from)?;
+                writeln!(out)?;
+            } else {
+                counts.print(&mut out)?;
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Validate { input } => {
+            let (xml, native_type) = input.read()?;
+            let source = input.name();
+            let result = match native_type {
+                NativeType::Subject => validate(&source, &xml, iter_subject_native_patients(&xml)),
+                NativeType::Site => validate(&source, &xml, iter_site_native_sites(&xml)),
+                NativeType::User => validate(&source, &xml, iter_user_native_users(&xml)),
+            };
+            match result {
+                Ok(count) => {
+                    writeln!(
+                        io::stdout().lock(),
+                        "{source}: valid {} native with {count} {}",
+                        native_type.name(),
+                        native_type.record_label()
+                    )?;
+                    Ok(ExitCode::SUCCESS)
+                }
+                Err(errors) => {
+                    for error in &errors {
+                        eprintln!("{error}");
+                    }
+                    eprintln!("prelude-xml: {} error(s)", errors.len());
+                    Ok(ExitCode::from(EXIT_INVALID))
+                }
+            }
+        }
+        Command::Query {
+            input,
+            form,
+            field,
+            format,
+        } => {
+            let (xml, native_type) = input.read()?;
+            let filter = Filter {
+                form: form.as_deref(),
+                field: field.as_deref(),
+            };
+            let out = BufWriter::new(io::stdout().lock());
+            match native_type {
+                NativeType::Subject => {
+                    query(iter_subject_native_patients(&xml), filter, format, out)
+                }
+                NativeType::Site => query(iter_site_native_sites(&xml), filter, format, out),
+                NativeType::User => query(iter_user_native_users(&xml), filter, format, out),
+            }?;
+            Ok(ExitCode::SUCCESS)
+        }
+    }
+}
+
+impl Input {
+    /// Reads the whole input and works out the type of native it holds.
+    fn read(&self) -> Result<(String, NativeType), CliError> {
+        let mut xml = String::new();
+        match self.path() {
+            Some(path) => {
+                File::open(path)
+                    .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))?
+                    .read_to_string(&mut xml)?;
+            }
+            None => {
+                io::stdin().lock().read_to_string(&mut xml)?;
+            }
+        }
+
+        let native_type = match self.native_type {
+            Some(native_type) => native_type,
+            None => detect_native_type(&xml).ok_or_else(|| {
+                CliError::Invalid(format!(
+                    "{}: could not detect the native type, no <patient>, <site>, or <user> \
+                     found; pass --type",
+                    self.name()
+                ))
+            })?,
+        };
+
+        Ok((xml, native_type))
+    }
+
+    fn path(&self) -> Option<&Path> {
+        self.input.as_deref().filter(|path| *path != Path::new("-"))
+    }
+
+    /// The name used for the input in diagnostics.
+    fn name(&self) -> String {
+        self.path()
+            .map_or_else(|| "<stdin>".to_string(), |path| path.display().to_string())
+    }
+}
+
+impl NativeType {
+    fn name(self) -> &'static str {
+        match self {
+            NativeType::Subject => "subject",
+            NativeType::Site => "site",
+            NativeType::User => "user",
+        }
+    }
+
+    fn record_label(self) -> &'static str {
+        match self {
+            NativeType::Subject => "patients",
+            NativeType::Site => "sites",
+            NativeType::User => "users",
+        }
+    }
+}
+
+/// Detects the type of native from the first record element in `xml`.
+fn detect_native_type(xml: &str) -> Option<NativeType> {
+    [
+        ("<patient ", NativeType::Subject),
+        ("<site ", NativeType::Site),
+        ("<user ", NativeType::User),
+    ]
+    .into_iter()
+    .filter_map(|(tag, native_type)| xml.find(tag).map(|pos| (pos, native_type)))
+    .min_by_key(|(pos, _)| *pos)
+    .map(|(_, native_type)| native_type)
+}
+
+fn open_output(path: Option<&Path>) -> Result<Box<dyn Write + Send>, CliError> {
+    Ok(match path {
+        Some(path) => {
+            Box::new(BufWriter::new(File::create(path).map_err(|e| {
+                io::Error::new(e.kind(), format!("{}: {e}", path.display()))
+            })?))
+        }
+        None => Box::new(BufWriter::new(io::stdout())),
+    })
+}
+
+fn convert(
+    xml: &str,
+    native_type: NativeType,
+    to: Format,
+    entries: bool,
+    mut out: Box<dyn Write + Send>,
+) -> Result<(), CliError> {
+    match to {
+        Format::Json => {
+            let json = match native_type {
+                NativeType::Subject => parse_subject_native_string(xml)?.to_json(),
+                NativeType::Site => parse_site_native_string(xml)?.to_json(),
+                NativeType::User => parse_user_native_string(xml)?.to_json(),
+            }
+            .map_err(|e| CliError::Output(e.to_string()))?;
+            writeln!(out, "{json}")?;
+            out.flush()?;
+        }
+        Format::Ndjson => {
+            let lines = if entries {
+                NdjsonLines::Entries
+            } else {
+                NdjsonLines::Records
+            };
+            match native_type {
+                NativeType::Subject => write_subject_native_ndjson(xml, out, lines),
+                NativeType::Site => write_site_native_ndjson(xml, out, lines),
+                NativeType::User => write_user_native_ndjson(xml, out, lines),
+            }?;
+        }
+        Format::Csv => {
+            let filter = Filter::default();
+            match native_type {
+                NativeType::Subject => write_csv(iter_subject_native_patients(xml), filter, out),
+                NativeType::Site => write_csv(iter_site_native_sites(xml), filter, out),
+                NativeType::User => write_csv(iter_user_native_users(xml), filter, out),
+            }?;
+        }
+        Format::Parquet => match native_type {
+            NativeType::Subject => write_parquet(iter_subject_native_patients(xml), out),
+            NativeType::Site => write_parquet(iter_site_native_sites(xml), out),
+            NativeType::User => write_parquet(iter_user_native_users(xml), out),
+        }?,
+    }
+
+    Ok(())
+}
+
+/// Selects the field entries printed by `query`. `None` matches everything.
+#[derive(Clone, Copy, Debug, Default)]
+struct Filter<'a> {
+    form: Option<&'a str>,
+    field: Option<&'a str>,
+}
+
+impl Filter<'_> {
+    fn matches(&self, entry: &FlatEntry<'_>) -> bool {
+        self.form
+            .is_none_or(|form| entry.form_name == form || entry.form_title == form)
+            && self.field.is_none_or(|field| entry.field_name == field)
+    }
+}
+
+fn query<R: Record, W: Write>(
+    records: impl Iterator<Item = Result<R, Error>>,
+    filter: Filter<'_>,
+    format: QueryFormat,
+    mut out: W,
+) -> Result<(), CliError> {
+    match format {
+        QueryFormat::Csv => write_csv(records, filter, out),
+        QueryFormat::Ndjson => {
+            for record in records {
+                let record = record?;
+                for entry in record.flat_entries().iter().filter(|e| filter.matches(e)) {
+                    serde_json::to_writer(&mut out, entry).map_err(io::Error::from)?;
+                    writeln!(out)?;
+                }
+            }
+            out.flush()?;
+            Ok(())
+        }
+    }
+}
+
+/// Writes one CSV row per field entry, with the columns of the flat Arrow schema.
+fn write_csv<R: Record, W: Write>(
+    records: impl Iterator<Item = Result<R, Error>>,
+    filter: Filter<'_>,
+    out: W,
+) -> Result<(), CliError> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(out);
+    writer.write_record(flat_entries_schema().fields().iter().map(|f| f.name()))?;
+    for record in records {
+        let record = record?;
+        for entry in record.flat_entries().iter().filter(|e| filter.matches(e)) {
+            writer.serialize(entry)?;
+        }
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Writes one Parquet row per field entry. Each record is converted and handed to the writer on
+/// its own; the writer groups them into row groups.
+fn write_parquet<R: Record, W: Write + Send>(
+    records: impl Iterator<Item = Result<R, Error>>,
+    out: W,
+) -> Result<(), CliError> {
+    let mut writer = ArrowWriter::try_new(out, flat_entries_schema(), None)?;
+    for record in records {
+        let record = record?;
+        writer.write(&flat_entries_to_record_batch(&record.flat_entries())?)?;
+    }
+    writer.into_inner()?.flush()?;
+
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+struct Counts {
+    #[serde(rename = "type")]
+    native_type: NativeType,
+    records: usize,
+    forms: usize,
+    fields: usize,
+    entries: usize,
+}
+
+impl Counts {
+    fn of<R: Record>(
+        native_type: NativeType,
+        records: impl Iterator<Item = Result<R, Error>>,
+    ) -> Result<Self, Error> {
+        let mut counts = Counts {
+            native_type,
+            records: 0,
+            forms: 0,
+            fields: 0,
+            entries: 0,
+        };
+        for record in records {
+            let record = record?;
+            counts.records += 1;
+            for form in record.forms().into_iter().flatten() {
+                counts.forms += 1;
+                for field in form
+                    .categories
+                    .iter()
+                    .flatten()
+                    .flat_map(|category| category.fields.iter().flatten())
+                {
+                    counts.fields += 1;
+                    counts.entries += field.entries.as_ref().map_or(0, Vec::len);
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    fn print(&self, out: &mut impl Write) -> io::Result<()> {
+        let label = format!("{}:", self.native_type.record_label());
+        writeln!(out, "type:     {}", self.native_type.name())?;
+        writeln!(out, "{label:<9} {}", self.records)?;
+        writeln!(out, "forms:    {}", self.forms)?;
+        writeln!(out, "fields:   {}", self.fields)?;
+        writeln!(out, "entries:  {}", self.entries)
+    }
+}
+
+/// Checks that the document is well-formed and parses every record, returning the number of
+/// records or a `source:line:column: message` diagnostic for each error.
+fn validate<R>(
+    source: &str,
+    xml: &str,
+    records: impl Iterator<Item = Result<R, Error>>,
+) -> Result<usize, Vec<String>> {
+    let diagnostic = |position: usize, message: &str| {
+        let (line, column) = line_and_column(xml, position);
+        format!("{source}:{line}:{column}: {message}")
+    };
+
+    let mut count = 0;
+    let mut errors = Vec::new();
+    for record in records {
+        match record {
+            Ok(_) => count += 1,
+            Err(Error::XmlError { position, message }) => {
+                errors.push(diagnostic(position as usize, &message));
+            }
+            Err(e) => errors.push(format!("{source}: {e}")),
+        }
+    }
+    // Records are only read once they are closed, so e.g. a truncated file is only caught here
+    if let Err((position, message)) = check_document(xml) {
+        let error = diagnostic(position, &message);
+        if !errors.contains(&error) {
+            errors.push(error);
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(count)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Reads the whole document, returning the position and message of the first well-formedness
+/// error, including an element that is never closed.
+fn check_document(xml: &str) -> Result<(), (usize, String)> {
+    let mut reader = Reader::from_str(xml);
+    let mut open = Vec::new();
+
+    loop {
+        let position = reader.buffer_position() as usize;
+        match reader.read_event() {
+            Err(e) => return Err((reader.error_position() as usize, e.to_string())),
+            Ok(Event::Start(e)) => {
+                let name = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+                open.push((position, name));
+            }
+            Ok(Event::End(_)) => {
+                open.pop();
+            }
+            Ok(Event::Eof) => {
+                return match open.pop() {
+                    Some((position, name)) => Err((position, format!("Unclosed element <{name}>"))),
+                    None => Ok(()),
+                }
+            }
+            Ok(_) => (),
+        }
+    }
+}
+
+/// The 1-based line and column of the byte `position` in `xml`.
+fn line_and_column(xml: &str, position: usize) -> (usize, usize) {
+    let before = &xml.as_bytes()[..position.min(xml.len())];
+    let line = before.iter().filter(|b| **b == b'\n').count() + 1;
+    let line_start = before
+        .iter()
+        .rposition(|b| *b == b'\n')
+        .map_or(0, |i| i + 1);
+
+    (line, before.len() - line_start + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use super::*;
+
+    #[test]
+    fn detects_native_type() {
+        for (path, expected) in [
+            ("tests/assets/subject_native.xml", NativeType::Subject),
+            ("tests/assets/site_native.xml", NativeType::Site),
+            ("tests/assets/user_native.xml", NativeType::User),
+        ] {
+            let xml = read_to_string(path).unwrap();
+            assert_eq!(detect_native_type(&xml), Some(expected), "{path}");
+        }
+
+        assert_eq!(detect_native_type("<export_from_vision_EDC />"), None);
+    }
+
+    #[test]
+    fn rejects_entries_unless_converting_to_ndjson() {
+        let parse = |to| {
+            Cli::try_parse_from([
+                "prelude-xml",
+                "convert",
+                "native.xml",
+                "--to",
+                to,
+                "--entries",
+            ])
+            .unwrap()
+        };
+
+        assert!(parse("ndjson").check().is_ok());
+        for to in ["json", "csv", "parquet"] {
+            let e = parse(to).check().unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::ArgumentConflict, "{to}");
+            assert_eq!(e.exit_code(), 2, "{to}");
+        }
+    }
+
+    #[test]
+    fn counts_records() {
+        let xml = read_to_string("tests/assets/subject_native.xml").unwrap();
+
+        let counts = Counts::of(NativeType::Subject, iter_subject_native_patients(&xml)).unwrap();
+
+        // The two self-closing fields of the fixture are left out of subject natives
+        assert_eq!(
+            counts,
+            Counts {
+                native_type: NativeType::Subject,
+                records: 2,
+                forms: 4,
+                fields: 11,
+                entries: 11,
+            }
+        );
+    }
+
+    #[test]
+    fn queries_entries_by_form_and_field() {
+        let xml = read_to_string("tests/assets/subject_native.xml").unwrap();
+        let filter = Filter {
+            form: None,
+            field: Some("breed"),
+        };
+
+        let mut out = Vec::new();
+        query(
+            iter_subject_native_patients(&xml),
+            filter,
+            QueryFormat::Ndjson,
+            &mut out,
+        )
+        .unwrap();
+        let lines = String::from_utf8(out).unwrap();
+
+        assert!(!lines.is_empty());
+        for line in lines.lines() {
+            let entry: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(entry["field_name"], "breed");
+        }
+    }
+
+    #[test]
+    fn writes_csv_with_header() {
+        let xml = read_to_string("tests/assets/site_native.xml").unwrap();
+        let native = parse_site_native_string(&xml).unwrap();
+
+        let mut out = Vec::new();
+        write_csv(iter_site_native_sites(&xml), Filter::default(), &mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+
+        assert!(csv.starts_with("record_unique_id,patient_id,site_name,"));
+        assert_eq!(csv.lines().count(), native.flat_entries().len() + 1);
+    }
+
+    #[test]
+    fn writes_parquet() {
+        let xml = read_to_string("tests/assets/subject_native.xml").unwrap();
+        let native = parse_subject_native_string(&xml).unwrap();
+        let expected = flat_entries_to_record_batch(&native.flat_entries()).unwrap();
+
+        let mut out = tempfile::tempfile().unwrap();
+        write_parquet(iter_subject_native_patients(&xml), &mut out).unwrap();
+
+        let batches = ParquetRecordBatchReaderBuilder::try_new(out)
+            .unwrap()
+            .with_batch_size(expected.num_rows())
+            .build()
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.num_rows(), 11);
+        assert_eq!(batch.schema().fields(), expected.schema().fields());
+        assert_eq!(batch.columns(), expected.columns());
+    }
+
+    #[test]
+    fn reports_invalid_records_with_line_and_column() {
+        let xml = "<export_from_vision_EDC>\n  <patient patientId=\"A\" uniqueId=\"1\" creator=\"c\" siteName=\"s\" siteUniqueId=\"2\"></patient>\n  <patient patientId=\"B\" uniqueId=\"3\" creator=\"c\" siteName=\"s\" siteUniqueId=\"2\">\n    <form name=\"f\"></category>\n  </patient>\n</export_from_vision_EDC>";
+
+        let errors = validate("in.xml", xml, iter_subject_native_patients(xml)).unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("in.xml:4:"), "{}", errors[0]);
+    }
+
+    #[test]
+    fn reports_truncated_documents() {
+        let xml = "<export_from_vision_EDC>\n  <patient patientId=\"A\" uniqueId=\"1\" creator=\"c\" siteName=\"s\" siteUniqueId=\"2\"></patient>\n  <patient patientId=\"B\" uniqueId=\"3\" creator=\"c\" siteName=\"s\" siteUniqueId=\"2\">\n    <form name=\"f\"></form>\n";
+
+        let errors = validate("in.xml", xml, iter_subject_native_patients(xml)).unwrap_err();
+
+        assert_eq!(errors, vec!["in.xml:3:3: Unclosed element <patient>"]);
+    }
+
+    #[test]
+    fn line_and_column_are_one_based() {
+        assert_eq!(line_and_column("ab\ncd", 0), (1, 1));
+        assert_eq!(line_and_column("ab\ncd", 4), (2, 2));
+    }
+}
@@ -22,7 +22,37 @@ pub enum Error {
     #[error(transparent)]
     ParsingError(#[from] quick_xml::de::DeError),
 
+    /// The XML is malformed. `position` is the byte offset in the input where the error starts.
+    #[error("XML error at position {position}: {message}")]
+    XmlError { position: u64, message: String },
+
+    /// The JSON could not be read.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    /// The JSON was written with a `schemaVersion` this version of the crate cannot read.
+    #[error("Unsupported JSON schema version {found}, expected {supported}")]
+    UnsupportedSchemaVersion { found: u32, supported: u32 },
+
+    /// A pattern is not a valid regular expression.
+    #[error(transparent)]
+    InvalidPattern(#[from] regex::Error),
+
     /// An unknown error occurred.
     #[error("Unknown error")]
     Unknown,
 }
+
+impl Error {
+    /// Moves a position reported relative to a record chunk so it is relative to the whole
+    /// document.
+    pub(crate) fn offset_by(self, offset: usize) -> Self {
+        match self {
+            Error::XmlError { position, message } => Error::XmlError {
+                position: position + offset as u64,
+                message,
+            },
+            e => e,
+        }
+    }
+}
@@ -1,20 +1,78 @@
+#[cfg(feature = "tokio")]
+mod async_native;
 pub mod errors;
 pub mod native;
+#[cfg(feature = "python")]
+pub mod python;
+
+#[cfg(feature = "tokio")]
+pub use async_native::{
+    parse_site_native_async, parse_site_native_async_with_options, parse_subject_native_async,
+    parse_subject_native_async_with_options, parse_user_native_async,
+    parse_user_native_async_with_options, stream_native_records, stream_site_native_sites,
+    stream_subject_native_patients, stream_user_native_users,
+};
 
-use std::{borrow::Cow, collections::HashMap, fs::read_to_string, path::Path, str::from_utf8};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    fs::read_to_string,
+    path::Path,
+    str::from_utf8,
+};
 
 use rayon::prelude::*;
 
 use crate::errors::Error;
 use crate::native::{
-    common::{Category, Comment, Entry, Field, LockState, Reason, State, Value},
+    common::{
+        Category, Comment, Entry, ExportInfo, Field, FromAttributes, LockState, NativeRecord,
+        Reason, State, Value, XmlElement,
+    },
     site_native::{Site, SiteNative},
     subject_native::{Form, Patient, SubjectNative},
     user_native::{User, UserNative},
+    visitor::NativeVisitor,
 };
-use quick_xml::events::{BytesStart, Event};
+use quick_xml::events::{BytesRef, BytesStart, Event};
 use quick_xml::Reader;
 
+/// The elements the parser reads inside a record. Any others are unknown to it.
+const KNOWN_ELEMENTS: &[&str] = &[
+    "form",
+    "state",
+    "lockState",
+    "category",
+    "field",
+    "entry",
+    "comment",
+    "value",
+    "reason",
+];
+
+/// Options for parsing a native export.
+///
+/// # Example
+///
+/// ```
+/// use prelude_xml_parser::{parse_subject_native_string_with_options, ParseOptions};
+///
+/// let xml = std::fs::read_to_string("tests/assets/subject_native.xml").unwrap();
+/// let options = ParseOptions {
+///     keep_unknown: true,
+/// };
+/// let native = parse_subject_native_string_with_options(&xml, &options).unwrap();
+///
+/// assert!(native.patients[0].extra.is_empty());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Keep the attributes and child elements the parser does not know in the `extra` and
+    /// `extra_elements` of each model, e.g. those added by a newer version of Prelude. Off by
+    /// default, as collecting them slows parsing down.
+    pub keep_unknown: bool,
+}
+
 /// Parses a Prelude native XML file into a `Native` struct.
 ///
 /// # Example
@@ -30,10 +88,18 @@ use quick_xml::Reader;
 /// assert!(native.sites.len() >= 1, "Vector length is less than 1");
 /// ```
 pub fn parse_site_native_file(xml_path: &Path) -> Result<SiteNative, Error> {
+    parse_site_native_file_with_options(xml_path, &ParseOptions::default())
+}
+
+/// Parses a Prelude native site XML file like [`parse_site_native_file`], with `options`.
+pub fn parse_site_native_file_with_options(
+    xml_path: &Path,
+    options: &ParseOptions,
+) -> Result<SiteNative, Error> {
     check_valid_xml_file(xml_path)?;
 
     let xml_file = read_to_string(xml_path)?;
-    let native = parse_site_native_string(&xml_file)?;
+    let native = parse_site_native_string_with_options(&xml_file, options)?;
 
     Ok(native)
 }
@@ -43,7 +109,7 @@ pub fn parse_site_native_file(xml_path: &Path) -> Result<SiteNative, Error> {
 /// # Example
 ///
 /// ```
-/// use chrono::{DateTime, Utc};
+/// use chrono::DateTime;
 /// use prelude_xml_parser::parse_site_native_string;
 /// use prelude_xml_parser::native::site_native::*;
 ///
@@ -109,17 +175,15 @@ pub fn parse_site_native_file(xml_path: &Path) -> Result<SiteNative, Error> {
 ///             unique_id: "1681574834910".to_string(),
 ///             number_of_patients: 4,
 ///             count_of_randomized_patients: 0,
-///             when_created: Some(DateTime::parse_from_rfc3339("2023-04-15T16:08:19Z")
-///                 .unwrap()
-///                 .with_timezone(&Utc)),
+///             when_created: Some(DateTime::parse_from_rfc3339("2023-04-15T12:08:19-04:00")
+///                 .unwrap()),
 ///             creator: "Paul Sanders".to_string(),
 ///             number_of_forms: 1,
 ///             forms: Some(vec![Form {
 ///                 name: "demographic.form.name.site.demographics".to_string(),
 ///                 last_modified: Some(
-///                     DateTime::parse_from_rfc3339("2023-04-15T16:08:19Z")
-///                         .unwrap()
-///                         .with_timezone(&Utc),
+///                     DateTime::parse_from_rfc3339("2023-04-15T12:08:19-04:00")
+///                         .unwrap(),
 ///                 ),
 ///                 who_last_modified_name: Some("Paul Sanders".to_string()),
 ///                 who_last_modified_role: Some("Project Manager".to_string()),
@@ -139,10 +203,10 @@ pub fn parse_site_native_file(xml_path: &Path) -> Result<SiteNative, Error> {
 ///                     signer: "Paul Sanders - Project Manager".to_string(),
 ///                     signer_unique_id: "1681162687395".to_string(),
 ///                     date_signed: Some(
-///                         DateTime::parse_from_rfc3339("2023-04-15T16:08:19Z")
-///                             .unwrap()
-///                             .with_timezone(&Utc),
+///                         DateTime::parse_from_rfc3339("2023-04-15T12:08:19-04:00")
+///                             .unwrap(),
 ///                     ),
+///                     extra: Default::default(),
 ///                 }]),
 ///                 categories: Some(vec![
 ///                     Category {
@@ -156,13 +220,14 @@ pub fn parse_site_native_file(xml_path: &Path) -> Result<SiteNative, Error> {
 ///                                 data_type: Some("string".to_string()),
 ///                                 error_code: "valid".to_string(),
 ///                                 when_created: Some(DateTime::parse_from_rfc3339(
-///                                     "2023-04-15T16:07:14Z",
+///                                     "2023-04-15T11:07:14-05:00",
 ///                                 )
-///                                 .unwrap()
-///                                 .with_timezone(&Utc)),
+///                                 .unwrap()),
 ///                                 keep_history: true,
 ///                                 entries: None,
 ///                                 comments: None,
+///                                 extra: Default::default(),
+///                                 extra_elements: Default::default(),
 ///                             },
 ///                             Field {
 ///                                 name: "company".to_string(),
@@ -170,10 +235,9 @@ pub fn parse_site_native_file(xml_path: &Path) -> Result<SiteNative, Error> {
 ///                                 data_type: Some("string".to_string()),
 ///                                 error_code: "valid".to_string(),
 ///                                 when_created: Some(DateTime::parse_from_rfc3339(
-///                                     "2023-04-15T16:07:14Z",
+///                                     "2023-04-15T11:07:14-05:00",
 ///                                 )
-///                                 .unwrap()
-///                                 .with_timezone(&Utc)),
+///                                 .unwrap()),
 ///                                 keep_history: true,
 ///                                 entries: Some(vec![Entry {
 ///                                     entry_id: "1".to_string(),
@@ -185,15 +249,19 @@ pub fn parse_site_native_file(xml_path: &Path) -> Result<SiteNative, Error> {
 ///                                         by_unique_id: Some("1681162687395".to_string()),
 ///                                         role: "Project Manager".to_string(),
 ///                                         when: Some(DateTime::parse_from_rfc3339(
-///                                             "2023-04-15T16:08:19Z",
+///                                             "2023-04-15T12:08:19-04:00",
 ///                                         )
-///                                         .unwrap()
-///                                         .with_timezone(&Utc)),
+///                                         .unwrap()),
 ///                                         value: "Some Company".to_string(),
+///                                         extra: Default::default(),
 ///                                     }),
 ///                                     reason: None,
+///                                     extra: Default::default(),
+///                                     extra_elements: Default::default(),
 ///                                 }]),
 ///                                 comments: None,
+///                                 extra: Default::default(),
+///                                 extra_elements: Default::default(),
 ///                             },
 ///                             Field {
 ///                                 name: "site_code_name".to_string(),
@@ -201,10 +269,9 @@ pub fn parse_site_native_file(xml_path: &Path) -> Result<SiteNative, Error> {
 ///                                 data_type: Some("string".to_string()),
 ///                                 error_code: "valid".to_string(),
 ///                                 when_created: Some(DateTime::parse_from_rfc3339(
-///                                     "2023-04-15T16:07:14Z",
+///                                     "2023-04-15T11:07:14-05:00",
 ///                                 )
-///                                 .unwrap()
-///                                 .with_timezone(&Utc)),
+///                                 .unwrap()),
 ///                                 keep_history: true,
 ///                                 entries: Some(vec![
 ///                                     Entry {
@@ -217,23 +284,25 @@ pub fn parse_site_native_file(xml_path: &Path) -> Result<SiteNative, Error> {
 ///                                             by_unique_id: None,
 ///                                             role: "System".to_string(),
 ///                                             when: Some(DateTime::parse_from_rfc3339(
-///                                                 "2023-04-15T16:08:19Z",
+///                                                 "2023-04-15T12:08:19-04:00",
 ///                                             )
-///                                             .unwrap()
-///                                             .with_timezone(&Utc)),
+///                                             .unwrap()),
 ///                                             value: "ABC-Some Site".to_string(),
+///                                             extra: Default::default(),
 ///                                         }),
 ///                                         reason: Some(Reason {
 ///                                             by: "set from calculation".to_string(),
 ///                                             by_unique_id: None,
 ///                                             role: "System".to_string(),
 ///                                             when: Some(DateTime::parse_from_rfc3339(
-///                                                 "2023-04-15T16:08:19Z",
+///                                                 "2023-04-15T12:08:19-04:00",
 ///                                             )
-///                                             .unwrap()
-///                                             .with_timezone(&Utc)),
+///                                             .unwrap()),
 ///                                             value: "calculated value".to_string(),
+///                                             extra: Default::default(),
 ///                                         }),
+///                                         extra: Default::default(),
+///                                         extra_elements: Default::default(),
 ///                                     },
 ///                                     Entry {
 ///                                         entry_id: "2".to_string(),
@@ -245,28 +314,34 @@ pub fn parse_site_native_file(xml_path: &Path) -> Result<SiteNative, Error> {
 ///                                             by_unique_id: None,
 ///                                             role: "System".to_string(),
 ///                                             when: Some(DateTime::parse_from_rfc3339(
-///                                                 "2023-04-15T16:07:24Z",
+///                                                 "2023-04-15T12:07:24-04:00",
 ///                                             )
-///                                             .unwrap()
-///                                             .with_timezone(&Utc)),
+///                                             .unwrap()),
 ///                                             value: "Some Site".to_string(),
+///                                             extra: Default::default(),
 ///                                         }),
 ///                                         reason: Some(Reason {
 ///                                             by: "set from calculation".to_string(),
 ///                                             by_unique_id: None,
 ///                                             role: "System".to_string(),
 ///                                             when: Some(DateTime::parse_from_rfc3339(
-///                                                 "2023-04-15T16:07:24Z",
+///                                                 "2023-04-15T12:07:24-04:00",
 ///                                             )
-///                                             .unwrap()
-///                                             .with_timezone(&Utc)),
+///                                             .unwrap()),
 ///                                             value: "calculated value".to_string(),
+///                                             extra: Default::default(),
 ///                                         }),
+///                                         extra: Default::default(),
+///                                         extra_elements: Default::default(),
 ///                                     },
 ///                                 ]),
 ///                                 comments: None,
+///                                 extra: Default::default(),
+///                                 extra_elements: Default::default(),
 ///                             },
 ///                         ]),
+///                         extra: Default::default(),
+///                         extra_elements: Default::default(),
 ///                     },
 ///                     Category {
 ///                         name: "Enrollment".to_string(),
@@ -279,13 +354,14 @@ pub fn parse_site_native_file(xml_path: &Path) -> Result<SiteNative, Error> {
 ///                                 data_type: Some("date".to_string()),
 ///                                 error_code: "valid".to_string(),
 ///                                 when_created: Some(DateTime::parse_from_rfc3339(
-///                                     "2023-04-15T16:07:14Z",
+///                                     "2023-04-15T11:07:14-05:00",
 ///                                 )
-///                                 .unwrap()
-///                                 .with_timezone(&Utc)),
+///                                 .unwrap()),
 ///                                 keep_history: true,
 ///                                 entries: None,
 ///                                 comments: None,
+///                                 extra: Default::default(),
+///                                 extra_elements: Default::default(),
 ///                             },
 ///                             Field {
 ///                                 name: "enrollment_open".to_string(),
@@ -293,10 +369,9 @@ pub fn parse_site_native_file(xml_path: &Path) -> Result<SiteNative, Error> {
 ///                                 data_type: Some("string".to_string()),
 ///                                 error_code: "valid".to_string(),
 ///                                 when_created: Some(DateTime::parse_from_rfc3339(
-///                                     "2023-04-15T16:07:14Z",
+///                                     "2023-04-15T11:07:14-05:00",
 ///                                 )
-///                                 .unwrap()
-///                                 .with_timezone(&Utc)),
+///                                 .unwrap()),
 ///                                 keep_history: true,
 ///                                 entries: Some(vec![Entry {
 ///                                     entry_id: "1".to_string(),
@@ -308,15 +383,19 @@ pub fn parse_site_native_file(xml_path: &Path) -> Result<SiteNative, Error> {
 ///                                         by_unique_id: Some("1681162687395".to_string()),
 ///                                         role: "Project Manager".to_string(),
 ///                                         when: Some(DateTime::parse_from_rfc3339(
-///                                             "2023-04-15T16:08:19Z",
+///                                             "2023-04-15T12:08:19-04:00",
 ///                                         )
-///                                         .unwrap()
-///                                         .with_timezone(&Utc)),
+///                                         .unwrap()),
 ///                                         value: "Yes".to_string(),
+///                                         extra: Default::default(),
 ///                                     }),
 ///                                     reason: None,
+///                                     extra: Default::default(),
+///                                     extra_elements: Default::default(),
 ///                                 }]),
 ///                                 comments: None,
+///                                 extra: Default::default(),
+///                                 extra_elements: Default::default(),
 ///                             },
 ///                             Field {
 ///                                 name: "enrollment_open_date".to_string(),
@@ -324,35 +403,40 @@ pub fn parse_site_native_file(xml_path: &Path) -> Result<SiteNative, Error> {
 ///                                 data_type: Some("date".to_string()),
 ///                                 error_code: "valid".to_string(),
 ///                                 when_created: Some(DateTime::parse_from_rfc3339(
-///                                     "2023-04-15T16:07:14Z",
+///                                     "2023-04-15T11:07:14-05:00",
 ///                                 )
-///                                 .unwrap()
-///                                 .with_timezone(&Utc)),
+///                                 .unwrap()),
 ///                                 keep_history: true,
 ///                                 entries: None,
 ///                                 comments: None,
+///                                 extra: Default::default(),
+///                                 extra_elements: Default::default(),
 ///                             },
 ///                         ]),
+///                         extra: Default::default(),
+///                         extra_elements: Default::default(),
 ///                     },
 ///                 ]),
+///                 extra: Default::default(),
+///                 extra_elements: Default::default(),
 ///             }]),
+///             extra: Default::default(),
+///             extra_elements: Default::default(),
 ///         },
 ///         Site {
 ///             name: "Artemis".to_string(),
 ///             unique_id: "1691420994591".to_string(),
 ///             number_of_patients: 0,
 ///             count_of_randomized_patients: 0,
-///             when_created: Some(DateTime::parse_from_rfc3339("2023-08-07T15:14:23Z")
-///                 .unwrap()
-///                 .with_timezone(&Utc)),
+///             when_created: Some(DateTime::parse_from_rfc3339("2023-08-07T08:14:23-07:00")
+///                 .unwrap()),
 ///             creator: "Paul Sanders".to_string(),
 ///             number_of_forms: 1,
 ///             forms: Some(vec![Form {
 ///                 name: "demographic.form.name.site.demographics".to_string(),
 ///                 last_modified: Some(
-///                     DateTime::parse_from_rfc3339("2023-08-07T15:14:23Z")
-///                         .unwrap()
-///                         .with_timezone(&Utc),
+///                     DateTime::parse_from_rfc3339("2023-08-07T08:14:23-07:00")
+///                         .unwrap(),
 ///                 ),
 ///                 who_last_modified_name: Some("Paul Sanders".to_string()),
 ///                 who_last_modified_role: Some("Project Manager".to_string()),
@@ -372,10 +456,10 @@ pub fn parse_site_native_file(xml_path: &Path) -> Result<SiteNative, Error> {
 ///                     signer: "Paul Sanders - Project Manager".to_string(),
 ///                     signer_unique_id: "1681162687395".to_string(),
 ///                     date_signed: Some(
-///                         DateTime::parse_from_rfc3339("2023-08-07T15:14:23Z")
-///                             .unwrap()
-///                             .with_timezone(&Utc),
+///                         DateTime::parse_from_rfc3339("2023-08-07T08:14:23-07:00")
+///                             .unwrap(),
 ///                     ),
+///                     extra: Default::default(),
 ///                 }]),
 ///                 categories: Some(vec![Category {
 ///                     name: "Demographics".to_string(),
@@ -386,9 +470,8 @@ pub fn parse_site_native_file(xml_path: &Path) -> Result<SiteNative, Error> {
 ///                         field_type: "text".to_string(),
 ///                         data_type: Some("string".to_string()),
 ///                         error_code: "valid".to_string(),
-///                         when_created: Some(DateTime::parse_from_rfc3339("2023-08-07T15:09:54Z")
-///                             .unwrap()
-///                             .with_timezone(&Utc)),
+///                         when_created: Some(DateTime::parse_from_rfc3339("2023-08-07T10:09:54-05:00")
+///                             .unwrap()),
 ///                         keep_history: true,
 ///                         entries: Some(vec![Entry {
 ///                             entry_id: "1".to_string(),
@@ -399,12 +482,14 @@ pub fn parse_site_native_file(xml_path: &Path) -> Result<SiteNative, Error> {
 ///                                 by: "Paul Sanders".to_string(),
 ///                                 by_unique_id: Some("1681162687395".to_string()),
 ///                                 role: "Project Manager".to_string(),
-///                                 when: Some(DateTime::parse_from_rfc3339("2023-08-07T15:14:21Z")
-///                                     .unwrap()
-///                                     .with_timezone(&Utc)),
+///                                 when: Some(DateTime::parse_from_rfc3339("2023-08-07T08:14:21-07:00")
+///                                     .unwrap()),
 ///                                 value: "1111 Moon Drive".to_string(),
+///                                 extra: Default::default(),
 ///                             }),
 ///                             reason: None,
+///                             extra: Default::default(),
+///                             extra_elements: Default::default(),
 ///                         }]),
 ///                         comments: Some(vec![Comment {
 ///                             comment_id: "1".to_string(),
@@ -412,27 +497,54 @@ pub fn parse_site_native_file(xml_path: &Path) -> Result<SiteNative, Error> {
 ///                                 by: "Paul Sanders".to_string(),
 ///                                 by_unique_id: Some("1681162687395".to_string()),
 ///                                 role: "Project Manager".to_string(),
-///                                 when: Some(DateTime::parse_from_rfc3339("2023-08-07T15:14:21Z")
-///                                     .unwrap()
-///                                     .with_timezone(&Utc)),
+///                                 when: Some(DateTime::parse_from_rfc3339("2023-08-07T08:14:21-07:00")
+///                                     .unwrap()),
 ///                                 value: "Some comment".to_string(),
+///                                 extra: Default::default(),
 ///                             }),
+///                             replies: None,
+///                             reason: None,
+///                             extra: Default::default(),
+///                             extra_elements: Default::default(),
 ///                         }]),
+///                         extra: Default::default(),
+///                         extra_elements: Default::default(),
 ///                     }]),
+///                     extra: Default::default(),
+///                     extra_elements: Default::default(),
 ///                 }]),
+///                 extra: Default::default(),
+///                 extra_elements: Default::default(),
 ///             }]),
+///             extra: Default::default(),
+///             extra_elements: Default::default(),
 ///         },
 ///     ],
+///     export: Some(ExportInfo {
+///         date: Some(DateTime::parse_from_rfc3339("2024-06-01T18:17:00-05:00").unwrap()),
+///         created_by: "Paul Sanders".to_string(),
+///         role: "Project Manager".to_string(),
+///         number_subjects_processed: 2,
+///         extra: Default::default(),
+///     }),
 /// };
 /// let result = parse_site_native_string(xml).unwrap();
 /// assert_eq!(result, expected);
 pub fn parse_site_native_string(xml_str: &str) -> Result<SiteNative, Error> {
-    let chunks = extract_site_chunks(xml_str);
-    let sites = chunks
-        .into_par_iter()
-        .map(parse_site_xml)
-        .collect::<Result<Vec<_>, _>>()?;
-    Ok(SiteNative { sites })
+    parse_site_native_string_with_options(xml_str, &ParseOptions::default())
+}
+
+/// Parses a string of Prelude native site XML like [`parse_site_native_string`], with
+/// `options`.
+pub fn parse_site_native_string_with_options(
+    xml_str: &str,
+    options: &ParseOptions,
+) -> Result<SiteNative, Error> {
+    let sites = parse_native_records(xml_str, options)?;
+    Ok(SiteNative {
+        sites,
+        export: parse_export_info(xml_str, options)?,
+    })
 }
 
 /// Parses a Prelude native subject XML file into a `SubjectNative` struct.
@@ -450,15 +562,18 @@ pub fn parse_site_native_string(xml_str: &str) -> Result<SiteNative, Error> {
 /// assert!(native.patients.len() >= 1, "Vector length is less than 1");
 /// ```
 pub fn parse_subject_native_file(xml_path: &Path) -> Result<SubjectNative, Error> {
+    parse_subject_native_file_with_options(xml_path, &ParseOptions::default())
+}
+
+/// Parses a Prelude native subject XML file like [`parse_subject_native_file`], with `options`.
+pub fn parse_subject_native_file_with_options(
+    xml_path: &Path,
+    options: &ParseOptions,
+) -> Result<SubjectNative, Error> {
     check_valid_xml_file(xml_path)?;
 
     let xml_str = read_to_string(xml_path)?;
-    let chunks = extract_patient_chunks(&xml_str);
-    let patients = chunks
-        .into_par_iter()
-        .map(parse_patient_xml)
-        .collect::<Result<Vec<_>, _>>()?;
-    Ok(SubjectNative { patients })
+    parse_subject_native_string_with_options(&xml_str, options)
 }
 
 /// Parse a string of Prelude native subject XML into a `SubjectNative` struct.
@@ -466,7 +581,7 @@ pub fn parse_subject_native_file(xml_path: &Path) -> Result<SubjectNative, Error
 /// # Example
 ///
 /// ```
-/// use chrono::{DateTime, Utc};
+/// use chrono::DateTime;
 /// use prelude_xml_parser::parse_subject_native_string;
 /// use prelude_xml_parser::native::common::LockState;
 /// use prelude_xml_parser::native::subject_native::*;
@@ -504,9 +619,8 @@ pub fn parse_subject_native_file(xml_path: &Path) -> Result<SubjectNative, Error
 ///         Patient {
 ///             patient_id: "ABC-001".to_string(),
 ///             unique_id: "1681574905819".to_string(),
-///             when_created: Some(DateTime::parse_from_rfc3339("2023-04-15T16:09:02Z")
-///                 .unwrap()
-///                 .with_timezone(&Utc)),
+///             when_created: Some(DateTime::parse_from_rfc3339("2023-04-15T12:09:02-04:00")
+///                 .unwrap()),
 ///             creator: "Paul Sanders".to_string(),
 ///             site_name: "Some Site".to_string(),
 ///             site_unique_id: "1681574834910".to_string(),
@@ -514,9 +628,8 @@ pub fn parse_subject_native_file(xml_path: &Path) -> Result<SubjectNative, Error
 ///             number_of_forms: 6,
 ///             forms: Some(vec![Form {
 ///                 name: "day.0.form.name.demographics".to_string(),
-///                 last_modified: Some(DateTime::parse_from_rfc3339("2023-04-15T16:09:15Z")
-///                     .unwrap()
-///                     .with_timezone(&Utc)),
+///                 last_modified: Some(DateTime::parse_from_rfc3339("2023-04-15T12:09:15-04:00")
+///                     .unwrap()),
 ///                 who_last_modified_name: Some("Paul Sanders".to_string()),
 ///                 who_last_modified_role: Some("Project Manager".to_string()),
 ///                 when_created: 1681574905839,
@@ -535,10 +648,10 @@ pub fn parse_subject_native_file(xml_path: &Path) -> Result<SubjectNative, Error
 ///                     signer: "Paul Sanders - Project Manager".to_string(),
 ///                     signer_unique_id: "1681162687395".to_string(),
 ///                     date_signed: Some(
-///                         DateTime::parse_from_rfc3339("2023-04-15T16:09:02Z")
-///                             .unwrap()
-///                             .with_timezone(&Utc),
+///                         DateTime::parse_from_rfc3339("2023-04-15T12:09:02-04:00")
+///                             .unwrap(),
 ///                     ),
+///                     extra: Default::default(),
 ///                 }]),
 ///                 categories: Some(vec![Category {
 ///                     name: "Demographics".to_string(),
@@ -549,9 +662,8 @@ pub fn parse_subject_native_file(xml_path: &Path) -> Result<SubjectNative, Error
 ///                         field_type: "combo-box".to_string(),
 ///                         data_type: Some("string".to_string()),
 ///                         error_code: "valid".to_string(),
-///                         when_created: Some(DateTime::parse_from_rfc3339("2023-04-15T16:08:26Z")
-///                             .unwrap()
-///                             .with_timezone(&Utc)),
+///                         when_created: Some(DateTime::parse_from_rfc3339("2023-04-15T12:08:26-04:00")
+///                             .unwrap()),
 ///                         keep_history: true,
 ///                         entries: Some(vec![Entry {
 ///                             entry_id: "1".to_string(),
@@ -562,24 +674,33 @@ pub fn parse_subject_native_file(xml_path: &Path) -> Result<SubjectNative, Error
 ///                                 by: "Paul Sanders".to_string(),
 ///                                 by_unique_id: Some("1681162687395".to_string()),
 ///                                 role: "Project Manager".to_string(),
-///                                 when: Some(DateTime::parse_from_rfc3339("2023-04-15T16:09:02Z")
-///                                     .unwrap()
-///                                     .with_timezone(&Utc)),
+///                                 when: Some(DateTime::parse_from_rfc3339("2023-04-15T12:09:02-04:00")
+///                                     .unwrap()),
 ///                                 value: "Labrador".to_string(),
+///                                 extra: Default::default(),
 ///                             }),
 ///                             reason: None,
+///                             extra: Default::default(),
+///                             extra_elements: Default::default(),
 ///                         }]),
 ///                         comments: None,
+///                         extra: Default::default(),
+///                         extra_elements: Default::default(),
 ///                     }]),
+///                     extra: Default::default(),
+///                     extra_elements: Default::default(),
 ///                 }]),
+///                 extra: Default::default(),
+///                 extra_elements: Default::default(),
 ///             }]),
+///             extra: Default::default(),
+///             extra_elements: Default::default(),
 ///         },
 ///         Patient {
 ///             patient_id: "DEF-002".to_string(),
 ///             unique_id: "1681574905820".to_string(),
-///             when_created: Some(DateTime::parse_from_rfc3339("2023-04-16T16:10:02Z")
-///                 .unwrap()
-///                 .with_timezone(&Utc)),
+///             when_created: Some(DateTime::parse_from_rfc3339("2023-04-16T12:10:02-04:00")
+///                 .unwrap()),
 ///             creator: "Wade Watts".to_string(),
 ///             site_name: "Another Site".to_string(),
 ///             site_unique_id: "1681574834911".to_string(),
@@ -587,9 +708,8 @@ pub fn parse_subject_native_file(xml_path: &Path) -> Result<SubjectNative, Error
 ///             number_of_forms: 8,
 ///             forms: Some(vec![Form {
 ///                 name: "day.0.form.name.demographics".to_string(),
-///                 last_modified: Some(DateTime::parse_from_rfc3339("2023-04-16T16:10:15Z")
-///                     .unwrap()
-///                     .with_timezone(&Utc)),
+///                 last_modified: Some(DateTime::parse_from_rfc3339("2023-04-16T12:10:15-04:00")
+///                     .unwrap()),
 ///                 who_last_modified_name: Some("Barney Rubble".to_string()),
 ///                 who_last_modified_role: Some("Technician".to_string()),
 ///                 when_created: 1681574905838,
@@ -608,10 +728,10 @@ pub fn parse_subject_native_file(xml_path: &Path) -> Result<SubjectNative, Error
 ///                     signer: "Paul Sanders - Project Manager".to_string(),
 ///                     signer_unique_id: "1681162687395".to_string(),
 ///                     date_signed: Some(
-///                         DateTime::parse_from_rfc3339("2023-04-16T16:10:02Z")
-///                             .unwrap()
-///                             .with_timezone(&Utc),
+///                         DateTime::parse_from_rfc3339("2023-04-16T12:10:02-04:00")
+///                             .unwrap(),
 ///                     ),
+///                     extra: Default::default(),
 ///                 }]),
 ///                 categories: Some(vec![Category {
 ///                     name: "Demographics".to_string(),
@@ -622,9 +742,8 @@ pub fn parse_subject_native_file(xml_path: &Path) -> Result<SubjectNative, Error
 ///                         field_type: "combo-box".to_string(),
 ///                         data_type: Some("string".to_string()),
 ///                         error_code: "valid".to_string(),
-///                         when_created: Some(DateTime::parse_from_rfc3339("2023-04-15T16:08:26Z")
-///                             .unwrap()
-///                             .with_timezone(&Utc)),
+///                         when_created: Some(DateTime::parse_from_rfc3339("2023-04-15T12:08:26-04:00")
+///                             .unwrap()),
 ///                         keep_history: true,
 ///                         entries: Some(vec![Entry {
 ///                             entry_id: "1".to_string(),
@@ -635,42 +754,220 @@ pub fn parse_subject_native_file(xml_path: &Path) -> Result<SubjectNative, Error
 ///                                 by: "Paul Sanders".to_string(),
 ///                                 by_unique_id: Some("1681162687395".to_string()),
 ///                                 role: "Project Manager".to_string(),
-///                                 when: Some(DateTime::parse_from_rfc3339("2023-04-15T16:09:02Z")
-///                                     .unwrap()
-///                                     .with_timezone(&Utc)),
+///                                 when: Some(DateTime::parse_from_rfc3339("2023-04-15T12:09:02-04:00")
+///                                     .unwrap()),
 ///                                 value: "Labrador".to_string(),
+///                                 extra: Default::default(),
 ///                             }),
 ///                             reason: None,
+///                             extra: Default::default(),
+///                             extra_elements: Default::default(),
 ///                         }]),
 ///                         comments: None,
+///                         extra: Default::default(),
+///                         extra_elements: Default::default(),
 ///                     }]),
+///                     extra: Default::default(),
+///                     extra_elements: Default::default(),
 ///                 }]),
+///                 extra: Default::default(),
+///                 extra_elements: Default::default(),
 ///             }]),
+///             extra: Default::default(),
+///             extra_elements: Default::default(),
 ///         },
 ///     ],
+///     export: Some(ExportInfo {
+///         date: Some(DateTime::parse_from_rfc3339("2024-05-30T10:35:00-05:00").unwrap()),
+///         created_by: "Paul Sanders".to_string(),
+///         role: "Project Manager".to_string(),
+///         number_subjects_processed: 4,
+///         extra: Default::default(),
+///     }),
 /// };
 /// let result = parse_subject_native_string(xml).unwrap();
 ///
 /// assert_eq!(result, expected);
 /// ```
 pub fn parse_subject_native_string(xml_str: &str) -> Result<SubjectNative, Error> {
-    let chunks = extract_patient_chunks(xml_str);
-    let patients = chunks
+    parse_subject_native_string_with_options(xml_str, &ParseOptions::default())
+}
+
+/// Parses a string of Prelude native subject XML like [`parse_subject_native_string`], with
+/// `options`.
+pub fn parse_subject_native_string_with_options(
+    xml_str: &str,
+    options: &ParseOptions,
+) -> Result<SubjectNative, Error> {
+    let patients = parse_native_records(xml_str, options)?;
+    Ok(SubjectNative {
+        patients,
+        export: parse_export_info(xml_str, options)?,
+    })
+}
+
+/// Lazily parses the patients of a string of Prelude native subject XML.
+///
+/// Unlike [`parse_subject_native_string`] this builds one `Patient` at a time, so the full tree
+/// is never held in memory.
+///
+/// # Example
+///
+/// ```
+/// use prelude_xml_parser::iter_subject_native_patients;
+///
+/// let xml = std::fs::read_to_string("tests/assets/subject_native.xml").unwrap();
+/// let patient_ids = iter_subject_native_patients(&xml)
+///     .map(|patient| patient.map(|p| p.patient_id))
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+///
+/// assert_eq!(patient_ids, vec!["ABC-001", "ABC-002"]);
+/// ```
+pub fn iter_subject_native_patients(
+    xml_str: &str,
+) -> impl Iterator<Item = Result<Patient, Error>> + '_ {
+    iter_native_records(xml_str, &ParseOptions::default())
+}
+
+/// Lazily parses the sites of a string of Prelude native site XML.
+///
+/// Unlike [`parse_site_native_string`] this builds one `Site` at a time, so the full tree is never
+/// held in memory.
+///
+/// # Example
+///
+/// ```
+/// use prelude_xml_parser::iter_site_native_sites;
+///
+/// let xml = std::fs::read_to_string("tests/assets/site_native.xml").unwrap();
+/// let site_names = iter_site_native_sites(&xml)
+///     .map(|site| site.map(|s| s.name))
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+///
+/// assert_eq!(site_names, vec!["Some Site", "Artemis"]);
+/// ```
+pub fn iter_site_native_sites(xml_str: &str) -> impl Iterator<Item = Result<Site, Error>> + '_ {
+    iter_native_records(xml_str, &ParseOptions::default())
+}
+
+/// Lazily parses the users of a string of Prelude native user XML.
+///
+/// Unlike [`parse_user_native_string`] this builds one `User` at a time, so the full tree is never
+/// held in memory.
+///
+/// # Example
+///
+/// ```
+/// use prelude_xml_parser::iter_user_native_users;
+///
+/// let xml = std::fs::read_to_string("tests/assets/user_native.xml").unwrap();
+/// let user_count = iter_user_native_users(&xml).filter(|user| user.is_ok()).count();
+///
+/// assert!(user_count >= 1);
+/// ```
+pub fn iter_user_native_users(xml_str: &str) -> impl Iterator<Item = Result<User, Error>> + '_ {
+    iter_native_records(xml_str, &ParseOptions::default())
+}
+
+/// Parses every `<R::TAG ...>` record of a string of Prelude native XML, e.g. each `<patient>`
+/// of a subject native, in parallel.
+///
+/// This is the engine behind the `parse_*_native_string` functions. Implement [`NativeRecord`]
+/// to parse other Prelude record types with it.
+///
+/// # Example
+///
+/// ```
+/// use prelude_xml_parser::{native::subject_native::Patient, parse_native_records, ParseOptions};
+///
+/// let xml = std::fs::read_to_string("tests/assets/subject_native.xml").unwrap();
+/// let patients: Vec<Patient> = parse_native_records(&xml, &ParseOptions::default()).unwrap();
+///
+/// assert_eq!(patients.len(), 2);
+/// ```
+pub fn parse_native_records<R: NativeRecord + Send>(
+    xml_str: &str,
+    options: &ParseOptions,
+) -> Result<Vec<R>, Error> {
+    let chunks = RecordChunks::new(xml_str, R::TAG).collect::<Vec<_>>();
+    chunks
         .into_par_iter()
-        .map(parse_patient_xml)
-        .collect::<Result<Vec<_>, _>>()?;
-    Ok(SubjectNative { patients })
+        .map(|chunk| {
+            parse_record(chunk, options).map_err(|e| e.offset_by(chunk_offset(xml_str, chunk)))
+        })
+        .collect()
+}
+
+/// Lazily parses the `<R::TAG ...>` records of a string of Prelude native XML, one at a time.
+///
+/// This is the engine behind the `iter_*` functions.
+pub fn iter_native_records<'a, R: NativeRecord + 'a>(
+    xml_str: &'a str,
+    options: &ParseOptions,
+) -> impl Iterator<Item = Result<R, Error>> + 'a {
+    let options = *options;
+    RecordChunks::new(xml_str, R::TAG).map(move |chunk| {
+        parse_record(chunk, &options).map_err(|e| e.offset_by(chunk_offset(xml_str, chunk)))
+    })
+}
+
+/// Drives `visitor` through every `<record_tag ...>` record of a string of Prelude native XML, e.g.
+/// `"patient"` for a subject native, without building the models.
+///
+/// See [`NativeVisitor`] for an example.
+pub fn visit_native_string<V: NativeVisitor>(
+    xml_str: &str,
+    record_tag: &str,
+    visitor: &mut V,
+    options: &ParseOptions,
+) -> Result<(), Error> {
+    for chunk in RecordChunks::new(xml_str, record_tag) {
+        visit_record(chunk, record_tag, visitor, options)
+            .map_err(|e| e.offset_by(chunk_offset(xml_str, chunk)))?;
+    }
+
+    Ok(())
+}
+
+/// Drives `visitor` through the records of a Prelude native XML file like
+/// [`visit_native_string`].
+pub fn visit_native_file<V: NativeVisitor>(
+    xml_path: &Path,
+    record_tag: &str,
+    visitor: &mut V,
+    options: &ParseOptions,
+) -> Result<(), Error> {
+    check_valid_xml_file(xml_path)?;
+
+    let xml_str = read_to_string(xml_path)?;
+    visit_native_string(&xml_str, record_tag, visitor, options)
+}
+
+/// The attributes of an element, with their values unescaped.
+struct Attributes<'a>(Vec<(&'a str, Cow<'a, str>)>);
+
+impl Attributes<'_> {
+    fn as_map(&self) -> HashMap<&str, &str> {
+        self.0
+            .iter()
+            .map(|(key, value)| (*key, value.as_ref()))
+            .collect()
+    }
 }
 
-fn extract_attributes<'a>(e: &'a BytesStart<'a>) -> Result<HashMap<&'a str, &'a str>, Error> {
-    let mut attrs = HashMap::new();
+fn extract_attributes<'a>(e: &'a BytesStart<'a>) -> Result<Attributes<'a>, Error> {
+    let attribute_error = |e: &dyn std::fmt::Display| {
+        Error::ParsingError(quick_xml::de::DeError::Custom(format!(
+            "Attribute error: {}",
+            e
+        )))
+    };
+
+    let mut attrs = Vec::new();
     for attr in e.attributes() {
-        let attr = attr.map_err(|e| {
-            Error::ParsingError(quick_xml::de::DeError::Custom(format!(
-                "Attribute error: {}",
-                e
-            )))
-        })?;
+        let attr = attr.map_err(|e| attribute_error(&e))?;
         let Cow::Borrowed(value) = attr.value else {
             return Err(Error::ParsingError(quick_xml::de::DeError::Custom(
                 "Attribute value was not borrowed from the source".to_string(),
@@ -681,302 +978,226 @@ fn extract_attributes<'a>(e: &'a BytesStart<'a>) -> Result<HashMap<&'a str, &'a
                 "Attribute was not valid UTF-8".to_string(),
             )));
         };
-        attrs.insert(key, value);
+        let value = quick_xml::escape::unescape(value).map_err(|e| attribute_error(&e))?;
+        attrs.push((key, value));
     }
-    Ok(attrs)
+    Ok(Attributes(attrs))
 }
 
-fn extract_patient_chunks(xml: &str) -> Vec<&str> {
-    let mut chunks = Vec::new();
-    let mut pos = 0;
-    loop {
-        match xml[pos..].find("<patient ") {
-            None => break,
-            Some(rel) => {
-                let start = pos + rel;
-                match xml[start..].find("</patient>") {
-                    None => break,
-                    Some(rel_end) => {
-                        let end = start + rel_end + "</patient>".len();
-                        chunks.push(&xml[start..end]);
-                        pos = end;
-                    }
-                }
-            }
-        }
+/// The text an entity or character reference in a value stands for, e.g. `&` for `&amp;`.
+fn resolve_reference(e: &BytesRef) -> Result<String, Error> {
+    let reference_error = |message: String| {
+        Error::ParsingError(quick_xml::de::DeError::Custom(format!(
+            "Reference error: {}",
+            message
+        )))
+    };
+
+    if let Some(ch) = e
+        .resolve_char_ref()
+        .map_err(|e| reference_error(e.to_string()))?
+    {
+        return Ok(ch.to_string());
     }
-    chunks
+    let name = e.decode().map_err(|e| reference_error(e.to_string()))?;
+    quick_xml::escape::resolve_predefined_entity(&name)
+        .map(str::to_string)
+        .ok_or_else(|| reference_error(format!("Unknown entity &{};", name)))
 }
 
-fn parse_patient_xml(patient_xml: &str) -> Result<Patient, Error> {
-    let mut xml_reader = Reader::from_str(patient_xml);
-    xml_reader.config_mut().trim_text(true);
-
-    let mut current_patient: Option<Patient> = None;
-    let mut current_forms: Vec<Form> = Vec::new();
-    let mut current_form: Option<Form> = None;
-    let mut current_states: Vec<State> = Vec::new();
-    let mut current_categories: Vec<Category> = Vec::new();
-    let mut current_category: Option<Category> = None;
-    let mut current_fields: Vec<Field> = Vec::new();
-    let mut current_field: Option<Field> = None;
-    let mut current_entries: Vec<Entry> = Vec::new();
-    let mut current_entry: Option<Entry> = None;
-    let mut current_comments: Vec<Comment> = Vec::new();
-    let mut current_comment: Option<Comment> = None;
-    let mut current_value: Option<Value> = None;
-    let mut current_reason: Option<Reason> = None;
-    let mut text_content = String::new();
-    let mut in_form = false;
-    let mut in_category = false;
-    let mut in_field = false;
-    let mut in_entry = false;
-    let mut in_comment = false;
-    let mut in_value = false;
-    let mut in_reason = false;
+/// Builds a model from the attributes of its element, keeping the unknown ones when asked to.
+fn build_model<T: FromAttributes>(
+    attrs: HashMap<&str, &str>,
+    options: &ParseOptions,
+) -> Result<T, Error> {
+    let extra = if options.keep_unknown {
+        attrs
+            .iter()
+            .filter(|(key, _)| !T::ATTRIBUTES.contains(key))
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    } else {
+        BTreeMap::new()
+    };
+
+    let mut model = T::from_attributes(attrs)?;
+    *model.extra_mut() = extra;
+    Ok(model)
+}
+
+/// Whether `name` is an element the parser does not know and should be kept.
+fn is_unknown_element(name: &str, record_tag: &str, options: &ParseOptions) -> bool {
+    options.keep_unknown && name != record_tag && !KNOWN_ELEMENTS.contains(&name)
+}
+
+/// Reads an unknown element without children, e.g. `<note text="..."/>`.
+fn unknown_element(e: &BytesStart) -> Result<XmlElement, Error> {
+    Ok(XmlElement {
+        name: String::from_utf8_lossy(e.name().as_ref()).into_owned(),
+        attributes: extract_attributes(e)?
+            .0
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value.into_owned()))
+            .collect(),
+        ..XmlElement::default()
+    })
+}
 
+/// Reads an unknown element and everything inside it, up to and including its end tag.
+///
+/// The text is kept escaped as in the document, so the element can be written back as it was.
+fn read_unknown_element(
+    xml_reader: &mut Reader<&[u8]>,
+    start: &BytesStart,
+) -> Result<XmlElement, Error> {
+    read_unknown_content(xml_reader, unknown_element(start)?)
+}
+
+fn read_unknown_content(
+    xml_reader: &mut Reader<&[u8]>,
+    mut element: XmlElement,
+) -> Result<XmlElement, Error> {
     loop {
         match xml_reader.read_event() {
             Err(e) => {
-                return Err(Error::ParsingError(quick_xml::de::DeError::Custom(
-                    format!("XML reading error: {}", e),
-                )))
+                return Err(Error::XmlError {
+                    position: xml_reader.error_position(),
+                    message: e.to_string(),
+                })
             }
-            Ok(Event::Eof) => break,
-
             Ok(Event::Start(ref e)) => {
-                let name_bytes = e.local_name();
-                if let Ok(name) = std::str::from_utf8(name_bytes.as_ref()) {
-                    match name {
-                        "patient" => {
-                            let attrs = extract_attributes(e)?;
-                            current_patient = Some(Patient::from_attributes(attrs)?);
-                            current_forms.clear();
-                        }
-                        "form" if current_patient.is_some() => {
-                            let attrs = extract_attributes(e)?;
-                            current_form = Some(Form::from_attributes(attrs)?);
-                            in_form = true;
-                            current_states.clear();
-                            current_categories.clear();
-                        }
-                        "category" if in_form => {
-                            let attrs = extract_attributes(e)?;
-                            current_category = Some(Category::from_attributes(attrs)?);
-                            in_category = true;
-                            current_fields.clear();
-                        }
-                        "field" if in_category => {
-                            let attrs = extract_attributes(e)?;
-                            current_field = Some(Field::from_attributes(attrs)?);
-                            in_field = true;
-                            current_entries.clear();
-                            current_comments.clear();
-                        }
-                        "entry" if in_field => {
-                            let attrs = extract_attributes(e)?;
-                            current_entry = Some(Entry::from_attributes(attrs)?);
-                            in_entry = true;
-                        }
-                        "comment" if in_field => {
-                            let attrs = extract_attributes(e)?;
-                            let comment_id = attrs.get("id").cloned().unwrap_or_default();
-                            current_comment = Some(Comment {
-                                comment_id: comment_id.to_string(),
-                                value: None,
-                            });
-                            in_comment = true;
-                        }
-                        "value" if in_entry || in_comment => {
-                            let attrs = extract_attributes(e)?;
-                            current_value = Some(Value::from_attributes(attrs)?);
-                            in_value = true;
-                            text_content.clear();
-                        }
-                        "reason" if in_entry => {
-                            let attrs = extract_attributes(e)?;
-                            current_reason = Some(Reason::from_attributes(attrs)?);
-                            in_reason = true;
-                            text_content.clear();
-                        }
-                        _ => {}
-                    }
-                }
+                let child = read_unknown_content(xml_reader, unknown_element(e)?)?;
+                element.children.push(child);
             }
-
-            Ok(Event::Text(e)) if (in_value || in_reason) => {
-                text_content.push_str(&String::from_utf8_lossy(&e));
+            Ok(Event::Empty(ref e)) => element.children.push(unknown_element(e)?),
+            Ok(Event::Text(e)) => element.text.push_str(&String::from_utf8_lossy(&e)),
+            Ok(Event::GeneralRef(e)) => {
+                element.text.push('&');
+                element.text.push_str(&String::from_utf8_lossy(&e));
+                element.text.push(';');
             }
-
-            Ok(Event::End(ref e)) => {
-                let name_bytes = e.local_name();
-                if let Ok(name) = std::str::from_utf8(name_bytes.as_ref()) {
-                    match name {
-                        "patient" => {
-                            if let Some(mut patient) = current_patient.take() {
-                                if !current_forms.is_empty() {
-                                    patient.set_forms(std::mem::take(&mut current_forms));
-                                }
-                                current_patient = Some(patient);
-                            }
-                        }
-                        "form" if in_form => {
-                            if let Some(mut form) = current_form.take() {
-                                if !current_states.is_empty() {
-                                    form.states = Some(std::mem::take(&mut current_states));
-                                }
-                                if !current_categories.is_empty() {
-                                    form.categories = Some(std::mem::take(&mut current_categories));
-                                }
-                                current_forms.push(form);
-                            }
-                            in_form = false;
-                        }
-                        "category" if in_category => {
-                            if let Some(mut category) = current_category.take() {
-                                if !current_fields.is_empty() {
-                                    category.fields = Some(std::mem::take(&mut current_fields));
-                                }
-                                current_categories.push(category);
-                            }
-                            in_category = false;
-                        }
-                        "field" if in_field => {
-                            if let Some(mut field) = current_field.take() {
-                                if !current_entries.is_empty() {
-                                    field.entries = Some(std::mem::take(&mut current_entries));
-                                }
-                                if !current_comments.is_empty() {
-                                    field.comments = Some(std::mem::take(&mut current_comments));
-                                }
-                                current_fields.push(field);
-                            }
-                            in_field = false;
-                        }
-                        "entry" if in_entry => {
-                            if let Some(entry) = current_entry.take() {
-                                current_entries.push(entry);
-                            }
-                            in_entry = false;
-                        }
-                        "comment" if in_comment => {
-                            if let Some(comment) = current_comment.take() {
-                                current_comments.push(comment);
-                            }
-                            in_comment = false;
-                        }
-                        "value" if in_value => {
-                            if let Some(mut value) = current_value.take() {
-                                value.value = std::mem::take(&mut text_content);
-                                if let Some(ref mut entry) = current_entry {
-                                    entry.value = Some(value);
-                                } else if let Some(ref mut comment) = current_comment {
-                                    comment.value = Some(value);
-                                }
-                            }
-                            in_value = false;
-                        }
-                        "reason" if in_reason => {
-                            if let Some(mut reason) = current_reason.take() {
-                                reason.value = std::mem::take(&mut text_content);
-                                if let Some(ref mut entry) = current_entry {
-                                    entry.reason = Some(reason);
-                                }
-                            }
-                            in_reason = false;
-                        }
-                        _ => {}
-                    }
-                }
+            Ok(Event::CData(e)) => {
+                element
+                    .text
+                    .push_str(&quick_xml::escape::escape(String::from_utf8_lossy(&e)));
             }
-
-            Ok(Event::Empty(ref e)) => {
-                let name_bytes = e.local_name();
-                if let Ok(name) = std::str::from_utf8(name_bytes.as_ref()) {
-                    match name {
-                        "state" if in_form => {
-                            let attrs = extract_attributes(e)?;
-                            let state = State::from_attributes(attrs)?;
-                            current_states.push(state);
-                        }
-                        "lockState" if in_form => {
-                            let attrs = extract_attributes(e)?;
-                            let lock_state = LockState::from_attributes(attrs)?;
-                            if let Some(ref mut form) = current_form {
-                                form.lock_state = Some(lock_state);
-                            }
-                        }
-                        "value" if in_entry => {
-                            let attrs = extract_attributes(e)?;
-                            let value = Value::from_attributes(attrs)?;
-                            if let Some(ref mut entry) = current_entry {
-                                entry.value = Some(value);
-                            }
-                        }
-                        "reason" if in_entry => {
-                            let attrs = extract_attributes(e)?;
-                            let reason = Reason::from_attributes(attrs)?;
-                            if let Some(ref mut entry) = current_entry {
-                                entry.reason = Some(reason);
-                            }
-                        }
-                        _ => {}
-                    }
-                }
+            Ok(Event::End(_)) => {
+                element.text = element.text.trim().to_string();
+                return Ok(element);
             }
-
-            _ => {}
+            Ok(Event::Eof) => {
+                return Err(Error::XmlError {
+                    position: xml_reader.error_position(),
+                    message: format!("Unclosed element <{}>", element.name),
+                })
+            }
+            Ok(_) => (),
         }
     }
+}
 
-    current_patient.ok_or_else(|| {
-        Error::ParsingError(quick_xml::de::DeError::Custom(
-            "No patient found in chunk".to_string(),
-        ))
-    })
+/// Adds an unknown element to the innermost of the open models, given innermost first.
+fn push_unknown_element(element: XmlElement, open: [Option<&mut Vec<XmlElement>>; 6]) {
+    if let Some(extra_elements) = open.into_iter().flatten().next() {
+        extra_elements.push(element);
+    }
 }
 
-fn extract_site_chunks(xml: &str) -> Vec<&str> {
-    let mut chunks = Vec::new();
-    let mut pos = 0;
+/// Reads the attributes of the `<export_from_vision_EDC>` root element, if the document has one.
+fn parse_export_info(xml_str: &str, options: &ParseOptions) -> Result<Option<ExportInfo>, Error> {
+    let mut xml_reader = Reader::from_str(xml_str);
+
     loop {
-        match xml[pos..].find("<site ") {
-            None => break,
-            Some(rel) => {
-                let start = pos + rel;
-                match xml[start..].find("</site>") {
-                    None => break,
-                    Some(rel_end) => {
-                        let end = start + rel_end + "</site>".len();
-                        chunks.push(&xml[start..end]);
-                        pos = end;
-                    }
+        match xml_reader.read_event() {
+            Err(e) => {
+                return Err(Error::XmlError {
+                    position: xml_reader.error_position(),
+                    message: e.to_string(),
+                })
+            }
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                if e.local_name().as_ref() != b"export_from_vision_EDC" {
+                    return Ok(None);
                 }
+                return build_model(extract_attributes(e)?.as_map(), options).map(Some);
             }
+            Ok(Event::Eof) => return Ok(None),
+            Ok(_) => (),
         }
     }
-    chunks
 }
 
-fn parse_site_xml(site_xml: &str) -> Result<Site, Error> {
-    let mut xml_reader = Reader::from_str(site_xml);
-    xml_reader.config_mut().trim_text(true);
-
-    let mut current_site: Option<Site> = None;
-    let mut current_forms: Vec<Form> = Vec::new();
-    let mut current_form: Option<Form> = None;
-    let mut current_states: Vec<State> = Vec::new();
-    let mut current_categories: Vec<Category> = Vec::new();
-    let mut current_category: Option<Category> = None;
-    let mut current_fields: Vec<Field> = Vec::new();
-    let mut current_field: Option<Field> = None;
-    let mut current_entries: Vec<Entry> = Vec::new();
-    let mut current_entry: Option<Entry> = None;
-    let mut current_comments: Vec<Comment> = Vec::new();
-    let mut current_comment: Option<Comment> = None;
-    let mut current_value: Option<Value> = None;
-    let mut current_reason: Option<Reason> = None;
+/// Iterates over the `<tag ...>...</tag>` record chunks of a native XML string, e.g. each
+/// `<patient>` of a subject native.
+pub(crate) struct RecordChunks<'a> {
+    xml: &'a str,
+    open: String,
+    close: String,
+    pub(crate) pos: usize,
+}
+
+impl<'a> RecordChunks<'a> {
+    pub(crate) fn new(xml: &'a str, tag: &str) -> Self {
+        Self::starting_at(xml, tag, 0)
+    }
+
+    pub(crate) fn starting_at(xml: &'a str, tag: &str, pos: usize) -> Self {
+        Self {
+            xml,
+            open: format!("<{tag} "),
+            close: format!("</{tag}>"),
+            pos,
+        }
+    }
+}
+
+impl<'a> Iterator for RecordChunks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let start = self.pos + self.xml[self.pos..].find(&self.open)?;
+        let end = start + self.xml[start..].find(&self.close)? + self.close.len();
+        self.pos = end;
+        Some(&self.xml[start..end])
+    }
+}
+
+/// The byte offset of `chunk` within `xml`. `chunk` must be a subslice of `xml`.
+fn chunk_offset(xml: &str, chunk: &str) -> usize {
+    chunk.as_ptr() as usize - xml.as_ptr() as usize
+}
+
+/// Parses one `<R::TAG ...>` record chunk, e.g. a `<patient>` of a subject native, into an `R`.
+pub(crate) fn parse_record<R: NativeRecord>(
+    record_xml: &str,
+    options: &ParseOptions,
+) -> Result<R, Error> {
+    let mut builder = RecordBuilder::new(options);
+    visit_record(record_xml, R::TAG, &mut builder, options)?;
+
+    builder.records.pop().ok_or_else(|| {
+        Error::ParsingError(quick_xml::de::DeError::Custom(format!(
+            "No {} found in chunk",
+            R::TAG
+        )))
+    })
+}
+
+/// Drives `visitor` through one `<record_tag ...>` record chunk.
+fn visit_record<V: NativeVisitor>(
+    record_xml: &str,
+    record_tag: &str,
+    visitor: &mut V,
+    options: &ParseOptions,
+) -> Result<(), Error> {
+    // Text is not trimmed by the reader, which would drop the spaces around entity references, but
+    // once the whole value has been read
+    let mut xml_reader = Reader::from_str(record_xml);
+
+    // The start of the `<value>` or `<reason>` whose text is being read
+    let mut text_start: Option<BytesStart> = None;
     let mut text_content = String::new();
+    let mut in_record = false;
     let mut in_form = false;
     let mut in_category = false;
     let mut in_field = false;
@@ -988,66 +1209,54 @@ fn parse_site_xml(site_xml: &str) -> Result<Site, Error> {
     loop {
         match xml_reader.read_event() {
             Err(e) => {
-                return Err(Error::ParsingError(quick_xml::de::DeError::Custom(
-                    format!("XML reading error: {}", e),
-                )))
+                return Err(Error::XmlError {
+                    position: xml_reader.error_position(),
+                    message: e.to_string(),
+                })
             }
             Ok(Event::Eof) => break,
 
-            Ok(Event::Start(ref e)) => {
+            Ok(Event::Start(e)) => {
                 let name_bytes = e.local_name();
                 if let Ok(name) = std::str::from_utf8(name_bytes.as_ref()) {
                     match name {
-                        "site" => {
-                            let attrs = extract_attributes(e)?;
-                            current_site = Some(Site::from_attributes(attrs)?);
-                            current_forms.clear();
+                        name if name == record_tag => {
+                            visitor.on_record_start(extract_attributes(&e)?.as_map())?;
+                            in_record = true;
                         }
-                        "form" if current_site.is_some() => {
-                            let attrs = extract_attributes(e)?;
-                            current_form = Some(Form::from_attributes(attrs)?);
+                        "form" if in_record => {
+                            visitor.on_form(extract_attributes(&e)?.as_map())?;
                             in_form = true;
-                            current_states.clear();
-                            current_categories.clear();
                         }
                         "category" if in_form => {
-                            let attrs = extract_attributes(e)?;
-                            current_category = Some(Category::from_attributes(attrs)?);
+                            visitor.on_category(extract_attributes(&e)?.as_map())?;
                             in_category = true;
-                            current_fields.clear();
                         }
                         "field" if in_category => {
-                            let attrs = extract_attributes(e)?;
-                            current_field = Some(Field::from_attributes(attrs)?);
+                            visitor.on_field(extract_attributes(&e)?.as_map())?;
                             in_field = true;
-                            current_entries.clear();
-                            current_comments.clear();
                         }
                         "entry" if in_field => {
-                            let attrs = extract_attributes(e)?;
-                            current_entry = Some(Entry::from_attributes(attrs)?);
+                            visitor.on_entry(extract_attributes(&e)?.as_map())?;
                             in_entry = true;
                         }
                         "comment" if in_field => {
-                            let attrs = extract_attributes(e)?;
-                            let comment_id = attrs.get("id").cloned().unwrap_or_default();
-                            current_comment = Some(Comment {
-                                comment_id: comment_id.to_string(),
-                                value: None,
-                            });
+                            visitor.on_comment(extract_attributes(&e)?.as_map())?;
                             in_comment = true;
                         }
                         "value" if in_entry || in_comment => {
-                            let attrs = extract_attributes(e)?;
-                            current_value = Some(Value::from_attributes(attrs)?);
                             in_value = true;
                             text_content.clear();
+                            text_start = Some(e.clone());
                         }
-                        "reason" if in_entry => {
-                            let attrs = extract_attributes(e)?;
-                            current_reason = Some(Reason::from_attributes(attrs)?);
+                        "reason" if in_entry || in_comment => {
                             in_reason = true;
                             text_content.clear();
+                            text_start = Some(e.clone());
+                        }
+                        name if in_record && is_unknown_element(name, record_tag, options) => {
+                            let element = read_unknown_element(&mut xml_reader, &e)?;
+                            visitor.on_unknown_element(element)?;
                         }
                         _ => {}
                     }
@@ -1058,80 +1267,53 @@ fn parse_site_xml(site_xml: &str) -> Result<Site, Error> {
                 text_content.push_str(&String::from_utf8_lossy(&e));
             }
 
+            Ok(Event::GeneralRef(e)) if (in_value || in_reason) => {
+                text_content.push_str(&resolve_reference(&e)?);
+            }
+
             Ok(Event::End(ref e)) => {
                 let name_bytes = e.local_name();
                 if let Ok(name) = std::str::from_utf8(name_bytes.as_ref()) {
                     match name {
-                        "site" => {
-                            if let Some(mut site) = current_site.take() {
-                                if !current_forms.is_empty() {
-                                    site.set_forms(std::mem::take(&mut current_forms));
-                                }
-                                current_site = Some(site);
-                            }
+                        name if name == record_tag && in_record => {
+                            visitor.on_record_end()?;
+                            in_record = false;
                         }
                         "form" if in_form => {
-                            if let Some(mut form) = current_form.take() {
-                                if !current_states.is_empty() {
-                                    form.states = Some(std::mem::take(&mut current_states));
-                                }
-                                if !current_categories.is_empty() {
-                                    form.categories = Some(std::mem::take(&mut current_categories));
-                                }
-                                current_forms.push(form);
-                            }
+                            visitor.on_form_end()?;
                             in_form = false;
                         }
                         "category" if in_category => {
-                            if let Some(mut category) = current_category.take() {
-                                if !current_fields.is_empty() {
-                                    category.fields = Some(std::mem::take(&mut current_fields));
-                                }
-                                current_categories.push(category);
-                            }
+                            visitor.on_category_end()?;
                             in_category = false;
                         }
                         "field" if in_field => {
-                            if let Some(mut field) = current_field.take() {
-                                if !current_entries.is_empty() {
-                                    field.entries = Some(std::mem::take(&mut current_entries));
-                                }
-                                if !current_comments.is_empty() {
-                                    field.comments = Some(std::mem::take(&mut current_comments));
-                                }
-                                current_fields.push(field);
-                            }
+                            visitor.on_field_end()?;
                             in_field = false;
                         }
                         "entry" if in_entry => {
-                            if let Some(entry) = current_entry.take() {
-                                current_entries.push(entry);
-                            }
+                            visitor.on_entry_end()?;
                             in_entry = false;
                         }
                         "comment" if in_comment => {
-                            if let Some(comment) = current_comment.take() {
-                                current_comments.push(comment);
-                            }
+                            visitor.on_comment_end()?;
                             in_comment = false;
                         }
                         "value" if in_value => {
-                            if let Some(mut value) = current_value.take() {
-                                value.value = std::mem::take(&mut text_content);
-                                if let Some(ref mut entry) = current_entry {
-                                    entry.value = Some(value);
-                                } else if let Some(ref mut comment) = current_comment {
-                                    comment.value = Some(value);
-                                }
+                            if let Some(start) = text_start.take() {
+                                visitor.on_value(
+                                    extract_attributes(&start)?.as_map(),
+                                    text_content.trim(),
+                                )?;
                             }
                             in_value = false;
                         }
                         "reason" if in_reason => {
-                            if let Some(mut reason) = current_reason.take() {
-                                reason.value = std::mem::take(&mut text_content);
-                                if let Some(ref mut entry) = current_entry {
-                                    entry.reason = Some(reason);
-                                }
+                            if let Some(start) = text_start.take() {
+                                visitor.on_reason(
+                                    extract_attributes(&start)?.as_map(),
+                                    text_content.trim(),
+                                )?;
                             }
                             in_reason = false;
                         }
@@ -1144,36 +1326,21 @@ fn parse_site_xml(site_xml: &str) -> Result<Site, Error> {
                 let name_bytes = e.local_name();
                 if let Ok(name) = std::str::from_utf8(name_bytes.as_ref()) {
                     match name {
-                        "state" if in_form => {
-                            let attrs = extract_attributes(e)?;
-                            let state = State::from_attributes(attrs)?;
-                            current_states.push(state);
-                        }
+                        "state" if in_form => visitor.on_state(extract_attributes(e)?.as_map())?,
                         "lockState" if in_form => {
-                            let attrs = extract_attributes(e)?;
-                            let lock_state = LockState::from_attributes(attrs)?;
-                            if let Some(ref mut form) = current_form {
-                                form.lock_state = Some(lock_state);
-                            }
+                            visitor.on_lock_state(extract_attributes(e)?.as_map())?
                         }
                         "field" if in_category => {
-                            let attrs = extract_attributes(e)?;
-                            let field = Field::from_attributes(attrs)?;
-                            current_fields.push(field);
+                            visitor.on_empty_field(extract_attributes(e)?.as_map())?
                         }
-                        "value" if in_entry => {
-                            let attrs = extract_attributes(e)?;
-                            let value = Value::from_attributes(attrs)?;
-                            if let Some(ref mut entry) = current_entry {
-                                entry.value = Some(value);
-                            }
+                        "value" if in_entry || in_comment => {
+                            visitor.on_value(extract_attributes(e)?.as_map(), "")?
                         }
-                        "reason" if in_entry => {
-                            let attrs = extract_attributes(e)?;
-                            let reason = Reason::from_attributes(attrs)?;
-                            if let Some(ref mut entry) = current_entry {
-                                entry.reason = Some(reason);
-                            }
+                        "reason" if in_entry || in_comment => {
+                            visitor.on_reason(extract_attributes(e)?.as_map(), "")?
+                        }
+                        name if in_record && is_unknown_element(name, record_tag, options) => {
+                            visitor.on_unknown_element(unknown_element(e)?)?
                         }
                         _ => {}
                     }
@@ -1184,32 +1351,230 @@ fn parse_site_xml(site_xml: &str) -> Result<Site, Error> {
         }
     }
 
-    current_site.ok_or_else(|| {
-        Error::ParsingError(quick_xml::de::DeError::Custom(
-            "No site found in chunk".to_string(),
-        ))
-    })
+    Ok(())
 }
 
-/// Parses a Prelude native user XML file into a `UserNative` struct.
-///
-/// # Example
-///
-/// ```
-/// use std::path::Path;
-///
-/// use prelude_xml_parser::parse_user_native_file;
-///
-/// let file_path = Path::new("tests/assets/user_native.xml");
-/// let native = parse_user_native_file(&file_path).unwrap();
+/// The visitor behind the parsers, building an `R` from each record it visits.
+struct RecordBuilder<R> {
+    options: ParseOptions,
+    records: Vec<R>,
+    record: Option<R>,
+    forms: Vec<Form>,
+    form: Option<Form>,
+    states: Vec<State>,
+    categories: Vec<Category>,
+    category: Option<Category>,
+    fields: Vec<Field>,
+    field: Option<Field>,
+    entries: Vec<Entry>,
+    entry: Option<Entry>,
+    comments: Vec<Comment>,
+    comment: Option<Comment>,
+}
+
+impl<R: NativeRecord> RecordBuilder<R> {
+    fn new(options: &ParseOptions) -> Self {
+        RecordBuilder {
+            options: *options,
+            records: Vec::new(),
+            record: None,
+            forms: Vec::new(),
+            form: None,
+            states: Vec::new(),
+            categories: Vec::new(),
+            category: None,
+            fields: Vec::new(),
+            field: None,
+            entries: Vec::new(),
+            entry: None,
+            comments: Vec::new(),
+            comment: None,
+        }
+    }
+}
+
+impl<R: NativeRecord> NativeVisitor for RecordBuilder<R> {
+    fn on_record_start(&mut self, attributes: HashMap<&str, &str>) -> Result<(), Error> {
+        self.record = Some(build_model(attributes, &self.options)?);
+        self.forms.clear();
+        Ok(())
+    }
+
+    fn on_record_end(&mut self) -> Result<(), Error> {
+        if let Some(mut record) = self.record.take() {
+            if !self.forms.is_empty() {
+                record.set_forms(std::mem::take(&mut self.forms));
+            }
+            self.records.push(record);
+        }
+        Ok(())
+    }
+
+    fn on_form(&mut self, attributes: HashMap<&str, &str>) -> Result<(), Error> {
+        self.form = Some(build_model(attributes, &self.options)?);
+        self.states.clear();
+        self.categories.clear();
+        Ok(())
+    }
+
+    fn on_form_end(&mut self) -> Result<(), Error> {
+        if let Some(mut form) = self.form.take() {
+            if !self.states.is_empty() {
+                form.states = Some(std::mem::take(&mut self.states));
+            }
+            if !self.categories.is_empty() {
+                form.categories = Some(std::mem::take(&mut self.categories));
+            }
+            self.forms.push(form);
+        }
+        Ok(())
+    }
+
+    fn on_state(&mut self, attributes: HashMap<&str, &str>) -> Result<(), Error> {
+        self.states.push(build_model(attributes, &self.options)?);
+        Ok(())
+    }
+
+    fn on_lock_state(&mut self, attributes: HashMap<&str, &str>) -> Result<(), Error> {
+        let lock_state: LockState = build_model(attributes, &self.options)?;
+        if let Some(ref mut form) = self.form {
+            form.lock_state = Some(lock_state);
+        }
+        Ok(())
+    }
+
+    fn on_category(&mut self, attributes: HashMap<&str, &str>) -> Result<(), Error> {
+        self.category = Some(build_model(attributes, &self.options)?);
+        self.fields.clear();
+        Ok(())
+    }
+
+    fn on_category_end(&mut self) -> Result<(), Error> {
+        if let Some(mut category) = self.category.take() {
+            if !self.fields.is_empty() {
+                category.fields = Some(std::mem::take(&mut self.fields));
+            }
+            self.categories.push(category);
+        }
+        Ok(())
+    }
+
+    fn on_field(&mut self, attributes: HashMap<&str, &str>) -> Result<(), Error> {
+        self.field = Some(build_model(attributes, &self.options)?);
+        self.entries.clear();
+        self.comments.clear();
+        Ok(())
+    }
+
+    fn on_field_end(&mut self) -> Result<(), Error> {
+        if let Some(mut field) = self.field.take() {
+            if !self.entries.is_empty() {
+                field.entries = Some(std::mem::take(&mut self.entries));
+            }
+            if !self.comments.is_empty() {
+                field.comments = Some(std::mem::take(&mut self.comments));
+            }
+            self.fields.push(field);
+        }
+        Ok(())
+    }
+
+    fn on_empty_field(&mut self, attributes: HashMap<&str, &str>) -> Result<(), Error> {
+        if R::KEEP_EMPTY_FIELDS {
+            self.fields.push(build_model(attributes, &self.options)?);
+        }
+        Ok(())
+    }
+
+    fn on_entry(&mut self, attributes: HashMap<&str, &str>) -> Result<(), Error> {
+        self.entry = Some(build_model(attributes, &self.options)?);
+        Ok(())
+    }
+
+    fn on_entry_end(&mut self) -> Result<(), Error> {
+        if let Some(entry) = self.entry.take() {
+            self.entries.push(entry);
+        }
+        Ok(())
+    }
+
+    fn on_comment(&mut self, attributes: HashMap<&str, &str>) -> Result<(), Error> {
+        self.comment = Some(build_model(attributes, &self.options)?);
+        Ok(())
+    }
+
+    fn on_comment_end(&mut self) -> Result<(), Error> {
+        if let Some(comment) = self.comment.take() {
+            self.comments.push(comment);
+        }
+        Ok(())
+    }
+
+    fn on_value(&mut self, attributes: HashMap<&str, &str>, value: &str) -> Result<(), Error> {
+        let mut model: Value = build_model(attributes, &self.options)?;
+        model.value = value.to_string();
+        if let Some(ref mut entry) = self.entry {
+            entry.value = Some(model);
+        } else if let Some(ref mut comment) = self.comment {
+            comment.push_value(model);
+        }
+        Ok(())
+    }
+
+    fn on_reason(&mut self, attributes: HashMap<&str, &str>, value: &str) -> Result<(), Error> {
+        let mut model: Reason = build_model(attributes, &self.options)?;
+        model.value = value.to_string();
+        if let Some(ref mut entry) = self.entry {
+            entry.reason = Some(model);
+        } else if let Some(ref mut comment) = self.comment {
+            comment.reason = Some(model);
+        }
+        Ok(())
+    }
+
+    fn on_unknown_element(&mut self, element: XmlElement) -> Result<(), Error> {
+        push_unknown_element(
+            element,
+            [
+                self.comment.as_mut().map(|c| &mut c.extra_elements),
+                self.entry.as_mut().map(|e| &mut e.extra_elements),
+                self.field.as_mut().map(|f| &mut f.extra_elements),
+                self.category.as_mut().map(|c| &mut c.extra_elements),
+                self.form.as_mut().map(|f| &mut f.extra_elements),
+                self.record.as_mut().map(|r| r.extra_elements_mut()),
+            ],
+        );
+        Ok(())
+    }
+}
+
+/// Parses a Prelude native user XML file into a `UserNative` struct.
+///
+/// # Example
+///
+/// ```
+/// use std::path::Path;
+///
+/// use prelude_xml_parser::parse_user_native_file;
+///
+/// let file_path = Path::new("tests/assets/user_native.xml");
+/// let native = parse_user_native_file(&file_path).unwrap();
 ///
 /// assert!(native.users.len() >= 1, "Vector length is less than 1");
 /// ```
 pub fn parse_user_native_file(xml_path: &Path) -> Result<UserNative, Error> {
+    parse_user_native_file_with_options(xml_path, &ParseOptions::default())
+}
+
+/// Parses a Prelude native user XML file like [`parse_user_native_file`], with `options`.
+pub fn parse_user_native_file_with_options(
+    xml_path: &Path,
+    options: &ParseOptions,
+) -> Result<UserNative, Error> {
     check_valid_xml_file(xml_path)?;
 
     let xml_file = read_to_string(xml_path)?;
-    let native = parse_user_native_string(&xml_file)?;
+    let native = parse_user_native_string_with_options(&xml_file, options)?;
 
     Ok(native)
 }
@@ -1219,7 +1584,7 @@ pub fn parse_user_native_file(xml_path: &Path) -> Result<UserNative, Error> {
 /// # Example
 ///
 /// ```
-/// use chrono::{DateTime, Utc};
+/// use chrono::DateTime;
 /// use prelude_xml_parser::parse_user_native_string;
 /// use prelude_xml_parser::native::user_native::*;
 ///
@@ -1258,9 +1623,8 @@ pub fn parse_user_native_file(xml_path: &Path) -> Result<UserNative, Error> {
 ///         forms: Some(vec![Form {
 ///             name: "form.name.demographics".to_string(),
 ///             last_modified: Some(
-///                 DateTime::parse_from_rfc3339("2023-08-07T15:15:41Z")
-///                     .unwrap()
-///                     .with_timezone(&Utc),
+///                 DateTime::parse_from_rfc3339("2023-08-07T10:15:41-05:00")
+///                     .unwrap(),
 ///             ),
 ///             who_last_modified_name: Some("Paul Sanders".to_string()),
 ///             who_last_modified_role: Some("Project Manager".to_string()),
@@ -1280,10 +1644,10 @@ pub fn parse_user_native_file(xml_path: &Path) -> Result<UserNative, Error> {
 ///                 signer: "Paul Sanders - Project Manager".to_string(),
 ///                 signer_unique_id: "1681162687395".to_string(),
 ///                 date_signed: Some(
-///                     DateTime::parse_from_rfc3339("2023-08-07T15:15:41Z")
-///                         .unwrap()
-///                         .with_timezone(&Utc),
+///                     DateTime::parse_from_rfc3339("2023-08-07T10:15:41-05:00")
+///                         .unwrap(),
 ///                 ),
+///                 extra: Default::default(),
 ///             }]),
 ///             categories: Some(vec![
 ///                         Category {
@@ -1296,21 +1660,21 @@ pub fn parse_user_native_file(xml_path: &Path) -> Result<UserNative, Error> {
 ///                                     field_type: "text".to_string(),
 ///                                     data_type: Some("string".to_string()),
 ///                                     error_code: "undefined".to_string(),
-///                                     when_created: Some(DateTime::parse_from_rfc3339("2024-01-12T20:14:09Z")
-///                                         .unwrap()
-///                                         .with_timezone(&Utc)),
+///                                     when_created: Some(DateTime::parse_from_rfc3339("2024-01-12T14:14:09-06:00")
+///                                         .unwrap()),
 ///                                     keep_history: true,
 ///                                     entries: None,
 ///                                     comments: None,
+///                                     extra: Default::default(),
+///                                     extra_elements: Default::default(),
 ///                                 },
 ///                                 Field {
 ///                                     name: "email".to_string(),
 ///                                     field_type: "text".to_string(),
 ///                                     data_type: Some("string".to_string()),
 ///                                     error_code: "undefined".to_string(),
-///                                     when_created: Some(DateTime::parse_from_rfc3339("2023-08-07T15:15:41Z")
-///                                         .unwrap()
-///                                         .with_timezone(&Utc)),
+///                                     when_created: Some(DateTime::parse_from_rfc3339("2023-08-07T10:15:41-05:00")
+///                                         .unwrap()),
 ///                                     keep_history: true,
 ///                                     entries: Some(vec![Entry {
 ///                                         entry_id: "1".to_string(),
@@ -1321,16 +1685,22 @@ pub fn parse_user_native_file(xml_path: &Path) -> Result<UserNative, Error> {
 ///                                             by: "Paul Sanders".to_string(),
 ///                                             by_unique_id: Some("1681162687395".to_string()),
 ///                                             role: "Project Manager".to_string(),
-///                                             when: Some(DateTime::parse_from_rfc3339("2023-08-07T15:15:41Z")
-///                                                 .unwrap()
-///                                                 .with_timezone(&Utc)),
+///                                             when: Some(DateTime::parse_from_rfc3339("2023-08-07T10:15:41-05:00")
+///                                                 .unwrap()),
 ///                                             value: "jazz@artemis.com".to_string(),
+///                                             extra: Default::default(),
 ///                                         }),
 ///                                         reason: None,
+///                                         extra: Default::default(),
+///                                         extra_elements: Default::default(),
 ///                                     }]),
 ///                                     comments: None,
+///                                     extra: Default::default(),
+///                                     extra_elements: Default::default(),
 ///                                 },
 ///                             ]),
+///                             extra: Default::default(),
+///                             extra_elements: Default::default(),
 ///                         },
 ///                         Category {
 ///                             name: "Administrative".to_string(),
@@ -1342,9 +1712,8 @@ pub fn parse_user_native_file(xml_path: &Path) -> Result<UserNative, Error> {
 ///                                     field_type: "text".to_string(),
 ///                                     data_type: None,
 ///                                     error_code: "undefined".to_string(),
-///                                     when_created: Some(DateTime::parse_from_rfc3339("2023-08-07T15:15:41Z")
-///                                         .unwrap()
-///                                         .with_timezone(&Utc)),
+///                                     when_created: Some(DateTime::parse_from_rfc3339("2023-08-07T10:15:41-05:00")
+///                                         .unwrap()),
 ///                                     keep_history: true,
 ///                                     entries: Some(vec![
 ///                                         Entry {
@@ -1356,29 +1725,46 @@ pub fn parse_user_native_file(xml_path: &Path) -> Result<UserNative, Error> {
 ///                                                 by: "set from calculation".to_string(),
 ///                                                 by_unique_id: None,
 ///                                                 role: "System".to_string(),
-///                                                 when: Some(DateTime::parse_from_rfc3339("2023-08-07T15:15:41Z")
-///                                                     .unwrap()
-///                                                     .with_timezone(&Utc)),
+///                                                 when: Some(DateTime::parse_from_rfc3339("2023-08-07T10:15:41-05:00")
+///                                                     .unwrap()),
 ///                                                 value: "On 07-Aug-2023 10:15 -0500, Paul Sanders assigned user from another study".to_string(),
+///                                                 extra: Default::default(),
 ///                                             }),
 ///                                             reason: Some(Reason {
 ///                                                 by: "set from calculation".to_string(),
 ///                                                 by_unique_id: None,
 ///                                                 role: "System".to_string(),
-///                                                 when: Some(DateTime::parse_from_rfc3339("2023-08-07T15:15:41Z")
-///                                                     .unwrap()
-///                                                     .with_timezone(&Utc)),
+///                                                 when: Some(DateTime::parse_from_rfc3339("2023-08-07T10:15:41-05:00")
+///                                                     .unwrap()),
 ///                                                 value: "calculated value".to_string(),
+///                                                 extra: Default::default(),
 ///                                             }),
+///                                             extra: Default::default(),
+///                                             extra_elements: Default::default(),
 ///                                         },
 ///                                     ]),
 ///                                     comments: None,
+///                                     extra: Default::default(),
+///                                     extra_elements: Default::default(),
 ///                                 },
 ///                             ]),
+///                             extra: Default::default(),
+///                             extra_elements: Default::default(),
 ///                         },
 ///             ]),
+///             extra: Default::default(),
+///             extra_elements: Default::default(),
 ///         }]),
+///         extra: Default::default(),
+///         extra_elements: Default::default(),
 ///     }],
+///     export: Some(ExportInfo {
+///         date: Some(DateTime::parse_from_rfc3339("2024-06-02T06:59:00-05:00").unwrap()),
+///         created_by: "Paul Sanders".to_string(),
+///         role: "Project Manager".to_string(),
+///         number_subjects_processed: 3,
+///         extra: Default::default(),
+///     }),
 /// };
 ///
 /// let result = parse_user_native_string(xml).unwrap();
@@ -1386,239 +1772,19 @@ pub fn parse_user_native_file(xml_path: &Path) -> Result<UserNative, Error> {
 /// assert_eq!(result, expected);
 /// ```
 pub fn parse_user_native_string(xml_str: &str) -> Result<UserNative, Error> {
-    let chunks = extract_user_chunks(xml_str);
-    let users = chunks
-        .into_par_iter()
-        .map(parse_user_xml)
-        .collect::<Result<Vec<_>, _>>()?;
-    Ok(UserNative { users })
-}
-
-fn extract_user_chunks(xml: &str) -> Vec<&str> {
-    let mut chunks = Vec::new();
-    let mut pos = 0;
-    loop {
-        match xml[pos..].find("<user ") {
-            None => break,
-            Some(rel) => {
-                let start = pos + rel;
-                match xml[start..].find("</user>") {
-                    None => break,
-                    Some(rel_end) => {
-                        let end = start + rel_end + "</user>".len();
-                        chunks.push(&xml[start..end]);
-                        pos = end;
-                    }
-                }
-            }
-        }
-    }
-    chunks
+    parse_user_native_string_with_options(xml_str, &ParseOptions::default())
 }
 
-fn parse_user_xml(user_xml: &str) -> Result<User, Error> {
-    let mut xml_reader = Reader::from_str(user_xml);
-    xml_reader.config_mut().trim_text(true);
-
-    let mut current_user: Option<User> = None;
-    let mut current_forms: Vec<Form> = Vec::new();
-    let mut current_form: Option<Form> = None;
-    let mut current_states: Vec<State> = Vec::new();
-    let mut current_categories: Vec<Category> = Vec::new();
-    let mut current_category: Option<Category> = None;
-    let mut current_fields: Vec<Field> = Vec::new();
-    let mut current_field: Option<Field> = None;
-    let mut current_entries: Vec<Entry> = Vec::new();
-    let mut current_entry: Option<Entry> = None;
-    let mut current_comments: Vec<Comment> = Vec::new();
-    let mut current_comment: Option<Comment> = None;
-    let mut current_value: Option<Value> = None;
-    let mut current_reason: Option<Reason> = None;
-    let mut text_content = String::new();
-    let mut in_form = false;
-    let mut in_category = false;
-    let mut in_field = false;
-    let mut in_entry = false;
-    let mut in_comment = false;
-    let mut in_value = false;
-    let mut in_reason = false;
-
-    loop {
-        match xml_reader.read_event() {
-            Err(e) => {
-                return Err(Error::ParsingError(quick_xml::de::DeError::Custom(
-                    format!("XML reading error: {}", e),
-                )))
-            }
-            Ok(Event::Eof) => break,
-
-            Ok(Event::Start(ref e)) => {
-                let name_bytes = e.local_name();
-                if let Ok(name) = std::str::from_utf8(name_bytes.as_ref()) {
-                    match name {
-                        "user" => {
-                            let attrs = extract_attributes(e)?;
-                            current_user = Some(User::from_attributes(attrs)?);
-                        }
-                        "form" => {
-                            let attrs = extract_attributes(e)?;
-                            current_form = Some(Form::from_attributes(attrs)?);
-                            in_form = true;
-                        }
-                        "category" if in_form => {
-                            let attrs = extract_attributes(e)?;
-                            current_category = Some(Category::from_attributes(attrs)?);
-                            in_category = true;
-                        }
-                        "field" if in_category => {
-                            let attrs = extract_attributes(e)?;
-                            current_field = Some(Field::from_attributes(attrs)?);
-                            in_field = true;
-                        }
-                        "entry" if in_field => {
-                            let attrs = extract_attributes(e)?;
-                            current_entry = Some(Entry::from_attributes(attrs)?);
-                            in_entry = true;
-                        }
-                        "comment" if in_field => {
-                            let attrs = extract_attributes(e)?;
-                            let comment_id = attrs.get("id").cloned().unwrap_or_default();
-                            current_comment = Some(Comment {
-                                comment_id: comment_id.to_string(),
-                                value: None,
-                            });
-                            in_comment = true;
-                        }
-                        "value" if in_entry || in_comment => {
-                            let attrs = extract_attributes(e)?;
-                            current_value = Some(Value::from_attributes(attrs)?);
-                            in_value = true;
-                            text_content.clear();
-                        }
-                        "reason" if in_entry => {
-                            let attrs = extract_attributes(e)?;
-                            current_reason = Some(Reason::from_attributes(attrs)?);
-                            in_reason = true;
-                            text_content.clear();
-                        }
-                        _ => {}
-                    }
-                }
-            }
-
-            Ok(Event::Text(e)) if (in_value || in_reason) => {
-                text_content.push_str(&String::from_utf8_lossy(&e));
-            }
-
-            Ok(Event::End(ref e)) => {
-                let name_bytes = e.local_name();
-                if let Ok(name) = std::str::from_utf8(name_bytes.as_ref()) {
-                    match name {
-                        "user" => {
-                            if let Some(mut user) = current_user.take() {
-                                if !current_forms.is_empty() {
-                                    user.set_forms(std::mem::take(&mut current_forms));
-                                }
-                                current_user = Some(user);
-                            }
-                        }
-                        "form" if in_form => {
-                            if let Some(mut form) = current_form.take() {
-                                if !current_states.is_empty() {
-                                    form.states = Some(std::mem::take(&mut current_states));
-                                }
-                                if !current_categories.is_empty() {
-                                    form.categories = Some(std::mem::take(&mut current_categories));
-                                }
-                                current_forms.push(form);
-                            }
-                            in_form = false;
-                        }
-                        "category" if in_category => {
-                            if let Some(mut category) = current_category.take() {
-                                if !current_fields.is_empty() {
-                                    category.fields = Some(std::mem::take(&mut current_fields));
-                                }
-                                current_categories.push(category);
-                            }
-                            in_category = false;
-                        }
-                        "field" if in_field => {
-                            if let Some(mut field) = current_field.take() {
-                                if !current_entries.is_empty() {
-                                    field.entries = Some(std::mem::take(&mut current_entries));
-                                }
-                                if !current_comments.is_empty() {
-                                    field.comments = Some(std::mem::take(&mut current_comments));
-                                }
-                                current_fields.push(field);
-                            }
-                            in_field = false;
-                        }
-                        "entry" if in_entry => {
-                            if let Some(entry) = current_entry.take() {
-                                current_entries.push(entry);
-                            }
-                            in_entry = false;
-                        }
-                        "comment" if in_comment => {
-                            if let Some(comment) = current_comment.take() {
-                                current_comments.push(comment);
-                            }
-                            in_comment = false;
-                        }
-                        "value" if in_value => {
-                            if let Some(mut value) = current_value.take() {
-                                value.value = std::mem::take(&mut text_content);
-                                if let Some(ref mut entry) = current_entry {
-                                    entry.value = Some(value);
-                                } else if let Some(ref mut comment) = current_comment {
-                                    comment.value = Some(value);
-                                }
-                            }
-                            in_value = false;
-                        }
-                        "reason" if in_reason => {
-                            if let Some(mut reason) = current_reason.take() {
-                                reason.value = std::mem::take(&mut text_content);
-                                if let Some(ref mut entry) = current_entry {
-                                    entry.reason = Some(reason);
-                                }
-                            }
-                            in_reason = false;
-                        }
-                        _ => {}
-                    }
-                }
-            }
-
-            Ok(Event::Empty(ref e)) => {
-                let name_bytes = e.local_name();
-                if let Ok(name) = std::str::from_utf8(name_bytes.as_ref()) {
-                    match name {
-                        "state" if in_form => {
-                            let attrs = extract_attributes(e)?;
-                            let state = State::from_attributes(attrs)?;
-                            current_states.push(state);
-                        }
-                        "field" if in_category => {
-                            let attrs = extract_attributes(e)?;
-                            let field = Field::from_attributes(attrs)?;
-                            current_fields.push(field);
-                        }
-                        _ => {}
-                    }
-                }
-            }
-
-            _ => {}
-        }
-    }
-
-    current_user.ok_or_else(|| {
-        Error::ParsingError(quick_xml::de::DeError::Custom(
-            "No user element found".to_string(),
-        ))
+/// Parses a string of Prelude native user XML like [`parse_user_native_string`], with
+/// `options`.
+pub fn parse_user_native_string_with_options(
+    xml_str: &str,
+    options: &ParseOptions,
+) -> Result<UserNative, Error> {
+    let users = parse_native_records(xml_str, options)?;
+    Ok(UserNative {
+        users,
+        export: parse_export_info(xml_str, options)?,
     })
 }
 
@@ -1867,6 +2033,222 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_comment_with_replies_and_reason() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<export_from_vision_EDC date="30-May-2024 10:35 -0500" createdBy="Test User" role="Project Manager" numberSubjectsProcessed="1">
+    <patient patientId="TEST-003" uniqueId="123456791" whenCreated="2023-04-15 12:09:02 -0400" creator="Test User" siteName="Test Site" siteUniqueId="987654321" lastLanguage="English" numberOfForms="1">
+        <form name="test.form.with.thread" lastModified="2023-04-15 12:09:15 -0400" whoLastModifiedName="Test User" whoLastModifiedRole="Tester" whenCreated="123456789" hasErrors="false" hasWarnings="false" locked="false" user="" dateTimeChanged="" formTitle="Test Form With Thread" formIndex="1" formGroup="Test Group" formState="In-Work">
+            <category name="Test Category" type="normal" highestIndex="0">
+                <field name="field_with_thread" type="text" dataType="string" errorCode="valid" whenCreated="2023-04-15 12:08:26 -0400" keepHistory="true">
+                    <entry id="1">
+                        <value by="Test User" byUniqueId="111111111" role="Tester" when="2023-04-15 12:09:02 -0400" xml:space="preserve">Field Value</value>
+                    </entry>
+                    <comment id="1">
+                        <value by="Test User" byUniqueId="111111111" role="Tester" when="2023-04-15 12:09:05 -0400" xml:space="preserve">Please confirm</value>
+                        <value by="Another User" byUniqueId="222222222" role="Reviewer" when="2023-04-16 09:00:00 -0400" xml:space="preserve">Confirmed</value>
+                        <reason by="Another User" byUniqueId="222222222" role="Reviewer" when="2023-04-16 09:00:00 -0400" xml:space="preserve">Source verified</reason>
+                    </comment>
+                </field>
+            </category>
+        </form>
+    </patient>
+</export_from_vision_EDC>"#;
+
+        let result = parse_subject_native_string(xml).expect("Should parse successfully");
+
+        let field = &result.patients[0].forms.as_ref().unwrap()[0]
+            .categories
+            .as_ref()
+            .unwrap()[0]
+            .fields
+            .as_ref()
+            .unwrap()[0];
+        let comment = &field.comments.as_ref().expect("Field should have comments")[0];
+        assert_eq!(comment.value.as_ref().unwrap().value, "Please confirm");
+        let replies = comment
+            .replies
+            .as_ref()
+            .expect("Comment should have replies");
+        assert_eq!(replies.len(), 1);
+        assert_eq!(replies[0].value, "Confirmed");
+        assert_eq!(replies[0].by, "Another User");
+        let reason = comment
+            .reason
+            .as_ref()
+            .expect("Comment should have a reason");
+        assert_eq!(reason.value, "Source verified");
+        assert_eq!(
+            comment
+                .values()
+                .map(|v| v.value.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Please confirm", "Confirmed"]
+        );
+
+        let written = result.to_xml().unwrap();
+        assert_eq!(parse_subject_native_string(&written).unwrap(), result);
+    }
+
+    #[test]
+    fn test_repeating_log_forms() {
+        let native =
+            parse_subject_native_file(Path::new("tests/assets/subject_native_repeating.xml"))
+                .unwrap();
+        let forms = native.patients[0].forms.as_ref().unwrap();
+
+        let categories = forms[0].categories.as_ref().unwrap();
+        let summary = &categories[0];
+        assert!(!summary.is_repeating());
+        assert_eq!(summary.instances().len(), 1);
+        assert_eq!(summary.instances()[0].index, None);
+
+        let adverse_events = &categories[1];
+        assert!(adverse_events.is_repeating());
+        let instances = adverse_events.instances();
+        assert_eq!(
+            instances.iter().map(|i| i.index).collect::<Vec<_>>(),
+            vec![Some(1), Some(2), Some(3)]
+        );
+        let names = |i: usize| {
+            instances[i]
+                .fields
+                .iter()
+                .map(|f| f.base_name())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(0), vec!["ae_term", "ae_start_date", "ae_serious"]);
+        // Empty fields are left out, so the third adverse event has no fields
+        assert_eq!(names(1), vec!["ae_term", "ae_start_date"]);
+        assert!(names(2).is_empty());
+        let term = instances[1].fields[0];
+        assert_eq!(term.name, "ae_term(2)");
+        assert_eq!(term.repeat_index(), Some(2));
+        assert_eq!(term.entries.as_ref().unwrap().len(), 2);
+
+        let medications = &forms[1].categories.as_ref().unwrap()[0];
+        assert_eq!(forms[1].form_title, "Concomitant Medications");
+        assert_eq!(medications.instances().len(), 2);
+        assert_eq!(
+            medications.instances()[1].fields[0]
+                .entries
+                .as_ref()
+                .unwrap()[0]
+                .value
+                .as_ref()
+                .unwrap()
+                .value,
+            "Omeprazole"
+        );
+    }
+
+    #[test]
+    fn test_repeat_index_parsing() {
+        let field = |name: &str| Field {
+            name: name.to_string(),
+            field_type: "text".to_string(),
+            data_type: None,
+            error_code: "valid".to_string(),
+            when_created: None,
+            keep_history: true,
+            entries: None,
+            comments: None,
+            extra: Default::default(),
+            extra_elements: Default::default(),
+        };
+
+        assert_eq!(field("date_field(1)").repeat_index(), Some(1));
+        assert_eq!(field("date_field(1)").base_name(), "date_field");
+        assert_eq!(field("ae_term(12)").repeat_index(), Some(12));
+        assert_eq!(field("hide_study_exit_7").repeat_index(), None);
+        assert_eq!(field("hide_study_exit_7").base_name(), "hide_study_exit_7");
+        assert_eq!(field("dose(mg)").repeat_index(), None);
+        assert_eq!(field("dose(mg)").base_name(), "dose(mg)");
+    }
+
+    #[test]
+    fn test_keep_unknown_attributes_and_elements() {
+        let path = Path::new("tests/assets/subject_native_unknown.xml");
+        let options = ParseOptions { keep_unknown: true };
+
+        let result = parse_subject_native_file_with_options(path, &options).unwrap();
+
+        let export = result.export.as_ref().unwrap();
+        assert_eq!(export.extra["exportVersion"], "2");
+        let patient = &result.patients[0];
+        assert_eq!(patient.extra["enrollmentStatus"], "Enrolled");
+        assert_eq!(
+            patient.extra_elements,
+            vec![XmlElement {
+                name: "visitPlan".to_string(),
+                attributes: BTreeMap::from([("name".to_string(), "Standard".to_string())]),
+                ..XmlElement::default()
+            }]
+        );
+
+        let form = &patient.forms.as_ref().unwrap()[0];
+        assert_eq!(form.extra["visitWindow"], "0");
+        assert!(form.states.as_ref().unwrap()[0].extra.is_empty());
+        let signature = &form.extra_elements[0];
+        assert_eq!(signature.name, "signature");
+        assert_eq!(signature.attributes["meaning"], "Author");
+        assert_eq!(signature.children[0].name, "note");
+        assert_eq!(signature.children[0].text, "Signed on &lt;paper&gt;");
+        assert_eq!(signature.children[1].attributes["name"], "Some User");
+
+        let field = &form.categories.as_ref().unwrap()[0]
+            .fields
+            .as_ref()
+            .unwrap()[0];
+        assert_eq!(field.extra["sdvRequired"], "true");
+        let entry = &field.entries.as_ref().unwrap()[0];
+        assert!(entry.extra.is_empty());
+        assert_eq!(entry.extra_elements[0].name, "audit");
+        let value = entry.value.as_ref().unwrap();
+        assert_eq!(value.value, "Labrador");
+        assert_eq!(
+            value.extra,
+            BTreeMap::from([("source".to_string(), "device".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_unknown_attributes_and_elements_dropped_by_default() {
+        let path = Path::new("tests/assets/subject_native_unknown.xml");
+
+        let result = parse_subject_native_file(path).unwrap();
+
+        assert!(result.export.as_ref().unwrap().extra.is_empty());
+        let patient = &result.patients[0];
+        assert!(patient.extra.is_empty());
+        assert!(patient.extra_elements.is_empty());
+        let form = &patient.forms.as_ref().unwrap()[0];
+        assert!(form.extra.is_empty());
+        assert!(form.extra_elements.is_empty());
+        assert_eq!(
+            result,
+            parse_subject_native_file_with_options(path, &ParseOptions::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_keep_unknown_in_site_and_user_natives() {
+        let options = ParseOptions { keep_unknown: true };
+        let site_xml = read_to_string("tests/assets/site_native.xml")
+            .unwrap()
+            .replacen("<site ", "<site region=\"North\" ", 1);
+        let user_xml = read_to_string("tests/assets/user_native.xml")
+            .unwrap()
+            .replacen("<user ", "<user department=\"Research\" ", 1);
+
+        let sites = parse_site_native_string_with_options(&site_xml, &options).unwrap();
+        let users = parse_user_native_string_with_options(&user_xml, &options).unwrap();
+
+        assert_eq!(sites.sites[0].extra["region"], "North");
+        assert!(sites.sites[1].extra.is_empty());
+        assert_eq!(users.users[0].extra["department"], "Research");
+    }
+
     #[test]
     fn test_empty_forms_handling() {
         let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -2075,4 +2457,305 @@ mod tests {
         );
         assert_eq!(reason.value, "Test Reason");
     }
+
+    #[test]
+    fn test_timezone_offsets_preserved() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<export_from_vision_EDC date="30-May-2024 10:35 -0500" createdBy="Test User" role="Project Manager" numberSubjectsProcessed="1">
+  <patient patientId="TEST-001" uniqueId="123456" whenCreated="2023-04-15 12:09:02 -0400" creator="Test User" siteName="Test Site" siteUniqueId="654321" lastLanguage="" numberOfForms="1">
+    <form name="test.form" lastModified="2023-12-15 17:00:00 -0500" whoLastModifiedName="Test User" whoLastModifiedRole="Manager" whenCreated="1681574905839" hasErrors="false" hasWarnings="false" locked="false" user="" dateTimeChanged="" formTitle="Test Form" formIndex="1" formGroup="Test" formState="In-Work">
+      <state value="form.state.in.work" signer="Test User - Manager" signerUniqueId="123456" dateSigned="2023-04-15 12:09:02 -0400" />
+      <category name="Test Category" type="normal" highestIndex="0">
+        <field name="test_field" type="text" dataType="string" errorCode="valid" whenCreated="2023-04-15 12:08:26 -0400" keepHistory="true">
+          <entry id="1">
+            <value by="Test User" byUniqueId="123456" role="Manager" when="2023-04-15 17:00:00 +0200" xml:space="preserve">Test Value</value>
+          </entry>
+        </field>
+      </category>
+    </form>
+  </patient>
+</export_from_vision_EDC>"#;
+
+        let native = parse_subject_native_string(xml).expect("Should parse successfully");
+        let patient = &native.patients[0];
+        let when_created = patient
+            .when_created
+            .expect("Patient should have whenCreated");
+        assert_eq!(when_created.offset().local_minus_utc(), -4 * 3600);
+        assert_eq!(when_created.to_rfc3339(), "2023-04-15T12:09:02-04:00");
+
+        let form = &patient.forms.as_ref().expect("Patient should have forms")[0];
+        let last_modified = form.last_modified.expect("Form should have lastModified");
+        assert_eq!(last_modified.offset().local_minus_utc(), -5 * 3600);
+        assert_eq!(last_modified.format("%H:%M").to_string(), "17:00");
+
+        let value = form.categories.as_ref().unwrap()[0]
+            .fields
+            .as_ref()
+            .unwrap()[0]
+            .entries
+            .as_ref()
+            .unwrap()[0]
+            .value
+            .as_ref()
+            .expect("Entry should have value");
+        let when = value.when.expect("Value should have when");
+        assert_eq!(when.to_rfc3339(), "2023-04-15T17:00:00+02:00");
+
+        let json = serde_json::to_string(&native).expect("Should serialize to JSON");
+        assert!(json.contains("\"whenCreated\":\"2023-04-15T12:09:02-04:00\""));
+        let round_trip: SubjectNative = serde_json::from_str(&json).expect("Should deserialize");
+        assert_eq!(round_trip, native);
+        assert_eq!(
+            round_trip.patients[0].forms.as_ref().unwrap()[0]
+                .last_modified
+                .unwrap()
+                .offset()
+                .local_minus_utc(),
+            -5 * 3600
+        );
+    }
+
+    #[test]
+    fn test_malformed_xml_error_position() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<export_from_vision_EDC date="30-May-2024 10:35 -0500" createdBy="Test User" role="Project Manager" numberSubjectsProcessed="2">
+  <patient patientId="TEST-001" uniqueId="1" whenCreated="" creator="Test User" siteName="Test Site" siteUniqueId="2" lastLanguage="" numberOfForms="0">
+  </patient>
+  <patient patientId="TEST-002" uniqueId="3" whenCreated="" creator="Test User" siteName="Test Site" siteUniqueId="2" lastLanguage="" numberOfForms="1">
+    <form name="test.form"></category>
+  </patient>
+</export_from_vision_EDC>"#;
+
+        let result = parse_subject_native_string(xml);
+
+        match result {
+            Err(Error::XmlError { position, .. }) => {
+                assert_eq!(position as usize, xml.find("</category>").unwrap());
+            }
+            other => panic!("Expected an XmlError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_native_records_with_custom_record() {
+        #[derive(Debug, Default)]
+        struct Query {
+            id: String,
+            forms: Vec<Form>,
+            extra: BTreeMap<String, String>,
+            extra_elements: Vec<XmlElement>,
+        }
+
+        impl FromAttributes for Query {
+            const ATTRIBUTES: &'static [&'static str] = &["id"];
+
+            fn from_attributes(attrs: HashMap<&str, &str>) -> Result<Self, Error> {
+                Ok(Query {
+                    id: attrs.get("id").copied().unwrap_or_default().to_string(),
+                    ..Query::default()
+                })
+            }
+
+            fn extra_mut(&mut self) -> &mut BTreeMap<String, String> {
+                &mut self.extra
+            }
+        }
+
+        impl NativeRecord for Query {
+            const TAG: &'static str = "query";
+
+            fn set_forms(&mut self, forms: Vec<Form>) {
+                self.forms = forms;
+            }
+
+            fn extra_elements_mut(&mut self) -> &mut Vec<XmlElement> {
+                &mut self.extra_elements
+            }
+        }
+
+        let xml = r#"
+            <export_from_vision_EDC>
+              <query id="1" status="open">
+                <form name="adverse" lastModified="" whoLastModifiedName="" whoLastModifiedRole="" whenCreated="1681574905839" hasErrors="false" hasWarnings="false" locked="false" user="" dateTimeChanged="" formTitle="Adverse" formIndex="1" formGroup="" formState="In-Work">
+                  <category name="Event" type="normal" highestIndex="0">
+                    <field name="ae_term" type="text" dataType="string" errorCode="valid" whenCreated="2023-04-15 12:08:26 -0400" keepHistory="true" />
+                  </category>
+                </form>
+                <assignee name="Some User" />
+              </query>
+              <query id="2"></query>
+            </export_from_vision_EDC>
+        "#;
+
+        let options = ParseOptions { keep_unknown: true };
+        let queries: Vec<Query> = parse_native_records(xml, &options).unwrap();
+
+        assert_eq!(queries.len(), 2);
+        assert_eq!(queries[0].id, "1");
+        assert_eq!(queries[0].extra["status"], "open");
+        assert_eq!(queries[0].extra_elements[0].name, "assignee");
+        let fields = queries[0].forms[0].categories.as_ref().unwrap()[0]
+            .fields
+            .as_ref()
+            .unwrap();
+        assert_eq!(fields[0].name, "ae_term");
+        assert!(queries[1].forms.is_empty());
+
+        let ids = iter_native_records::<Query>(xml, &ParseOptions::default())
+            .map(|query| query.map(|q| q.id))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(ids, vec!["1", "2"]);
+    }
+
+    #[test]
+    fn test_visit_native_string() {
+        #[derive(Default)]
+        struct Recorder {
+            events: Vec<String>,
+        }
+
+        impl NativeVisitor for Recorder {
+            fn on_record_start(&mut self, attributes: HashMap<&str, &str>) -> Result<(), Error> {
+                self.events
+                    .push(format!("record {}", attributes["patientId"]));
+                Ok(())
+            }
+
+            fn on_record_end(&mut self) -> Result<(), Error> {
+                self.events.push("/record".to_string());
+                Ok(())
+            }
+
+            fn on_form(&mut self, attributes: HashMap<&str, &str>) -> Result<(), Error> {
+                self.events.push(format!("form {}", attributes["name"]));
+                Ok(())
+            }
+
+            fn on_state(&mut self, attributes: HashMap<&str, &str>) -> Result<(), Error> {
+                self.events.push(format!("state {}", attributes["value"]));
+                Ok(())
+            }
+
+            fn on_field(&mut self, attributes: HashMap<&str, &str>) -> Result<(), Error> {
+                self.events.push(format!("field {}", attributes["name"]));
+                Ok(())
+            }
+
+            fn on_field_end(&mut self) -> Result<(), Error> {
+                self.events.push("/field".to_string());
+                Ok(())
+            }
+
+            fn on_value(&mut self, _: HashMap<&str, &str>, value: &str) -> Result<(), Error> {
+                self.events.push(format!("value {value}"));
+                Ok(())
+            }
+
+            fn on_reason(&mut self, _: HashMap<&str, &str>, value: &str) -> Result<(), Error> {
+                self.events.push(format!("reason {value}"));
+                Ok(())
+            }
+
+            fn on_unknown_element(&mut self, element: XmlElement) -> Result<(), Error> {
+                self.events.push(format!("unknown {}", element.name));
+                Ok(())
+            }
+        }
+
+        let xml = r#"
+            <export_from_vision_EDC>
+              <patient patientId="ABC-001">
+                <form name="demographics">
+                  <state value="form.state.in.work" />
+                  <category name="Demographics">
+                    <field name="breed">
+                      <entry id="1">
+                        <value by="Paul Sanders">Labrador</value>
+                        <reason by="Paul Sanders">Typo</reason>
+                      </entry>
+                      <comment id="1">
+                        <value by="Paul Sanders" />
+                      </comment>
+                    </field>
+                    <field name="weight" />
+                  </category>
+                  <signature by="Paul Sanders" />
+                </form>
+              </patient>
+              <patient patientId="ABC-002"></patient>
+            </export_from_vision_EDC>
+        "#;
+
+        let mut recorder = Recorder::default();
+        let options = ParseOptions { keep_unknown: true };
+        visit_native_string(xml, "patient", &mut recorder, &options).unwrap();
+
+        assert_eq!(
+            recorder.events,
+            vec![
+                "record ABC-001",
+                "form demographics",
+                "state form.state.in.work",
+                "field breed",
+                "value Labrador",
+                "reason Typo",
+                "value ",
+                "/field",
+                "field weight",
+                "/field",
+                "unknown signature",
+                "/record",
+                "record ABC-002",
+                "/record",
+            ]
+        );
+
+        struct Failing;
+
+        impl NativeVisitor for Failing {
+            fn on_form(&mut self, _: HashMap<&str, &str>) -> Result<(), Error> {
+                Err(Error::Unknown)
+            }
+        }
+
+        assert!(matches!(
+            visit_native_string(xml, "patient", &mut Failing, &ParseOptions::default()),
+            Err(Error::Unknown)
+        ));
+    }
+
+    // The snapshots are shared by the default and `python` builds, so running the tests with and
+    // without the feature checks that both serialize to the same JSON.
+    #[test]
+    fn test_subject_native_json_snapshot() {
+        let native =
+            parse_subject_native_file(Path::new("tests/assets/subject_native.xml")).unwrap();
+        insta::assert_json_snapshot!(native);
+
+        let json = serde_json::to_string(&native).unwrap();
+        assert_eq!(
+            serde_json::from_str::<SubjectNative>(&json).unwrap(),
+            native
+        );
+    }
+
+    #[test]
+    fn test_site_native_json_snapshot() {
+        let native = parse_site_native_file(Path::new("tests/assets/site_native.xml")).unwrap();
+        insta::assert_json_snapshot!(native);
+
+        let json = serde_json::to_string(&native).unwrap();
+        assert_eq!(serde_json::from_str::<SiteNative>(&json).unwrap(), native);
+    }
+
+    #[test]
+    fn test_user_native_json_snapshot() {
+        let native = parse_user_native_file(Path::new("tests/assets/user_native.xml")).unwrap();
+        insta::assert_json_snapshot!(native);
+
+        let json = serde_json::to_string(&native).unwrap();
+        assert_eq!(serde_json::from_str::<UserNative>(&json).unwrap(), native);
+    }
 }
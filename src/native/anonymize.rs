@@ -0,0 +1,705 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate};
+use regex::Regex;
+use sha2::{Digest, Sha256};
+
+#[cfg(feature = "python")]
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::errors::Error;
+use crate::native::{
+    common::{ExportInfo, Form, Value, XmlElement},
+    subject_native::{Patient, SubjectNative},
+    user_native::{User, UserNative},
+};
+
+/// Formats of dates that can be shifted and generalized, written back in the format they were
+/// read in.
+const DATE_FORMATS: [&str; 2] = ["%d-%b-%Y", "%Y-%m-%d"];
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S %z";
+
+/// Keeps hashed values and pseudonyms short, 64 bits is plenty to keep them apart.
+const HASH_BYTES: usize = 8;
+
+/// What to do with the fields a rule matches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldAction {
+    /// Remove the field with its entries and comments.
+    Drop,
+
+    /// Replace each value with its salted hash, so equal values stay equal.
+    Hash,
+
+    /// Move each date by the date offset of the patient or user it belongs to.
+    ShiftDate,
+
+    /// Replace each value with a coarser one.
+    Generalize(Generalization),
+}
+
+/// How [`FieldAction::Generalize`] coarsens a value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Generalization {
+    /// Keep only the year of a date, e.g. "15-Apr-2020" becomes "2020".
+    Year,
+
+    /// Round a number down into a range of the given width, e.g. 37 becomes "30-39" with a
+    /// width of 10.
+    NumberRange(u32),
+
+    /// Keep the first characters of a value, e.g. of a postal code.
+    Prefix(usize),
+}
+
+/// De-identifies subject and user natives.
+///
+/// Fields are anonymized by the first rule whose pattern matches the field name. Values a rule
+/// cannot shift or generalize, e.g. a date in an unknown format, are cleared. Comments on
+/// fields matched by a rule are removed, as they are free text that may repeat the value.
+///
+/// The names and unique ids of the users who entered, signed, reviewed, or locked data are
+/// always replaced with pseudonyms, as are patient ids and the unique ids of patients and users.
+/// Pseudonyms are salted hashes, so the same salt gives the same pseudonym for a user across
+/// exports and a user's pseudonymized `unique_id` still matches the `by_unique_id` of the
+/// values they entered.
+///
+/// Attributes and elements kept with `ParseOptions::keep_unknown` are removed, as there is no
+/// telling what they hold.
+///
+/// # Example
+///
+/// ```
+/// use std::path::Path;
+///
+/// use prelude_xml_parser::native::anonymize::{Anonymizer, FieldAction, Generalization};
+/// use prelude_xml_parser::parse_subject_native_file;
+///
+/// let native = parse_subject_native_file(Path::new("tests/assets/subject_native.xml")).unwrap();
+/// let anonymizer = Anonymizer::new("study salt")
+///     .rule("^(first|last)_name$", FieldAction::Drop)
+///     .unwrap()
+///     .rule("^dob$", FieldAction::Generalize(Generalization::Year))
+///     .unwrap();
+///
+/// let anonymized = native.anonymize(&anonymizer);
+/// let json = anonymized.to_json().unwrap();
+///
+/// assert!(!json.contains("Imma"));
+/// assert!(!json.contains("Paul Sanders"));
+/// assert!(json.contains(&anonymizer.pseudonym("Paul Sanders")));
+/// ```
+#[cfg_attr(
+    feature = "python",
+    pyclass(module = "prelude_xml_parser", skip_from_py_object)
+)]
+#[derive(Clone, Debug)]
+pub struct Anonymizer {
+    salt: String,
+    rules: Vec<(Regex, FieldAction)>,
+    max_date_shift: u32,
+    shift_timestamps: bool,
+}
+
+impl Anonymizer {
+    /// Create an anonymizer without field rules.
+    ///
+    /// Keep the salt secret, anyone who has it can test whether a hash or pseudonym belongs to
+    /// a given value.
+    pub fn new(salt: impl Into<String>) -> Self {
+        Self {
+            salt: salt.into(),
+            rules: Vec::new(),
+            max_date_shift: 365,
+            shift_timestamps: false,
+        }
+    }
+
+    /// Apply `action` to the fields whose name matches the regular expression `pattern`.
+    ///
+    /// Rules are tried in the order they were added. The pattern matches anywhere in the name,
+    /// anchor it with `^` and `$` to match whole names.
+    pub fn rule(mut self, pattern: &str, action: FieldAction) -> Result<Self, Error> {
+        self.rules.push((Regex::new(pattern)?, action));
+        Ok(self)
+    }
+
+    /// The largest number of days dates are shifted by, in either direction. Defaults to 365.
+    ///
+    /// Each patient or user gets its own offset, derived from the salt and its unique id, so
+    /// intervals between its dates are kept.
+    pub fn max_date_shift(mut self, days: u32) -> Self {
+        self.max_date_shift = days;
+        self
+    }
+
+    /// Also shift the audit timestamps, e.g. when values were entered or forms signed, by the
+    /// date offset of the patient or user. Defaults to `false`.
+    pub fn shift_timestamps(mut self, shift: bool) -> Self {
+        self.shift_timestamps = shift;
+        self
+    }
+
+    /// The pseudonym `text` is replaced with, also used for [`FieldAction::Hash`].
+    ///
+    /// Empty text is kept empty.
+    pub fn pseudonym(&self, text: &str) -> String {
+        if text.is_empty() {
+            return String::new();
+        }
+
+        self.digest(b'p', text)[..HASH_BYTES]
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    /// The number of days the dates of the patient or user with `unique_id` are shifted by.
+    ///
+    /// Never zero unless the maximum shift is zero.
+    pub fn date_offset(&self, unique_id: &str) -> i64 {
+        if self.max_date_shift == 0 {
+            return 0;
+        }
+
+        let digest = self.digest(b'd', unique_id);
+        let n = u64::from_le_bytes(digest[..8].try_into().expect("digest is 32 bytes"));
+        let days = (n % u64::from(self.max_date_shift)) as i64 + 1;
+        if digest[8] & 1 == 0 {
+            days
+        } else {
+            -days
+        }
+    }
+
+    /// Hashes `text` with the salt. `domain` keeps pseudonyms and date offsets of the same text
+    /// unrelated.
+    fn digest(&self, domain: u8, text: &str) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update((self.salt.len() as u64).to_le_bytes());
+        hasher.update(self.salt.as_bytes());
+        hasher.update([domain]);
+        hasher.update(text.as_bytes());
+        hasher.finalize().into()
+    }
+
+    fn action(&self, field_name: &str) -> Option<FieldAction> {
+        self.rules
+            .iter()
+            .find(|(pattern, _)| pattern.is_match(field_name))
+            .map(|(_, action)| *action)
+    }
+
+    fn pseudonymize(&self, text: &mut String) {
+        *text = self.pseudonym(text);
+    }
+
+    /// Pseudonymizes the name and the id of a `"Name(unique_id)"` creator separately, so they
+    /// match the pseudonyms of the same user in `by` and `by_unique_id`.
+    fn pseudonymize_creator(&self, creator: &mut String) {
+        match creator.strip_suffix(')').and_then(|c| c.rsplit_once('(')) {
+            Some((name, id)) => {
+                *creator = format!("{}({})", self.pseudonym(name), self.pseudonym(id));
+            }
+            None => self.pseudonymize(creator),
+        }
+    }
+
+    fn pseudonymize_option(&self, text: &mut Option<String>) {
+        if let Some(text) = text {
+            self.pseudonymize(text);
+        }
+    }
+
+    fn shift(&self, timestamp: &mut Option<DateTime<FixedOffset>>, offset: Duration) {
+        if self.shift_timestamps {
+            *timestamp = timestamp.map(|t| t + offset);
+        }
+    }
+
+    fn anonymize_value(&self, value: &mut Value, offset: Duration) {
+        value.extra.clear();
+        self.pseudonymize(&mut value.by);
+        self.pseudonymize_option(&mut value.by_unique_id);
+        self.shift(&mut value.when, offset);
+    }
+
+    fn anonymize_forms(&self, forms: &mut Option<Vec<Form>>, unique_id: &str) {
+        let days = self.date_offset(unique_id);
+        let offset = Duration::days(days);
+
+        for form in forms.iter_mut().flatten() {
+            clear_unknown(&mut form.extra, &mut form.extra_elements);
+            self.pseudonymize_option(&mut form.who_last_modified_name);
+            self.pseudonymize_option(&mut form.user);
+            self.shift(&mut form.last_modified, offset);
+            self.shift(&mut form.date_time_changed, offset);
+            if self.shift_timestamps {
+                // Milliseconds since the epoch
+                form.when_created =
+                    (form.when_created as i64 + offset.num_milliseconds()).max(0) as usize;
+            }
+
+            for state in form.states.iter_mut().flatten() {
+                state.extra.clear();
+                self.pseudonymize(&mut state.signer);
+                self.pseudonymize(&mut state.signer_unique_id);
+                self.shift(&mut state.date_signed, offset);
+            }
+            if let Some(lock_state) = &mut form.lock_state {
+                lock_state.extra.clear();
+                self.pseudonymize_option(&mut lock_state.user);
+                self.pseudonymize_option(&mut lock_state.user_unique_id);
+                self.shift(&mut lock_state.date_time_changed, offset);
+            }
+
+            for category in form.categories.iter_mut().flatten() {
+                clear_unknown(&mut category.extra, &mut category.extra_elements);
+                let Some(fields) = &mut category.fields else {
+                    continue;
+                };
+                fields.retain(|field| self.action(&field.name) != Some(FieldAction::Drop));
+
+                for field in fields.iter_mut() {
+                    clear_unknown(&mut field.extra, &mut field.extra_elements);
+                    self.shift(&mut field.when_created, offset);
+                    let action = self.action(&field.name);
+                    if action.is_some() {
+                        field.comments = None;
+                    }
+
+                    for entry in field.entries.iter_mut().flatten() {
+                        clear_unknown(&mut entry.extra, &mut entry.extra_elements);
+                        self.pseudonymize_option(&mut entry.reviewed_by);
+                        self.pseudonymize_option(&mut entry.reviewed_by_unique_id);
+                        self.shift(&mut entry.reviewed_by_when, offset);
+                        if let Some(value) = &mut entry.value {
+                            self.anonymize_value(value, offset);
+                            if let Some(action) = action {
+                                value.value = self.apply(action, &value.value, days);
+                            }
+                        }
+                        if let Some(reason) = &mut entry.reason {
+                            reason.extra.clear();
+                            self.pseudonymize(&mut reason.by);
+                            self.pseudonymize_option(&mut reason.by_unique_id);
+                            self.shift(&mut reason.when, offset);
+                        }
+                    }
+                    for comment in field.comments.iter_mut().flatten() {
+                        clear_unknown(&mut comment.extra, &mut comment.extra_elements);
+                        let replies = comment.replies.iter_mut().flatten();
+                        for value in comment.value.iter_mut().chain(replies) {
+                            self.anonymize_value(value, offset);
+                        }
+                        if let Some(reason) = &mut comment.reason {
+                            reason.extra.clear();
+                            self.pseudonymize(&mut reason.by);
+                            self.pseudonymize_option(&mut reason.by_unique_id);
+                            self.shift(&mut reason.when, offset);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn apply(&self, action: FieldAction, value: &str, days: i64) -> String {
+        if value.is_empty() {
+            return String::new();
+        }
+
+        let anonymized = match action {
+            FieldAction::Drop => None,
+            FieldAction::Hash => Some(self.pseudonym(value)),
+            FieldAction::ShiftDate => shift_date(value, days),
+            FieldAction::Generalize(generalization) => generalize(value, generalization),
+        };
+
+        anonymized.unwrap_or_default()
+    }
+
+    fn anonymize_export(&self, export: &mut Option<ExportInfo>) {
+        if let Some(export) = export {
+            export.extra.clear();
+            self.pseudonymize(&mut export.created_by);
+        }
+    }
+}
+
+fn shift_date(value: &str, days: i64) -> Option<String> {
+    let value = value.trim();
+    for format in DATE_FORMATS {
+        if let Ok(date) = NaiveDate::parse_from_str(value, format) {
+            let shifted = date.checked_add_signed(Duration::days(days))?;
+            return Some(shifted.format(format).to_string());
+        }
+    }
+
+    DateTime::parse_from_str(value, DATETIME_FORMAT)
+        .ok()
+        .map(|dt| {
+            (dt + Duration::days(days))
+                .format(DATETIME_FORMAT)
+                .to_string()
+        })
+}
+
+fn generalize(value: &str, generalization: Generalization) -> Option<String> {
+    let value = value.trim();
+    match generalization {
+        Generalization::Year => DATE_FORMATS
+            .iter()
+            .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+            .map(|date| date.year())
+            .or_else(|| {
+                DateTime::parse_from_str(value, DATETIME_FORMAT)
+                    .ok()
+                    .map(|dt| dt.year())
+            })
+            .map(|year| year.to_string()),
+        Generalization::NumberRange(width) => {
+            let number: f64 = value.parse().ok().filter(|n: &f64| n.is_finite())?;
+            let width = i64::from(width.max(1));
+            let lower = (number / width as f64).floor() as i64 * width;
+            Some(format!("{}-{}", lower, lower + width - 1))
+        }
+        Generalization::Prefix(length) => Some(value.chars().take(length).collect()),
+    }
+}
+
+impl SubjectNative {
+    /// De-identify the patients with `anonymizer`.
+    ///
+    /// The result can be written with [`Self::to_json`] or [`Self::to_xml`] like a parsed
+    /// native. See [`Anonymizer`].
+    pub fn anonymize(&self, anonymizer: &Anonymizer) -> SubjectNative {
+        let mut native = self.clone();
+        for patient in &mut native.patients {
+            anonymize_patient(anonymizer, patient);
+        }
+        anonymizer.anonymize_export(&mut native.export);
+
+        native
+    }
+}
+
+impl UserNative {
+    /// De-identify the users with `anonymizer`.
+    ///
+    /// See [`SubjectNative::anonymize`].
+    pub fn anonymize(&self, anonymizer: &Anonymizer) -> UserNative {
+        let mut native = self.clone();
+        for user in &mut native.users {
+            anonymize_user(anonymizer, user);
+        }
+        anonymizer.anonymize_export(&mut native.export);
+
+        native
+    }
+}
+
+fn clear_unknown(extra: &mut BTreeMap<String, String>, extra_elements: &mut Vec<XmlElement>) {
+    extra.clear();
+    extra_elements.clear();
+}
+
+fn anonymize_patient(anonymizer: &Anonymizer, patient: &mut Patient) {
+    clear_unknown(&mut patient.extra, &mut patient.extra_elements);
+    // The offset is derived from the original id so it is the same across exports
+    anonymizer.anonymize_forms(&mut patient.forms, &patient.unique_id);
+    let offset = Duration::days(anonymizer.date_offset(&patient.unique_id));
+    anonymizer.shift(&mut patient.when_created, offset);
+    anonymizer.pseudonymize(&mut patient.patient_id);
+    anonymizer.pseudonymize(&mut patient.unique_id);
+    anonymizer.pseudonymize(&mut patient.creator);
+}
+
+fn anonymize_user(anonymizer: &Anonymizer, user: &mut User) {
+    clear_unknown(&mut user.extra, &mut user.extra_elements);
+    anonymizer.anonymize_forms(&mut user.forms, &user.unique_id);
+    anonymizer.pseudonymize(&mut user.unique_id);
+    anonymizer.pseudonymize_creator(&mut user.creator);
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Anonymizer {
+    #[new]
+    #[pyo3(signature = (salt, max_date_shift=365, shift_timestamps=false))]
+    fn py_new(salt: String, max_date_shift: u32, shift_timestamps: bool) -> Self {
+        Self::new(salt)
+            .max_date_shift(max_date_shift)
+            .shift_timestamps(shift_timestamps)
+    }
+
+    /// Apply an action to the fields whose name matches the regular expression `pattern`.
+    ///
+    /// `action` is one of "drop", "hash", "shift_date", "year", "number_range", or "prefix".
+    /// `size` is the width of the ranges for "number_range" and the number of characters kept
+    /// for "prefix".
+    #[pyo3(name = "rule", signature = (pattern, action, size=None))]
+    fn py_rule(&mut self, pattern: &str, action: &str, size: Option<u32>) -> PyResult<()> {
+        let size = || size.ok_or_else(|| PyValueError::new_err(format!("{action} needs a size")));
+        let action = match action {
+            "drop" => FieldAction::Drop,
+            "hash" => FieldAction::Hash,
+            "shift_date" => FieldAction::ShiftDate,
+            "year" => FieldAction::Generalize(Generalization::Year),
+            "number_range" => FieldAction::Generalize(Generalization::NumberRange(size()?)),
+            "prefix" => FieldAction::Generalize(Generalization::Prefix(size()? as usize)),
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Unknown anonymizer action: {action}"
+                )))
+            }
+        };
+        let pattern = Regex::new(pattern).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.rules.push((pattern, action));
+
+        Ok(())
+    }
+
+    /// The pseudonym `text` is replaced with
+    #[pyo3(name = "pseudonym")]
+    fn py_pseudonym(&self, text: &str) -> String {
+        self.pseudonym(text)
+    }
+
+    /// The number of days the dates of the patient or user with `unique_id` are shifted by
+    #[pyo3(name = "date_offset")]
+    fn py_date_offset(&self, unique_id: &str) -> i64 {
+        self.date_offset(unique_id)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Anonymizer(rules=<{} items>, max_date_shift={}, shift_timestamps={})",
+            self.rules.len(),
+            self.max_date_shift,
+            if self.shift_timestamps {
+                "True"
+            } else {
+                "False"
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::native::common::Field;
+    use crate::native::test_helpers::{subject_native, user_native};
+    use crate::{
+        parse_subject_native_file_with_options, parse_subject_native_string, ParseOptions,
+    };
+
+    fn fields(patient: &Patient) -> Vec<&Field> {
+        patient
+            .forms
+            .iter()
+            .flatten()
+            .flat_map(|form| form.categories.iter().flatten())
+            .flat_map(|category| category.fields.iter().flatten())
+            .collect()
+    }
+
+    fn value<'a>(patient: &'a Patient, field_name: &str) -> &'a str {
+        let field = fields(patient)
+            .into_iter()
+            .find(|field| field.name == field_name)
+            .unwrap();
+        &field.entries.as_ref().unwrap()[0]
+            .value
+            .as_ref()
+            .unwrap()
+            .value
+    }
+
+    #[test]
+    fn applies_field_rules() {
+        let native = subject_native();
+        let anonymizer = Anonymizer::new("salt")
+            .rule("^(first|last)_name$", FieldAction::Drop)
+            .unwrap()
+            .rule("^breed$", FieldAction::Hash)
+            .unwrap()
+            .rule("^dob$", FieldAction::ShiftDate)
+            .unwrap()
+            .rule(
+                "^gender$",
+                FieldAction::Generalize(Generalization::Prefix(1)),
+            )
+            .unwrap();
+
+        let anonymized = native.anonymize(&anonymizer);
+        let original = &native.patients[0];
+        let patient = &anonymized.patients[0];
+
+        assert!(fields(patient)
+            .iter()
+            .all(|field| !field.name.ends_with("_name")));
+        assert_eq!(value(patient, "breed"), anonymizer.pseudonym("Labrador"));
+        assert_eq!(value(patient, "gender"), "F");
+        let days = anonymizer.date_offset(&original.unique_id);
+        assert_ne!(days, 0);
+        assert!(days.abs() <= 365);
+        assert_eq!(
+            NaiveDate::parse_from_str(value(patient, "dob"), "%d-%b-%Y").unwrap(),
+            NaiveDate::from_ymd_opt(2020, 4, 15).unwrap() + Duration::days(days)
+        );
+        let breed = fields(patient)
+            .into_iter()
+            .find(|field| field.name == "breed")
+            .unwrap();
+        assert!(breed.comments.is_none());
+    }
+
+    #[test]
+    fn pseudonymizes_people_and_ids() {
+        let native = subject_native();
+        let anonymizer = Anonymizer::new("salt");
+
+        let anonymized = native.anonymize(&anonymizer);
+        let json = anonymized.to_json().unwrap();
+
+        assert!(!json.contains("Paul Sanders"));
+        assert!(!json.contains("1681162687395"));
+        let patient = &anonymized.patients[0];
+        assert_eq!(patient.patient_id, anonymizer.pseudonym("ABC-001"));
+        assert_eq!(patient.creator, anonymizer.pseudonym("Paul Sanders"));
+        // Timestamps are kept unless asked for
+        assert_eq!(patient.when_created, native.patients[0].when_created);
+
+        let other = native.anonymize(&Anonymizer::new("other salt"));
+        assert_ne!(other.patients[0].patient_id, patient.patient_id);
+        assert_eq!(native.anonymize(&anonymizer), anonymized);
+    }
+
+    #[test]
+    fn shifts_timestamps_by_the_patient_offset() {
+        let native = subject_native();
+        let anonymizer = Anonymizer::new("salt")
+            .max_date_shift(30)
+            .shift_timestamps(true);
+
+        let anonymized = native.anonymize(&anonymizer);
+
+        for (original, patient) in native.patients.iter().zip(&anonymized.patients) {
+            let days = anonymizer.date_offset(&original.unique_id);
+            assert!(days.abs() <= 30);
+            assert_eq!(
+                patient.when_created,
+                original.when_created.map(|t| t + Duration::days(days))
+            );
+            let form = &patient.forms.as_ref().unwrap()[0];
+            let original_form = &original.forms.as_ref().unwrap()[0];
+            assert_eq!(
+                form.last_modified,
+                original_form
+                    .last_modified
+                    .map(|t| t + Duration::days(days))
+            );
+        }
+    }
+
+    #[test]
+    fn clears_values_that_cannot_be_generalized() {
+        let anonymizer = Anonymizer::new("salt");
+        let range = FieldAction::Generalize(Generalization::NumberRange(10));
+        let year = FieldAction::Generalize(Generalization::Year);
+
+        assert_eq!(anonymizer.apply(range, "37", 0), "30-39");
+        assert_eq!(anonymizer.apply(range, "-2.5", 0), "-10--1");
+        assert_eq!(anonymizer.apply(range, "unknown", 0), "");
+        assert_eq!(
+            anonymizer.apply(year, "2023-04-15 12:09:02 -0400", 0),
+            "2023"
+        );
+        assert_eq!(anonymizer.apply(year, "someday", 0), "");
+        assert_eq!(
+            anonymizer.apply(FieldAction::ShiftDate, "2020-02-28", 2),
+            "2020-03-01"
+        );
+    }
+
+    #[test]
+    fn anonymizes_users() {
+        let native = user_native();
+        let anonymizer = Anonymizer::new("salt")
+            .rule("^(email|address)$", FieldAction::Drop)
+            .unwrap()
+            // Free text that names the user who made the assignment
+            .rule("^study_assignment$", FieldAction::Hash)
+            .unwrap();
+
+        let anonymized = native.anonymize(&anonymizer);
+
+        let user = &anonymized.users[1];
+        assert_eq!(user.unique_id, anonymizer.pseudonym("1681162687395"));
+        let json = anonymized.to_json().unwrap();
+        assert!(!json.contains("\"email\""));
+        assert!(!json.contains("Paul Sanders"));
+    }
+
+    #[test]
+    fn pseudonymizes_the_creator_like_the_values_they_entered() {
+        let anonymizer = Anonymizer::new("salt");
+
+        let anonymized = user_native().anonymize(&anonymizer);
+
+        // Paul Sanders created the first user and entered its email, and is the second user
+        let user = &anonymized.users[0];
+        let value = user
+            .forms
+            .iter()
+            .flatten()
+            .flat_map(|form| form.categories.iter().flatten())
+            .flat_map(|category| category.fields.iter().flatten())
+            .flat_map(|field| field.entries.iter().flatten())
+            .filter_map(|entry| entry.value.as_ref())
+            .find(|value| value.by_unique_id.is_some())
+            .unwrap();
+        assert_eq!(
+            user.creator,
+            format!("{}({})", value.by, value.by_unique_id.as_ref().unwrap())
+        );
+        assert_eq!(
+            user.creator,
+            format!("{}({})", value.by, anonymized.users[1].unique_id)
+        );
+    }
+
+    #[test]
+    fn removes_unknown_attributes_and_elements() {
+        let native = parse_subject_native_file_with_options(
+            Path::new("tests/assets/subject_native_unknown.xml"),
+            &ParseOptions { keep_unknown: true },
+        )
+        .unwrap();
+
+        let anonymized = native.anonymize(&Anonymizer::new("salt"));
+
+        let json = anonymized.to_json().unwrap();
+        assert!(!json.contains("\"extra\""));
+        assert!(!json.contains("\"extraElements\""));
+        assert!(native.to_json().unwrap().contains("\"extraElements\""));
+    }
+
+    #[test]
+    fn writes_anonymized_xml() {
+        let anonymizer = Anonymizer::new("salt")
+            .rule("name", FieldAction::Hash)
+            .unwrap()
+            .shift_timestamps(true);
+        let anonymized = subject_native().anonymize(&anonymizer);
+
+        let xml = anonymized.to_xml().unwrap();
+
+        assert!(!xml.contains("Paul Sanders"));
+        assert_eq!(parse_subject_native_string(&xml).unwrap(), anonymized);
+    }
+}
@@ -0,0 +1,181 @@
+use chrono::{DateTime, FixedOffset};
+use serde::Serialize;
+
+use crate::native::{
+    common::{Comment, Form},
+    site_native::SiteNative,
+    subject_native::SubjectNative,
+    user_native::UserNative,
+};
+
+/// A comment with the location of the field it was made on.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldComment {
+    /// `Patient.unique_id`, `Site.unique_id`, or `User.unique_id`.
+    pub record_unique_id: String,
+    /// The site of the patient, or the site itself. `None` for users.
+    pub site_unique_id: Option<String>,
+    pub form_name: String,
+    pub form_index: usize,
+    pub form_title: String,
+    pub category_name: String,
+    pub field_name: String,
+    /// When the first value of the comment was entered.
+    pub when: Option<DateTime<FixedOffset>>,
+    #[serde(flatten)]
+    pub comment: Comment,
+}
+
+impl SubjectNative {
+    /// Lists the comments on the fields of every patient, oldest first. Comments without a time
+    /// come last, in export order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::path::Path;
+    ///
+    /// use prelude_xml_parser::parse_subject_native_file;
+    ///
+    /// let native = parse_subject_native_file(Path::new("tests/assets/subject_native.xml")).unwrap();
+    /// let comments = native.comments();
+    ///
+    /// assert_eq!(comments[0].record_unique_id, "1681574905819");
+    /// assert_eq!(comments[0].field_name, "breed");
+    /// assert_eq!(comments[0].comment.value.as_ref().unwrap().value, "Some Comment");
+    /// ```
+    pub fn comments(&self) -> Vec<FieldComment> {
+        sorted(
+            self.patients
+                .iter()
+                .flat_map(|patient| {
+                    field_comments(
+                        &patient.unique_id,
+                        Some(&patient.site_unique_id),
+                        &patient.forms,
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+impl SiteNative {
+    /// Lists the comments on the fields of every site, oldest first.
+    ///
+    /// See [`SubjectNative::comments`].
+    pub fn comments(&self) -> Vec<FieldComment> {
+        sorted(
+            self.sites
+                .iter()
+                .flat_map(|site| {
+                    field_comments(&site.unique_id, Some(&site.unique_id), &site.forms)
+                })
+                .collect(),
+        )
+    }
+}
+
+impl UserNative {
+    /// Lists the comments on the fields of every user, oldest first.
+    ///
+    /// See [`SubjectNative::comments`].
+    pub fn comments(&self) -> Vec<FieldComment> {
+        sorted(
+            self.users
+                .iter()
+                .flat_map(|user| field_comments(&user.unique_id, None, &user.forms))
+                .collect(),
+        )
+    }
+}
+
+fn sorted(mut comments: Vec<FieldComment>) -> Vec<FieldComment> {
+    // Stable, so comments made at the same time keep their export order
+    comments.sort_by_key(|comment| (comment.when.is_none(), comment.when));
+    comments
+}
+
+fn field_comments(
+    record_unique_id: &str,
+    site_unique_id: Option<&String>,
+    forms: &Option<Vec<Form>>,
+) -> Vec<FieldComment> {
+    let mut comments = Vec::new();
+    for form in forms.iter().flatten() {
+        for category in form.categories.iter().flatten() {
+            for field in category.fields.iter().flatten() {
+                for comment in field.comments.iter().flatten() {
+                    comments.push(FieldComment {
+                        record_unique_id: record_unique_id.to_string(),
+                        site_unique_id: site_unique_id.cloned(),
+                        form_name: form.name.clone(),
+                        form_index: form.form_index,
+                        form_title: form.form_title.clone(),
+                        category_name: category.name.clone(),
+                        field_name: field.name.clone(),
+                        when: comment.value.as_ref().and_then(|value| value.when),
+                        comment: comment.clone(),
+                    });
+                }
+            }
+        }
+    }
+    comments
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use crate::native::test_helpers::subject_native;
+
+    #[test]
+    fn lists_comments_with_their_location() {
+        let native = subject_native();
+
+        let comments = native.comments();
+
+        assert_eq!(comments.len(), 1);
+        let first = &comments[0];
+        assert_eq!(first.site_unique_id.as_deref(), Some("1681574834910"));
+        assert_eq!(first.form_name, "day.0.form.name.demographics");
+        assert_eq!(first.category_name, "Demographics");
+        assert_eq!(first.field_name, "breed");
+        assert_eq!(first.comment.comment_id, "1");
+        assert_eq!(first.when, first.comment.value.as_ref().unwrap().when);
+    }
+
+    #[test]
+    fn sorts_comments_by_time() {
+        let mut native = subject_native();
+        let field = &mut native.patients[0].forms.as_mut().unwrap()[0]
+            .categories
+            .as_mut()
+            .unwrap()[0]
+            .fields
+            .as_mut()
+            .unwrap()[0];
+        let comments = field.comments.as_mut().unwrap();
+        let mut older = comments[0].clone();
+        older.comment_id = "2".to_string();
+        let value = older.value.as_mut().unwrap();
+        value.when = value.when.map(|when| when - Duration::days(1));
+        let mut undated = comments[0].clone();
+        undated.comment_id = "3".to_string();
+        undated.value.as_mut().unwrap().when = None;
+        comments.insert(0, undated);
+        comments.push(older);
+
+        let comments = native.comments();
+
+        assert_eq!(
+            comments
+                .iter()
+                .map(|c| c.comment.comment_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["2", "1", "3"]
+        );
+    }
+}
@@ -1,10 +1,13 @@
-use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, FixedOffset};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "python")]
 use pyo3::{
     prelude::*,
-    types::{PyDateTime, PyDict},
+    types::{PyDateTime, PyDict, PyType},
 };
 
 use crate::native::deserializers::{
@@ -14,43 +17,20 @@ use crate::native::deserializers::{
 
 #[cfg(feature = "python")]
 use crate::native::deserializers::{to_py_datetime, to_py_datetime_option};
+#[cfg(feature = "python")]
+use crate::python::{
+    dict_get, dict_get_elements, dict_get_extra, dict_get_model, dict_get_models, from_json, hash,
+    reduce, to_dicts, FromPyDict, Repr,
+};
 
-#[cfg(not(feature = "python"))]
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-pub struct Value {
-    #[serde(rename = "by")]
-    #[serde(alias = "@by")]
-    #[serde(alias = "by")]
-    pub by: String,
-
-    #[serde(rename = "byUniqueId")]
-    #[serde(alias = "@byUniqueId")]
-    #[serde(alias = "byUniqueId")]
-    #[serde(
-        default = "default_string_none",
-        deserialize_with = "deserialize_empty_string_as_none"
-    )]
-    pub by_unique_id: Option<String>,
-    #[serde(rename = "role")]
-    #[serde(alias = "@role")]
-    #[serde(alias = "role")]
-    pub role: String,
-    #[serde(rename = "when")]
-    #[serde(alias = "@when")]
-    #[serde(alias = "when")]
-    pub when: Option<DateTime<Utc>>,
-
-    #[serde(rename = "value")]
-    #[serde(alias = "$text")]
-    #[serde(alias = "#text")]
-    #[serde(alias = "value")]
-    #[serde(default)]
-    pub value: String,
-}
+/// The id Prelude records for actions taken by the system, e.g. `signerUniqueId="system"`.
+pub(crate) const SYSTEM_UNIQUE_ID: &str = "system";
 
-#[cfg(feature = "python")]
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-#[pyclass(skip_from_py_object)]
+#[cfg_attr(
+    feature = "python",
+    pyclass(set_all, eq, module = "prelude_xml_parser", from_py_object)
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash, JsonSchema)]
 pub struct Value {
     #[serde(rename = "by")]
     #[serde(alias = "@by")]
@@ -72,7 +52,7 @@ pub struct Value {
     #[serde(rename = "when")]
     #[serde(alias = "@when")]
     #[serde(alias = "when")]
-    pub when: Option<DateTime<Utc>>,
+    pub when: Option<DateTime<FixedOffset>>,
 
     #[serde(rename = "value")]
     #[serde(alias = "$text")]
@@ -80,11 +60,35 @@ pub struct Value {
     #[serde(alias = "value")]
     #[serde(default)]
     pub value: String,
+
+    /// Attributes the parser does not know, kept when parsing with `ParseOptions::keep_unknown`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, String>,
 }
 
 #[cfg(feature = "python")]
 #[pymethods]
 impl Value {
+    #[new]
+    #[pyo3(signature = (by, role, value, by_unique_id=None, when=None, extra=None))]
+    fn new(
+        by: String,
+        role: String,
+        value: String,
+        by_unique_id: Option<String>,
+        when: Option<DateTime<FixedOffset>>,
+        extra: Option<BTreeMap<String, String>>,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            by,
+            by_unique_id,
+            role,
+            when,
+            value,
+            extra: extra.unwrap_or_default(),
+        })
+    }
+
     #[getter]
     fn by(&self) -> PyResult<String> {
         Ok(self.by.clone())
@@ -110,6 +114,12 @@ impl Value {
         Ok(self.value.clone())
     }
 
+    /// A copy of the value's unknown attributes; assign a changed copy back to update the value.
+    #[getter]
+    fn extra(&self) -> PyResult<BTreeMap<String, String>> {
+        Ok(self.extra.clone())
+    }
+
     pub fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
         let dict = PyDict::new(py);
         dict.set_item("by", &self.by)?;
@@ -118,47 +128,60 @@ impl Value {
         dict.set_item("when", to_py_datetime_option(py, &self.when)?)?;
         dict.set_item("value", &self.value)?;
 
+        dict.set_item("extra", &self.extra)?;
+
         Ok(dict)
     }
-}
 
-#[cfg(not(feature = "python"))]
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-pub struct Reason {
-    #[serde(rename = "by")]
-    #[serde(alias = "@by")]
-    #[serde(alias = "by")]
-    pub by: String,
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(Repr::new(py, "Value")
+            .field("value", &self.value)?
+            .field("by", &self.by)?
+            .field("role", &self.role)?
+            .field("when", to_py_datetime_option(py, &self.when)?)?
+            .finish())
+    }
 
-    #[serde(rename = "byUniqueId")]
-    #[serde(alias = "@byUniqueId")]
-    #[serde(alias = "byUniqueId")]
-    #[serde(
-        default = "default_string_none",
-        deserialize_with = "deserialize_empty_string_as_none"
-    )]
-    pub by_unique_id: Option<String>,
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, (String,))> {
+        reduce(slf)
+    }
 
-    #[serde(rename = "role")]
-    #[serde(alias = "@role")]
-    #[serde(alias = "role")]
-    pub role: String,
-    #[serde(rename = "when")]
-    #[serde(alias = "@when")]
-    #[serde(alias = "when")]
-    pub when: Option<DateTime<Utc>>,
+    fn __hash__(&self) -> u64 {
+        hash(self)
+    }
 
-    #[serde(rename = "value")]
-    #[serde(alias = "$text")]
-    #[serde(alias = "#text")]
-    #[serde(alias = "value")]
-    #[serde(default)]
-    pub value: String,
+    /// Create an instance from a dictionary shaped like the output of `to_dict`
+    #[classmethod]
+    fn from_dict(_cls: &Bound<'_, PyType>, data: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Self::from_py_dict(data)
+    }
+
+    /// Create an instance from a JSON string
+    #[classmethod]
+    fn from_json(_cls: &Bound<'_, PyType>, json: &str) -> PyResult<Self> {
+        from_json(json)
+    }
 }
 
 #[cfg(feature = "python")]
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-#[pyclass(skip_from_py_object)]
+impl FromPyDict for Value {
+    fn from_py_dict(dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Ok(Self {
+            by: dict_get(dict, "by")?,
+            by_unique_id: dict_get(dict, "by_unique_id")?,
+            role: dict_get(dict, "role")?,
+            when: dict_get(dict, "when")?,
+            value: dict_get(dict, "value")?,
+            extra: dict_get_extra(dict)?,
+        })
+    }
+}
+
+#[cfg_attr(
+    feature = "python",
+    pyclass(set_all, eq, module = "prelude_xml_parser", from_py_object)
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash, JsonSchema)]
 pub struct Reason {
     #[serde(rename = "by")]
     #[serde(alias = "@by")]
@@ -181,7 +204,7 @@ pub struct Reason {
     #[serde(rename = "when")]
     #[serde(alias = "@when")]
     #[serde(alias = "when")]
-    pub when: Option<DateTime<Utc>>,
+    pub when: Option<DateTime<FixedOffset>>,
 
     #[serde(rename = "value")]
     #[serde(alias = "$text")]
@@ -189,11 +212,35 @@ pub struct Reason {
     #[serde(alias = "value")]
     #[serde(default)]
     pub value: String,
+
+    /// Attributes the parser does not know, kept when parsing with `ParseOptions::keep_unknown`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, String>,
 }
 
 #[cfg(feature = "python")]
 #[pymethods]
 impl Reason {
+    #[new]
+    #[pyo3(signature = (by, role, value, by_unique_id=None, when=None, extra=None))]
+    fn new(
+        by: String,
+        role: String,
+        value: String,
+        by_unique_id: Option<String>,
+        when: Option<DateTime<FixedOffset>>,
+        extra: Option<BTreeMap<String, String>>,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            by,
+            by_unique_id,
+            role,
+            when,
+            value,
+            extra: extra.unwrap_or_default(),
+        })
+    }
+
     #[getter]
     fn by(&self) -> PyResult<String> {
         Ok(self.by.clone())
@@ -219,6 +266,12 @@ impl Reason {
         Ok(self.value.clone())
     }
 
+    /// A copy of the reason's unknown attributes; assign a changed copy back to update the reason.
+    #[getter]
+    fn extra(&self) -> PyResult<BTreeMap<String, String>> {
+        Ok(self.extra.clone())
+    }
+
     pub fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
         let dict = PyDict::new(py);
         dict.set_item("by", &self.by)?;
@@ -227,52 +280,60 @@ impl Reason {
         dict.set_item("when", to_py_datetime_option(py, &self.when)?)?;
         dict.set_item("value", &self.value)?;
 
+        dict.set_item("extra", &self.extra)?;
+
         Ok(dict)
     }
-}
 
-#[cfg(not(feature = "python"))]
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-pub struct Entry {
-    #[serde(rename = "entryId")]
-    #[serde(alias = "@id")]
-    #[serde(alias = "entryId")]
-    pub entry_id: String,
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(Repr::new(py, "Reason")
+            .field("value", &self.value)?
+            .field("by", &self.by)?
+            .field("role", &self.role)?
+            .field("when", to_py_datetime_option(py, &self.when)?)?
+            .finish())
+    }
 
-    #[serde(rename = "reviewedBy")]
-    #[serde(alias = "@reviewedBy")]
-    #[serde(alias = "reviewedBy")]
-    #[serde(
-        default = "default_string_none",
-        deserialize_with = "deserialize_empty_string_as_none"
-    )]
-    pub reviewed_by: Option<String>,
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, (String,))> {
+        reduce(slf)
+    }
 
-    #[serde(rename = "reviewedByUniqueId")]
-    #[serde(alias = "@reviewedByUniqueId")]
-    #[serde(alias = "reviewedByUniqueId")]
-    #[serde(
-        default = "default_string_none",
-        deserialize_with = "deserialize_empty_string_as_none"
-    )]
-    pub reviewed_by_unique_id: Option<String>,
+    fn __hash__(&self) -> u64 {
+        hash(self)
+    }
 
-    #[serde(rename = "reviewedByWhen")]
-    #[serde(alias = "@reviewedByWhen")]
-    #[serde(alias = "reviewedByWhen")]
-    #[serde(
-        default = "default_datetime_none",
-        deserialize_with = "deserialize_empty_string_as_none_datetime"
-    )]
-    pub reviewed_by_when: Option<DateTime<Utc>>,
+    /// Create an instance from a dictionary shaped like the output of `to_dict`
+    #[classmethod]
+    fn from_dict(_cls: &Bound<'_, PyType>, data: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Self::from_py_dict(data)
+    }
 
-    pub value: Option<Value>,
-    pub reason: Option<Reason>,
+    /// Create an instance from a JSON string
+    #[classmethod]
+    fn from_json(_cls: &Bound<'_, PyType>, json: &str) -> PyResult<Self> {
+        from_json(json)
+    }
 }
 
 #[cfg(feature = "python")]
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-#[pyclass(skip_from_py_object)]
+impl FromPyDict for Reason {
+    fn from_py_dict(dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Ok(Self {
+            by: dict_get(dict, "by")?,
+            by_unique_id: dict_get(dict, "by_unique_id")?,
+            role: dict_get(dict, "role")?,
+            when: dict_get(dict, "when")?,
+            value: dict_get(dict, "value")?,
+            extra: dict_get_extra(dict)?,
+        })
+    }
+}
+
+#[cfg_attr(
+    feature = "python",
+    pyclass(set_all, eq, module = "prelude_xml_parser", from_py_object)
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash, JsonSchema)]
 pub struct Entry {
     #[serde(rename = "entryId")]
     #[serde(alias = "@id")]
@@ -304,15 +365,52 @@ pub struct Entry {
         default = "default_datetime_none",
         deserialize_with = "deserialize_empty_string_as_none_datetime"
     )]
-    pub reviewed_by_when: Option<DateTime<Utc>>,
+    pub reviewed_by_when: Option<DateTime<FixedOffset>>,
 
     pub value: Option<Value>,
     pub reason: Option<Reason>,
+
+    /// Attributes the parser does not know, kept when parsing with `ParseOptions::keep_unknown`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, String>,
+    /// Child elements the parser does not know, kept when parsing with
+    /// `ParseOptions::keep_unknown`.
+    #[serde(
+        rename = "extraElements",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub extra_elements: Vec<XmlElement>,
 }
 
 #[cfg(feature = "python")]
 #[pymethods]
 impl Entry {
+    #[new]
+    #[pyo3(signature = (entry_id, reviewed_by=None, reviewed_by_unique_id=None, reviewed_by_when=None, value=None, reason=None, extra=None, extra_elements=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        entry_id: String,
+        reviewed_by: Option<String>,
+        reviewed_by_unique_id: Option<String>,
+        reviewed_by_when: Option<DateTime<FixedOffset>>,
+        value: Option<Value>,
+        reason: Option<Reason>,
+        extra: Option<BTreeMap<String, String>>,
+        extra_elements: Option<Vec<XmlElement>>,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            entry_id,
+            reviewed_by,
+            reviewed_by_unique_id,
+            reviewed_by_when,
+            value,
+            reason,
+            extra: extra.unwrap_or_default(),
+            extra_elements: extra_elements.unwrap_or_default(),
+        })
+    }
+
     #[getter]
     fn entry_id(&self) -> PyResult<String> {
         Ok(self.entry_id.clone())
@@ -333,16 +431,31 @@ impl Entry {
         to_py_datetime_option(py, &self.reviewed_by_when)
     }
 
+    /// A copy of the entry's value; assign a changed copy back to update the entry.
     #[getter]
     fn value(&self) -> PyResult<Option<Value>> {
         Ok(self.value.clone())
     }
 
+    /// A copy of the entry's reason; assign a changed copy back to update the entry.
     #[getter]
     fn reason(&self) -> PyResult<Option<Reason>> {
         Ok(self.reason.clone())
     }
 
+    /// A copy of the entry's unknown attributes; assign a changed copy back to update the entry.
+    #[getter]
+    fn extra(&self) -> PyResult<BTreeMap<String, String>> {
+        Ok(self.extra.clone())
+    }
+
+    /// A copy of the entry's unknown child elements; assign a changed copy back to update the
+    /// entry.
+    #[getter]
+    fn extra_elements(&self) -> PyResult<Vec<XmlElement>> {
+        Ok(self.extra_elements.clone())
+    }
+
     pub fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
         let dict = PyDict::new(py);
         dict.set_item("entry_id", &self.entry_id)?;
@@ -363,44 +476,164 @@ impl Entry {
             dict.set_item("reason", py.None())?;
         }
 
+        dict.set_item("extra", &self.extra)?;
+        dict.set_item("extra_elements", to_dicts(py, &self.extra_elements)?)?;
+
         Ok(dict)
     }
-}
 
-#[cfg(not(feature = "python"))]
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-pub struct Comment {
-    #[serde(rename = "commentId")]
-    #[serde(alias = "@id")]
-    #[serde(alias = "commentId")]
-    pub comment_id: String,
-    pub value: Option<Value>,
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(Repr::new(py, "Entry")
+            .field("entry_id", &self.entry_id)?
+            .field("value", self.value.clone())?
+            .field("reason", self.reason.clone())?
+            .finish())
+    }
+
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, (String,))> {
+        reduce(slf)
+    }
+
+    fn __hash__(&self) -> u64 {
+        hash(self)
+    }
+
+    /// Create an instance from a dictionary shaped like the output of `to_dict`
+    #[classmethod]
+    fn from_dict(_cls: &Bound<'_, PyType>, data: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Self::from_py_dict(data)
+    }
+
+    /// Create an instance from a JSON string
+    #[classmethod]
+    fn from_json(_cls: &Bound<'_, PyType>, json: &str) -> PyResult<Self> {
+        from_json(json)
+    }
 }
 
 #[cfg(feature = "python")]
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-#[pyclass(get_all, skip_from_py_object)]
+impl FromPyDict for Entry {
+    fn from_py_dict(dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Ok(Self {
+            entry_id: dict_get(dict, "entry_id")?,
+            reviewed_by: dict_get(dict, "reviewed_by")?,
+            reviewed_by_unique_id: dict_get(dict, "reviewed_by_unique_id")?,
+            reviewed_by_when: dict_get(dict, "reviewed_by_when")?,
+            value: dict_get_model(dict, "value")?,
+            reason: dict_get_model(dict, "reason")?,
+            extra: dict_get_extra(dict)?,
+            extra_elements: dict_get_elements(dict)?,
+        })
+    }
+}
+
+#[cfg_attr(
+    feature = "python",
+    pyclass(get_all, set_all, eq, module = "prelude_xml_parser", from_py_object)
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash, JsonSchema)]
 pub struct Comment {
     #[serde(rename = "commentId")]
     #[serde(alias = "@id")]
     #[serde(alias = "commentId")]
     pub comment_id: String,
+    /// The first value of the comment.
     pub value: Option<Value>,
+    /// Later values of the comment, e.g. replies, in export order.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replies: Option<Vec<Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<Reason>,
+
+    /// Attributes the parser does not know, kept when parsing with `ParseOptions::keep_unknown`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, String>,
+    /// Child elements the parser does not know, kept when parsing with
+    /// `ParseOptions::keep_unknown`.
+    #[serde(
+        rename = "extraElements",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub extra_elements: Vec<XmlElement>,
+}
+
+impl Comment {
+    /// Sets the first value, or adds a reply when the comment already has one.
+    pub(crate) fn push_value(&mut self, value: Value) {
+        if self.value.is_none() {
+            self.value = Some(value);
+        } else {
+            self.replies.get_or_insert_with(Vec::new).push(value);
+        }
+    }
+
+    /// The value and replies of the comment, in export order.
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        self.value.iter().chain(self.replies.iter().flatten())
+    }
 }
 
 #[cfg(feature = "python")]
 #[pymethods]
 impl Comment {
+    #[new]
+    #[pyo3(signature = (comment_id, value=None, replies=None, reason=None, extra=None, extra_elements=None))]
+    fn new(
+        comment_id: String,
+        value: Option<Value>,
+        replies: Option<Vec<Value>>,
+        reason: Option<Reason>,
+        extra: Option<BTreeMap<String, String>>,
+        extra_elements: Option<Vec<XmlElement>>,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            comment_id,
+            value,
+            replies,
+            reason,
+            extra: extra.unwrap_or_default(),
+            extra_elements: extra_elements.unwrap_or_default(),
+        })
+    }
+
     #[getter]
     fn comment_id(&self) -> PyResult<String> {
         Ok(self.comment_id.clone())
     }
 
+    /// A copy of the comment's value; assign a changed copy back to update the comment.
     #[getter]
     fn value(&self) -> PyResult<Option<Value>> {
         Ok(self.value.clone())
     }
 
+    /// A copy of the comment's replies; assign a changed copy back to update the comment.
+    #[getter]
+    fn replies(&self) -> PyResult<Option<Vec<Value>>> {
+        Ok(self.replies.clone())
+    }
+
+    /// A copy of the comment's reason; assign a changed copy back to update the comment.
+    #[getter]
+    fn reason(&self) -> PyResult<Option<Reason>> {
+        Ok(self.reason.clone())
+    }
+
+    /// A copy of the comment's unknown attributes; assign a changed copy back to update the
+    /// comment.
+    #[getter]
+    fn extra(&self) -> PyResult<BTreeMap<String, String>> {
+        Ok(self.extra.clone())
+    }
+
+    /// A copy of the comment's unknown child elements; assign a changed copy back to update the
+    /// comment.
+    #[getter]
+    fn extra_elements(&self) -> PyResult<Vec<XmlElement>> {
+        Ok(self.extra_elements.clone())
+    }
+
     pub fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
         let dict = PyDict::new(py);
         dict.set_item("comment_id", &self.comment_id)?;
@@ -409,13 +642,76 @@ impl Comment {
         } else {
             dict.set_item("value", py.None())?;
         }
+        let mut reply_dicts = Vec::new();
+        if let Some(replies) = &self.replies {
+            for reply in replies {
+                reply_dicts.push(reply.to_dict(py)?);
+            }
+            dict.set_item("replies", reply_dicts)?;
+        } else {
+            dict.set_item("replies", py.None())?;
+        }
+        if let Some(reason) = &self.reason {
+            dict.set_item("reason", reason.to_dict(py)?)?;
+        } else {
+            dict.set_item("reason", py.None())?;
+        }
+
+        dict.set_item("extra", &self.extra)?;
+        dict.set_item("extra_elements", to_dicts(py, &self.extra_elements)?)?;
 
         Ok(dict)
     }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(Repr::new(py, "Comment")
+            .field("comment_id", &self.comment_id)?
+            .field("value", self.value.clone())?
+            .items("replies", self.replies.as_ref().map(Vec::len))
+            .field("reason", self.reason.clone())?
+            .finish())
+    }
+
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, (String,))> {
+        reduce(slf)
+    }
+
+    fn __hash__(&self) -> u64 {
+        hash(self)
+    }
+
+    /// Create an instance from a dictionary shaped like the output of `to_dict`
+    #[classmethod]
+    fn from_dict(_cls: &Bound<'_, PyType>, data: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Self::from_py_dict(data)
+    }
+
+    /// Create an instance from a JSON string
+    #[classmethod]
+    fn from_json(_cls: &Bound<'_, PyType>, json: &str) -> PyResult<Self> {
+        from_json(json)
+    }
+}
+
+#[cfg(feature = "python")]
+impl FromPyDict for Comment {
+    fn from_py_dict(dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Ok(Self {
+            comment_id: dict_get(dict, "comment_id")?,
+            value: dict_get_model(dict, "value")?,
+            replies: dict_get_models(dict, "replies")?,
+            reason: dict_get_model(dict, "reason")?,
+            extra: dict_get_extra(dict)?,
+            extra_elements: dict_get_elements(dict)?,
+        })
+    }
 }
 
-#[cfg(not(feature = "python"))]
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(
+    feature = "python",
+    pyclass(set_all, eq, module = "prelude_xml_parser", from_py_object)
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash, JsonSchema)]
 pub struct Field {
     #[serde(rename = "name")]
     #[serde(alias = "@name")]
@@ -435,6 +731,7 @@ pub struct Field {
         deserialize_with = "deserialize_empty_string_as_none"
     )]
     pub data_type: Option<String>,
+
     #[serde(rename = "errorCode")]
     #[serde(alias = "@errorCode")]
     #[serde(alias = "errorCode")]
@@ -442,7 +739,7 @@ pub struct Field {
     #[serde(rename = "whenCreated")]
     #[serde(alias = "@whenCreated")]
     #[serde(alias = "whenCreated")]
-    pub when_created: Option<DateTime<Utc>>,
+    pub when_created: Option<DateTime<FixedOffset>>,
     #[serde(rename = "keepHistory")]
     #[serde(alias = "@keepHistory")]
     #[serde(alias = "keepHistory")]
@@ -453,54 +750,72 @@ pub struct Field {
 
     #[serde(alias = "comment")]
     pub comments: Option<Vec<Comment>>,
-}
-
-#[cfg(feature = "python")]
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-#[pyclass(skip_from_py_object)]
-pub struct Field {
-    #[serde(rename = "name")]
-    #[serde(alias = "@name")]
-    #[serde(alias = "name")]
-    pub name: String,
 
-    #[serde(rename = "fieldType")]
-    #[serde(alias = "@type")]
-    #[serde(alias = "fieldType")]
-    pub field_type: String,
-
-    #[serde(rename = "dataType")]
-    #[serde(alias = "@dataType")]
-    #[serde(alias = "dataType")]
+    /// Attributes the parser does not know, kept when parsing with `ParseOptions::keep_unknown`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, String>,
+    /// Child elements the parser does not know, kept when parsing with
+    /// `ParseOptions::keep_unknown`.
     #[serde(
-        default = "default_string_none",
-        deserialize_with = "deserialize_empty_string_as_none"
+        rename = "extraElements",
+        default,
+        skip_serializing_if = "Vec::is_empty"
     )]
-    pub data_type: Option<String>,
-
-    #[serde(rename = "errorCode")]
-    #[serde(alias = "@errorCode")]
-    #[serde(alias = "errorCode")]
-    pub error_code: String,
-    #[serde(rename = "whenCreated")]
-    #[serde(alias = "@whenCreated")]
-    #[serde(alias = "whenCreated")]
-    pub when_created: Option<DateTime<Utc>>,
-    #[serde(rename = "keepHistory")]
-    #[serde(alias = "@keepHistory")]
-    #[serde(alias = "keepHistory")]
-    pub keep_history: bool,
+    pub extra_elements: Vec<XmlElement>,
+}
 
-    #[serde(alias = "entry")]
-    pub entries: Option<Vec<Entry>>,
+impl Field {
+    /// The repeat index of a field in an indexed category, parsed from the "(n)" suffix Prelude
+    /// appends to its name, e.g. 2 for "ae_term(2)".
+    pub fn repeat_index(&self) -> Option<usize> {
+        split_repeat_index(&self.name).1
+    }
 
-    #[serde(alias = "comment")]
-    pub comments: Option<Vec<Comment>>,
+    /// The name without its repeat index, e.g. "ae_term" for "ae_term(2)".
+    pub fn base_name(&self) -> &str {
+        split_repeat_index(&self.name).0
+    }
+}
+
+fn split_repeat_index(name: &str) -> (&str, Option<usize>) {
+    name.strip_suffix(')')
+        .and_then(|name| name.rsplit_once('('))
+        .and_then(|(base, index)| Some((base, Some(index.parse().ok()?))))
+        .unwrap_or((name, None))
 }
 
 #[cfg(feature = "python")]
 #[pymethods]
 impl Field {
+    #[new]
+    #[pyo3(signature = (name, field_type, error_code, data_type=None, when_created=None, keep_history=false, entries=None, comments=None, extra=None, extra_elements=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        name: String,
+        field_type: String,
+        error_code: String,
+        data_type: Option<String>,
+        when_created: Option<DateTime<FixedOffset>>,
+        keep_history: bool,
+        entries: Option<Vec<Entry>>,
+        comments: Option<Vec<Comment>>,
+        extra: Option<BTreeMap<String, String>>,
+        extra_elements: Option<Vec<XmlElement>>,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            name,
+            field_type,
+            data_type,
+            error_code,
+            when_created,
+            keep_history,
+            entries,
+            comments,
+            extra: extra.unwrap_or_default(),
+            extra_elements: extra_elements.unwrap_or_default(),
+        })
+    }
+
     #[getter]
     fn name(&self) -> PyResult<String> {
         Ok(self.name.clone())
@@ -534,16 +849,43 @@ impl Field {
         Ok(self.keep_history)
     }
 
+    /// A copy of the field's entries; assign a changed copy back to update the field.
     #[getter]
     fn entries(&self) -> PyResult<Option<Vec<Entry>>> {
         Ok(self.entries.clone())
     }
 
+    /// A copy of the field's comments; assign a changed copy back to update the field.
     #[getter]
     fn comments(&self) -> PyResult<Option<Vec<Comment>>> {
         Ok(self.comments.clone())
     }
 
+    /// The repeat index parsed from the name of a field in an indexed category
+    #[getter(repeat_index)]
+    fn py_repeat_index(&self) -> Option<usize> {
+        self.repeat_index()
+    }
+
+    /// The name without its repeat index
+    #[getter(base_name)]
+    fn py_base_name(&self) -> String {
+        self.base_name().to_string()
+    }
+
+    /// A copy of the field's unknown attributes; assign a changed copy back to update the field.
+    #[getter]
+    fn extra(&self) -> PyResult<BTreeMap<String, String>> {
+        Ok(self.extra.clone())
+    }
+
+    /// A copy of the field's unknown child elements; assign a changed copy back to update the
+    /// field.
+    #[getter]
+    fn extra_elements(&self) -> PyResult<Vec<XmlElement>> {
+        Ok(self.extra_elements.clone())
+    }
+
     pub fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
         let dict = PyDict::new(py);
         dict.set_item("name", &self.name)?;
@@ -581,35 +923,66 @@ impl Field {
             dict.set_item("comments", py.None())?;
         }
 
+        dict.set_item("extra", &self.extra)?;
+        dict.set_item("extra_elements", to_dicts(py, &self.extra_elements)?)?;
+
         Ok(dict)
     }
-}
 
-#[cfg(not(feature = "python"))]
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-pub struct Category {
-    #[serde(rename = "name")]
-    #[serde(alias = "@name")]
-    #[serde(alias = "name")]
-    pub name: String,
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(Repr::new(py, "Field")
+            .field("name", &self.name)?
+            .field("field_type", &self.field_type)?
+            .field("data_type", &self.data_type)?
+            .items("entries", self.entries.as_ref().map(Vec::len))
+            .items("comments", self.comments.as_ref().map(Vec::len))
+            .finish())
+    }
 
-    #[serde(rename = "categoryType")]
-    #[serde(alias = "@type")]
-    #[serde(alias = "categoryType")]
-    pub category_type: String,
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, (String,))> {
+        reduce(slf)
+    }
 
-    #[serde(rename = "highestIndex")]
-    #[serde(alias = "@highestIndex")]
-    #[serde(alias = "highestIndex")]
-    pub highest_index: usize,
+    fn __hash__(&self) -> u64 {
+        hash(self)
+    }
 
-    #[serde(alias = "field")]
-    pub fields: Option<Vec<Field>>,
+    /// Create an instance from a dictionary shaped like the output of `to_dict`
+    #[classmethod]
+    fn from_dict(_cls: &Bound<'_, PyType>, data: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Self::from_py_dict(data)
+    }
+
+    /// Create an instance from a JSON string
+    #[classmethod]
+    fn from_json(_cls: &Bound<'_, PyType>, json: &str) -> PyResult<Self> {
+        from_json(json)
+    }
 }
 
 #[cfg(feature = "python")]
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-#[pyclass(get_all, skip_from_py_object)]
+impl FromPyDict for Field {
+    fn from_py_dict(dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Ok(Self {
+            name: dict_get(dict, "name")?,
+            field_type: dict_get(dict, "field_type")?,
+            data_type: dict_get(dict, "data_type")?,
+            error_code: dict_get(dict, "error_code")?,
+            when_created: dict_get(dict, "when_created")?,
+            keep_history: dict_get(dict, "keep_history")?,
+            entries: dict_get_models(dict, "entries")?,
+            comments: dict_get_models(dict, "comments")?,
+            extra: dict_get_extra(dict)?,
+            extra_elements: dict_get_elements(dict)?,
+        })
+    }
+}
+
+#[cfg_attr(
+    feature = "python",
+    pyclass(get_all, set_all, eq, module = "prelude_xml_parser", from_py_object)
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash, JsonSchema)]
 pub struct Category {
     #[serde(rename = "name")]
     #[serde(alias = "@name")]
@@ -628,11 +1001,103 @@ pub struct Category {
 
     #[serde(alias = "field")]
     pub fields: Option<Vec<Field>>,
+
+    /// Attributes the parser does not know, kept when parsing with `ParseOptions::keep_unknown`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, String>,
+    /// Child elements the parser does not know, kept when parsing with
+    /// `ParseOptions::keep_unknown`.
+    #[serde(
+        rename = "extraElements",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub extra_elements: Vec<XmlElement>,
+}
+
+/// The fields of one instance of a repeating category, e.g. one row of an adverse event log.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CategoryInstance<'a> {
+    /// The repeat index of the fields, `None` for fields that do not repeat.
+    pub index: Option<usize>,
+    pub fields: Vec<&'a Field>,
+}
+
+impl Category {
+    /// Whether the category repeats, which Prelude exports with the type "indexed".
+    pub fn is_repeating(&self) -> bool {
+        self.category_type == "indexed"
+    }
+
+    /// Groups the fields by their repeat index, fields that do not repeat first.
+    ///
+    /// A repeating category has an instance for every index up to `highest_index`, also when
+    /// all of its fields were empty and so left out of the export.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::path::Path;
+    ///
+    /// use prelude_xml_parser::parse_subject_native_file;
+    ///
+    /// let native =
+    ///     parse_subject_native_file(Path::new("tests/assets/subject_native_repeating.xml")).unwrap();
+    /// let medications = &native.patients[0].forms.as_ref().unwrap()[1]
+    ///     .categories
+    ///     .as_ref()
+    ///     .unwrap()[0];
+    ///
+    /// for instance in medications.instances() {
+    ///     let names: Vec<&str> = instance.fields.iter().map(|f| f.base_name()).collect();
+    ///     assert_eq!(names, vec!["cm_name", "cm_dose"]);
+    /// }
+    /// assert_eq!(medications.instances().len(), 2);
+    /// ```
+    pub fn instances(&self) -> Vec<CategoryInstance<'_>> {
+        let mut instances: BTreeMap<Option<usize>, Vec<&Field>> = BTreeMap::new();
+        if self.is_repeating() {
+            for index in 1..=self.highest_index {
+                instances.insert(Some(index), Vec::new());
+            }
+        }
+        for field in self.fields.iter().flatten() {
+            instances
+                .entry(field.repeat_index())
+                .or_default()
+                .push(field);
+        }
+
+        instances
+            .into_iter()
+            .map(|(index, fields)| CategoryInstance { index, fields })
+            .collect()
+    }
 }
 
 #[cfg(feature = "python")]
 #[pymethods]
 impl Category {
+    #[new]
+    #[pyo3(signature = (name, category_type, highest_index, fields=None, extra=None, extra_elements=None))]
+    fn new(
+        name: String,
+        category_type: String,
+        highest_index: usize,
+        fields: Option<Vec<Field>>,
+        extra: Option<BTreeMap<String, String>>,
+        extra_elements: Option<Vec<XmlElement>>,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            name,
+            category_type,
+            highest_index,
+            fields,
+            extra: extra.unwrap_or_default(),
+            extra_elements: extra_elements.unwrap_or_default(),
+        })
+    }
+
     #[getter]
     fn name(&self) -> PyResult<String> {
         Ok(self.name.clone())
@@ -648,11 +1113,45 @@ impl Category {
         Ok(self.highest_index)
     }
 
+    /// A copy of the category's fields; assign a changed copy back to update the category.
     #[getter]
     fn fields(&self) -> PyResult<Option<Vec<Field>>> {
         Ok(self.fields.clone())
     }
 
+    /// A copy of the category's unknown attributes; assign a changed copy back to update the
+    /// category.
+    #[getter]
+    fn extra(&self) -> PyResult<BTreeMap<String, String>> {
+        Ok(self.extra.clone())
+    }
+
+    /// A copy of the category's unknown child elements; assign a changed copy back to update the
+    /// category.
+    #[getter]
+    fn extra_elements(&self) -> PyResult<Vec<XmlElement>> {
+        Ok(self.extra_elements.clone())
+    }
+
+    /// Whether the category repeats
+    #[pyo3(name = "is_repeating")]
+    fn py_is_repeating(&self) -> bool {
+        self.is_repeating()
+    }
+
+    /// The fields grouped by repeat index as `(index, fields)` tuples, fields that do not repeat
+    /// first with the index `None`
+    #[pyo3(name = "instances")]
+    fn py_instances(&self) -> Vec<(Option<usize>, Vec<Field>)> {
+        self.instances()
+            .into_iter()
+            .map(|instance| {
+                let fields = instance.fields.into_iter().cloned().collect();
+                (instance.index, fields)
+            })
+            .collect()
+    }
+
     pub fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
         let dict = PyDict::new(py);
         dict.set_item("name", &self.name)?;
@@ -670,12 +1169,75 @@ impl Category {
             dict.set_item("fields", py.None())?;
         }
 
+        dict.set_item("extra", &self.extra)?;
+        dict.set_item("extra_elements", to_dicts(py, &self.extra_elements)?)?;
+
         Ok(dict)
     }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(Repr::new(py, "Category")
+            .field("name", &self.name)?
+            .field("category_type", &self.category_type)?
+            .field("highest_index", self.highest_index)?
+            .items("fields", self.fields.as_ref().map(Vec::len))
+            .finish())
+    }
+
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, (String,))> {
+        reduce(slf)
+    }
+
+    fn __hash__(&self) -> u64 {
+        hash(self)
+    }
+
+    /// Create an instance from a dictionary shaped like the output of `to_dict`
+    #[classmethod]
+    fn from_dict(_cls: &Bound<'_, PyType>, data: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Self::from_py_dict(data)
+    }
+
+    /// Create an instance from a JSON string
+    #[classmethod]
+    fn from_json(_cls: &Bound<'_, PyType>, json: &str) -> PyResult<Self> {
+        from_json(json)
+    }
 }
 
-impl Form {
-    pub(crate) fn from_attributes(
+#[cfg(feature = "python")]
+impl FromPyDict for Category {
+    fn from_py_dict(dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Ok(Self {
+            name: dict_get(dict, "name")?,
+            category_type: dict_get(dict, "category_type")?,
+            highest_index: dict_get(dict, "highest_index")?,
+            fields: dict_get_models(dict, "fields")?,
+            extra: dict_get_extra(dict)?,
+            extra_elements: dict_get_elements(dict)?,
+        })
+    }
+}
+
+impl FromAttributes for Form {
+    const ATTRIBUTES: &'static [&'static str] = &[
+        "name",
+        "lastModified",
+        "whoLastModifiedName",
+        "whoLastModifiedRole",
+        "whenCreated",
+        "hasErrors",
+        "hasWarnings",
+        "locked",
+        "user",
+        "dateTimeChanged",
+        "formTitle",
+        "formIndex",
+        "formGroup",
+        "formState",
+    ];
+
+    fn from_attributes(
         attrs: std::collections::HashMap<&str, &str>,
     ) -> Result<Self, crate::errors::Error> {
         let name = attrs.get("name").copied().unwrap_or_default().to_string();
@@ -770,17 +1332,26 @@ impl Form {
             states: None,
             lock_state: None,
             categories: None,
+            extra: BTreeMap::new(),
+            extra_elements: Vec::new(),
         })
     }
+
+    fn extra_mut(&mut self) -> &mut BTreeMap<String, String> {
+        &mut self.extra
+    }
 }
 
-fn parse_datetime_internal(s: &str) -> Result<DateTime<Utc>, crate::errors::Error> {
+fn parse_datetime_internal(s: &str) -> Result<DateTime<FixedOffset>, crate::errors::Error> {
     if let Ok(dt) = chrono::DateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S %z") {
-        Ok(dt.with_timezone(&Utc))
+        Ok(dt)
     } else if let Ok(dt) = chrono::DateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%z") {
-        Ok(dt.with_timezone(&Utc))
+        Ok(dt)
     } else if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(s) {
-        Ok(dt.with_timezone(&Utc))
+        Ok(dt)
+    } else if let Ok(dt) = chrono::DateTime::parse_from_str(s, "%d-%b-%Y %H:%M %z") {
+        // The format of the export date on the root element, e.g. "30-May-2024 10:35 -0500"
+        Ok(dt)
     } else {
         Err(crate::errors::Error::ParsingError(
             quick_xml::de::DeError::Custom(format!("Invalid datetime format: {}", s)),
@@ -788,8 +1359,11 @@ fn parse_datetime_internal(s: &str) -> Result<DateTime<Utc>, crate::errors::Erro
     }
 }
 
-#[cfg(not(feature = "python"))]
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(
+    feature = "python",
+    pyclass(set_all, eq, module = "prelude_xml_parser", from_py_object)
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash, JsonSchema)]
 pub struct State {
     #[serde(rename = "value")]
     #[serde(alias = "@value")]
@@ -811,39 +1385,34 @@ pub struct State {
         default = "default_datetime_none",
         deserialize_with = "deserialize_empty_string_as_none_datetime"
     )]
-    pub date_signed: Option<DateTime<Utc>>,
-}
-
-#[cfg(feature = "python")]
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-#[pyclass(skip_from_py_object)]
-pub struct State {
-    #[serde(rename = "value")]
-    #[serde(alias = "@value")]
-    #[serde(alias = "value")]
-    pub value: String,
-    #[serde(rename = "signer")]
-    #[serde(alias = "@signer")]
-    #[serde(alias = "signer")]
-    pub signer: String,
-    #[serde(rename = "signerUniqueId")]
-    #[serde(alias = "@signerUniqueId")]
-    #[serde(alias = "signerUniqueId")]
-    pub signer_unique_id: String,
+    pub date_signed: Option<DateTime<FixedOffset>>,
 
-    #[serde(rename = "dateSigned")]
-    #[serde(alias = "@dateSigned")]
-    #[serde(alias = "dateSigned")]
-    #[serde(
-        default = "default_datetime_none",
-        deserialize_with = "deserialize_empty_string_as_none_datetime"
-    )]
-    pub date_signed: Option<DateTime<Utc>>,
+    /// Attributes the parser does not know, kept when parsing with `ParseOptions::keep_unknown`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, String>,
 }
 
 #[cfg(feature = "python")]
 #[pymethods]
 impl State {
+    #[new]
+    #[pyo3(signature = (value, signer, signer_unique_id, date_signed=None, extra=None))]
+    fn new(
+        value: String,
+        signer: String,
+        signer_unique_id: String,
+        date_signed: Option<DateTime<FixedOffset>>,
+        extra: Option<BTreeMap<String, String>>,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            value,
+            signer,
+            signer_unique_id,
+            date_signed,
+            extra: extra.unwrap_or_default(),
+        })
+    }
+
     #[getter]
     fn value(&self) -> PyResult<String> {
         Ok(self.value.clone())
@@ -864,6 +1433,12 @@ impl State {
         to_py_datetime_option(py, &self.date_signed)
     }
 
+    /// A copy of the state's unknown attributes; assign a changed copy back to update the state.
+    #[getter]
+    fn extra(&self) -> PyResult<BTreeMap<String, String>> {
+        Ok(self.extra.clone())
+    }
+
     pub fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
         let dict = PyDict::new(py);
         dict.set_item("value", &self.value)?;
@@ -871,49 +1446,58 @@ impl State {
         dict.set_item("signer_unique_id", &self.signer_unique_id)?;
         dict.set_item("date_signed", to_py_datetime_option(py, &self.date_signed)?)?;
 
+        dict.set_item("extra", &self.extra)?;
+
         Ok(dict)
     }
-}
 
-#[cfg(not(feature = "python"))]
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-pub struct LockState {
-    #[serde(rename = "locked")]
-    #[serde(alias = "@locked")]
-    #[serde(alias = "locked")]
-    pub locked: bool,
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(Repr::new(py, "State")
+            .field("value", &self.value)?
+            .field("signer", &self.signer)?
+            .field("date_signed", to_py_datetime_option(py, &self.date_signed)?)?
+            .finish())
+    }
 
-    #[serde(rename = "user")]
-    #[serde(alias = "@user")]
-    #[serde(alias = "user")]
-    #[serde(
-        default = "default_string_none",
-        deserialize_with = "deserialize_empty_string_as_none"
-    )]
-    pub user: Option<String>,
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, (String,))> {
+        reduce(slf)
+    }
 
-    #[serde(rename = "userUniqueId")]
-    #[serde(alias = "@userUniqueId")]
-    #[serde(alias = "userUniqueId")]
-    #[serde(
-        default = "default_string_none",
-        deserialize_with = "deserialize_empty_string_as_none"
-    )]
-    pub user_unique_id: Option<String>,
+    fn __hash__(&self) -> u64 {
+        hash(self)
+    }
 
-    #[serde(rename = "dateTimeChanged")]
-    #[serde(alias = "@dateTimeChanged")]
-    #[serde(alias = "dateTimeChanged")]
-    #[serde(
-        default = "default_datetime_none",
-        deserialize_with = "deserialize_empty_string_as_none_datetime"
-    )]
-    pub date_time_changed: Option<DateTime<Utc>>,
+    /// Create an instance from a dictionary shaped like the output of `to_dict`
+    #[classmethod]
+    fn from_dict(_cls: &Bound<'_, PyType>, data: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Self::from_py_dict(data)
+    }
+
+    /// Create an instance from a JSON string
+    #[classmethod]
+    fn from_json(_cls: &Bound<'_, PyType>, json: &str) -> PyResult<Self> {
+        from_json(json)
+    }
 }
 
 #[cfg(feature = "python")]
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-#[pyclass(skip_from_py_object)]
+impl FromPyDict for State {
+    fn from_py_dict(dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Ok(Self {
+            value: dict_get(dict, "value")?,
+            signer: dict_get(dict, "signer")?,
+            signer_unique_id: dict_get(dict, "signer_unique_id")?,
+            date_signed: dict_get(dict, "date_signed")?,
+            extra: dict_get_extra(dict)?,
+        })
+    }
+}
+
+#[cfg_attr(
+    feature = "python",
+    pyclass(set_all, eq, module = "prelude_xml_parser", from_py_object)
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash, JsonSchema)]
 pub struct LockState {
     #[serde(rename = "locked")]
     #[serde(alias = "@locked")]
@@ -945,12 +1529,34 @@ pub struct LockState {
         default = "default_datetime_none",
         deserialize_with = "deserialize_empty_string_as_none_datetime"
     )]
-    pub date_time_changed: Option<DateTime<Utc>>,
+    pub date_time_changed: Option<DateTime<FixedOffset>>,
+
+    /// Attributes the parser does not know, kept when parsing with `ParseOptions::keep_unknown`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, String>,
 }
 
 #[cfg(feature = "python")]
 #[pymethods]
 impl LockState {
+    #[new]
+    #[pyo3(signature = (locked=false, user=None, user_unique_id=None, date_time_changed=None, extra=None))]
+    fn new(
+        locked: bool,
+        user: Option<String>,
+        user_unique_id: Option<String>,
+        date_time_changed: Option<DateTime<FixedOffset>>,
+        extra: Option<BTreeMap<String, String>>,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            locked,
+            user,
+            user_unique_id,
+            date_time_changed,
+            extra: extra.unwrap_or_default(),
+        })
+    }
+
     #[getter]
     fn locked(&self) -> PyResult<bool> {
         Ok(self.locked)
@@ -971,126 +1577,78 @@ impl LockState {
         to_py_datetime_option(py, &self.date_time_changed)
     }
 
-    pub fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
-        let dict = PyDict::new(py);
-        dict.set_item("locked", self.locked)?;
-        dict.set_item("user", &self.user)?;
-        dict.set_item("user_unique_id", &self.user_unique_id)?;
-        dict.set_item(
-            "date_time_changed",
-            to_py_datetime_option(py, &self.date_time_changed)?,
-        )?;
-
-        Ok(dict)
+    /// A copy of the lock state's unknown attributes; assign a changed copy back to update the lock
+    /// state.
+    #[getter]
+    fn extra(&self) -> PyResult<BTreeMap<String, String>> {
+        Ok(self.extra.clone())
     }
-}
-
-#[cfg(not(feature = "python"))]
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-pub struct Form {
-    #[serde(rename = "name")]
-    #[serde(alias = "@name")]
-    #[serde(alias = "name")]
-    pub name: String,
-
-    #[serde(rename = "lastModified")]
-    #[serde(alias = "@lastModified")]
-    #[serde(alias = "lastModified")]
-    #[serde(
-        default = "default_datetime_none",
-        deserialize_with = "deserialize_empty_string_as_none_datetime"
-    )]
-    pub last_modified: Option<DateTime<Utc>>,
-
-    #[serde(rename = "whoLastModifiedName")]
-    #[serde(alias = "@whoLastModifiedName")]
-    #[serde(alias = "whoLastModifiedName")]
-    #[serde(
-        default = "default_string_none",
-        deserialize_with = "deserialize_empty_string_as_none"
-    )]
-    pub who_last_modified_name: Option<String>,
-
-    #[serde(rename = "whoLastModifiedRole")]
-    #[serde(alias = "@whoLastModifiedRole")]
-    #[serde(alias = "whoLastModifiedRole")]
-    #[serde(
-        default = "default_string_none",
-        deserialize_with = "deserialize_empty_string_as_none"
-    )]
-    pub who_last_modified_role: Option<String>,
-
-    #[serde(rename = "whenCreated")]
-    #[serde(alias = "@whenCreated")]
-    #[serde(alias = "whenCreated")]
-    pub when_created: usize,
-    #[serde(rename = "hasErrors")]
-    #[serde(alias = "@hasErrors")]
-    #[serde(alias = "hasErrors")]
-    pub has_errors: bool,
-    #[serde(rename = "hasWarnings")]
-    #[serde(alias = "@hasWarnings")]
-    #[serde(alias = "hasWarnings")]
-    pub has_warnings: bool,
-    #[serde(rename = "locked")]
-    #[serde(alias = "@locked")]
-    #[serde(alias = "locked")]
-    pub locked: bool,
 
-    #[serde(rename = "user")]
-    #[serde(alias = "@user")]
-    #[serde(alias = "user")]
-    #[serde(
-        default = "default_string_none",
-        deserialize_with = "deserialize_empty_string_as_none"
-    )]
-    pub user: Option<String>,
+    pub fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("locked", self.locked)?;
+        dict.set_item("user", &self.user)?;
+        dict.set_item("user_unique_id", &self.user_unique_id)?;
+        dict.set_item(
+            "date_time_changed",
+            to_py_datetime_option(py, &self.date_time_changed)?,
+        )?;
 
-    #[serde(rename = "dateTimeChanged")]
-    #[serde(alias = "@dateTimeChanged")]
-    #[serde(alias = "dateTimeChanged")]
-    #[serde(
-        default = "default_datetime_none",
-        deserialize_with = "deserialize_empty_string_as_none_datetime"
-    )]
-    pub date_time_changed: Option<DateTime<Utc>>,
+        dict.set_item("extra", &self.extra)?;
 
-    #[serde(rename = "formTitle")]
-    #[serde(alias = "@formTitle")]
-    #[serde(alias = "formTitle")]
-    pub form_title: String,
-    #[serde(rename = "formIndex")]
-    #[serde(alias = "@formIndex")]
-    #[serde(alias = "formIndex")]
-    pub form_index: usize,
+        Ok(dict)
+    }
 
-    #[serde(rename = "formGroup")]
-    #[serde(alias = "@formGroup")]
-    #[serde(alias = "formGroup")]
-    #[serde(
-        default = "default_string_none",
-        deserialize_with = "deserialize_empty_string_as_none"
-    )]
-    pub form_group: Option<String>,
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(Repr::new(py, "LockState")
+            .field("locked", self.locked)?
+            .field("user", &self.user)?
+            .field(
+                "date_time_changed",
+                to_py_datetime_option(py, &self.date_time_changed)?,
+            )?
+            .finish())
+    }
 
-    #[serde(rename = "formState")]
-    #[serde(alias = "@formState")]
-    #[serde(alias = "formState")]
-    pub form_state: String,
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, (String,))> {
+        reduce(slf)
+    }
 
-    #[serde(alias = "state")]
-    pub states: Option<Vec<State>>,
+    fn __hash__(&self) -> u64 {
+        hash(self)
+    }
 
-    #[serde(alias = "lockState")]
-    pub lock_state: Option<LockState>,
+    /// Create an instance from a dictionary shaped like the output of `to_dict`
+    #[classmethod]
+    fn from_dict(_cls: &Bound<'_, PyType>, data: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Self::from_py_dict(data)
+    }
 
-    #[serde(alias = "category")]
-    pub categories: Option<Vec<Category>>,
+    /// Create an instance from a JSON string
+    #[classmethod]
+    fn from_json(_cls: &Bound<'_, PyType>, json: &str) -> PyResult<Self> {
+        from_json(json)
+    }
 }
 
 #[cfg(feature = "python")]
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-#[pyclass(skip_from_py_object)]
+impl FromPyDict for LockState {
+    fn from_py_dict(dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Ok(Self {
+            locked: dict_get(dict, "locked")?,
+            user: dict_get(dict, "user")?,
+            user_unique_id: dict_get(dict, "user_unique_id")?,
+            date_time_changed: dict_get(dict, "date_time_changed")?,
+            extra: dict_get_extra(dict)?,
+        })
+    }
+}
+
+#[cfg_attr(
+    feature = "python",
+    pyclass(set_all, eq, module = "prelude_xml_parser", from_py_object)
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash, JsonSchema)]
 pub struct Form {
     #[serde(rename = "name")]
     #[serde(alias = "@name")]
@@ -1104,7 +1662,7 @@ pub struct Form {
         default = "default_datetime_none",
         deserialize_with = "deserialize_empty_string_as_none_datetime"
     )]
-    pub last_modified: Option<DateTime<Utc>>,
+    pub last_modified: Option<DateTime<FixedOffset>>,
 
     #[serde(rename = "whoLastModifiedName")]
     #[serde(alias = "@whoLastModifiedName")]
@@ -1157,7 +1715,7 @@ pub struct Form {
         default = "default_datetime_none",
         deserialize_with = "deserialize_empty_string_as_none_datetime"
     )]
-    pub date_time_changed: Option<DateTime<Utc>>,
+    pub date_time_changed: Option<DateTime<FixedOffset>>,
 
     #[serde(rename = "formTitle")]
     #[serde(alias = "@formTitle")]
@@ -1185,16 +1743,76 @@ pub struct Form {
     #[serde(alias = "state")]
     pub states: Option<Vec<State>>,
 
-    #[serde(alias = "lockState")]
+    #[serde(rename = "lockState")]
+    #[serde(alias = "lock_state")]
     pub lock_state: Option<LockState>,
 
     #[serde(alias = "category")]
     pub categories: Option<Vec<Category>>,
+
+    /// Attributes the parser does not know, kept when parsing with `ParseOptions::keep_unknown`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, String>,
+    /// Child elements the parser does not know, kept when parsing with
+    /// `ParseOptions::keep_unknown`.
+    #[serde(
+        rename = "extraElements",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub extra_elements: Vec<XmlElement>,
 }
 
 #[cfg(feature = "python")]
 #[pymethods]
 impl Form {
+    #[new]
+    #[pyo3(signature = (name, when_created, form_title, form_index, form_state, last_modified=None, who_last_modified_name=None, who_last_modified_role=None, has_errors=false, has_warnings=false, locked=false, user=None, date_time_changed=None, form_group=None, states=None, lock_state=None, categories=None, extra=None, extra_elements=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        name: String,
+        when_created: usize,
+        form_title: String,
+        form_index: usize,
+        form_state: String,
+        last_modified: Option<DateTime<FixedOffset>>,
+        who_last_modified_name: Option<String>,
+        who_last_modified_role: Option<String>,
+        has_errors: bool,
+        has_warnings: bool,
+        locked: bool,
+        user: Option<String>,
+        date_time_changed: Option<DateTime<FixedOffset>>,
+        form_group: Option<String>,
+        states: Option<Vec<State>>,
+        lock_state: Option<LockState>,
+        categories: Option<Vec<Category>>,
+        extra: Option<BTreeMap<String, String>>,
+        extra_elements: Option<Vec<XmlElement>>,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            name,
+            last_modified,
+            who_last_modified_name,
+            who_last_modified_role,
+            when_created,
+            has_errors,
+            has_warnings,
+            locked,
+            user,
+            date_time_changed,
+            form_title,
+            form_index,
+            form_group,
+            form_state,
+            states,
+            lock_state,
+            categories,
+            extra: extra.unwrap_or_default(),
+            extra_elements: extra_elements.unwrap_or_default(),
+        })
+    }
+
     #[getter]
     fn name(&self) -> PyResult<String> {
         Ok(self.name.clone())
@@ -1265,21 +1883,36 @@ impl Form {
         Ok(self.form_state.clone())
     }
 
+    /// A copy of the form's states; assign a changed copy back to update the form.
     #[getter]
     fn states(&self) -> PyResult<Option<Vec<State>>> {
         Ok(self.states.clone())
     }
 
+    /// A copy of the form's lock state; assign a changed copy back to update the form.
     #[getter]
     fn lock_state(&self) -> PyResult<Option<LockState>> {
         Ok(self.lock_state.clone())
     }
 
+    /// A copy of the form's categories; assign a changed copy back to update the form.
     #[getter]
     fn categories(&self) -> PyResult<Option<Vec<Category>>> {
         Ok(self.categories.clone())
     }
 
+    /// A copy of the form's unknown attributes; assign a changed copy back to update the form.
+    #[getter]
+    fn extra(&self) -> PyResult<BTreeMap<String, String>> {
+        Ok(self.extra.clone())
+    }
+
+    /// A copy of the form's unknown child elements; assign a changed copy back to update the form.
+    #[getter]
+    fn extra_elements(&self) -> PyResult<Vec<XmlElement>> {
+        Ok(self.extra_elements.clone())
+    }
+
     pub fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
         let dict = PyDict::new(py);
         dict.set_item("name", &self.name)?;
@@ -1331,12 +1964,75 @@ impl Form {
             dict.set_item("categories", py.None())?;
         }
 
+        dict.set_item("extra", &self.extra)?;
+        dict.set_item("extra_elements", to_dicts(py, &self.extra_elements)?)?;
+
         Ok(dict)
     }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(Repr::new(py, "Form")
+            .field("name", &self.name)?
+            .field("form_title", &self.form_title)?
+            .field("form_index", self.form_index)?
+            .field("form_state", &self.form_state)?
+            .items("categories", self.categories.as_ref().map(Vec::len))
+            .finish())
+    }
+
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, (String,))> {
+        reduce(slf)
+    }
+
+    fn __hash__(&self) -> u64 {
+        hash(self)
+    }
+
+    /// Create an instance from a dictionary shaped like the output of `to_dict`
+    #[classmethod]
+    fn from_dict(_cls: &Bound<'_, PyType>, data: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Self::from_py_dict(data)
+    }
+
+    /// Create an instance from a JSON string
+    #[classmethod]
+    fn from_json(_cls: &Bound<'_, PyType>, json: &str) -> PyResult<Self> {
+        from_json(json)
+    }
 }
 
-impl State {
-    pub(crate) fn from_attributes(
+#[cfg(feature = "python")]
+impl FromPyDict for Form {
+    fn from_py_dict(dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Ok(Self {
+            name: dict_get(dict, "name")?,
+            last_modified: dict_get(dict, "last_modified")?,
+            who_last_modified_name: dict_get(dict, "who_last_modified_name")?,
+            who_last_modified_role: dict_get(dict, "who_last_modified_role")?,
+            when_created: dict_get(dict, "when_created")?,
+            has_errors: dict_get(dict, "has_errors")?,
+            has_warnings: dict_get(dict, "has_warnings")?,
+            locked: dict_get(dict, "locked")?,
+            user: dict_get(dict, "user")?,
+            date_time_changed: dict_get(dict, "date_time_changed")?,
+            form_title: dict_get(dict, "form_title")?,
+            form_index: dict_get(dict, "form_index")?,
+            form_group: dict_get(dict, "form_group")?,
+            form_state: dict_get(dict, "form_state")?,
+            states: dict_get_models(dict, "states")?,
+            lock_state: dict_get_model(dict, "lock_state")?,
+            categories: dict_get_models(dict, "categories")?,
+            extra: dict_get_extra(dict)?,
+            extra_elements: dict_get_elements(dict)?,
+        })
+    }
+}
+
+impl FromAttributes for State {
+    const ATTRIBUTES: &'static [&'static str] =
+        &["value", "signer", "signerUniqueId", "dateSigned"];
+
+    fn from_attributes(
         attrs: std::collections::HashMap<&str, &str>,
     ) -> Result<Self, crate::errors::Error> {
         let value = attrs.get("value").copied().unwrap_or_default().to_string();
@@ -1362,12 +2058,20 @@ impl State {
             signer,
             signer_unique_id,
             date_signed,
+            extra: BTreeMap::new(),
         })
     }
+
+    fn extra_mut(&mut self) -> &mut BTreeMap<String, String> {
+        &mut self.extra
+    }
 }
 
-impl LockState {
-    pub(crate) fn from_attributes(
+impl FromAttributes for LockState {
+    const ATTRIBUTES: &'static [&'static str] =
+        &["locked", "user", "userUniqueId", "dateTimeChanged"];
+
+    fn from_attributes(
         attrs: std::collections::HashMap<&str, &str>,
     ) -> Result<Self, crate::errors::Error> {
         let locked = attrs.get("locked").map(|s| *s == "true").unwrap_or(false);
@@ -1395,12 +2099,19 @@ impl LockState {
             user,
             user_unique_id,
             date_time_changed,
+            extra: BTreeMap::new(),
         })
     }
+
+    fn extra_mut(&mut self) -> &mut BTreeMap<String, String> {
+        &mut self.extra
+    }
 }
 
-impl Category {
-    pub(crate) fn from_attributes(
+impl FromAttributes for Category {
+    const ATTRIBUTES: &'static [&'static str] = &["name", "type", "highestIndex"];
+
+    fn from_attributes(
         attrs: std::collections::HashMap<&str, &str>,
     ) -> Result<Self, crate::errors::Error> {
         let name = attrs.get("name").copied().unwrap_or_default().to_string();
@@ -1415,12 +2126,27 @@ impl Category {
             category_type,
             highest_index,
             fields: None,
+            extra: BTreeMap::new(),
+            extra_elements: Vec::new(),
         })
     }
+
+    fn extra_mut(&mut self) -> &mut BTreeMap<String, String> {
+        &mut self.extra
+    }
 }
 
-impl Field {
-    pub(crate) fn from_attributes(
+impl FromAttributes for Field {
+    const ATTRIBUTES: &'static [&'static str] = &[
+        "name",
+        "type",
+        "dataType",
+        "errorCode",
+        "whenCreated",
+        "keepHistory",
+    ];
+
+    fn from_attributes(
         attrs: std::collections::HashMap<&str, &str>,
     ) -> Result<Self, crate::errors::Error> {
         let name = attrs.get("name").copied().unwrap_or_default().to_string();
@@ -1459,12 +2185,26 @@ impl Field {
             keep_history,
             entries: None,
             comments: None,
+            extra: BTreeMap::new(),
+            extra_elements: Vec::new(),
         })
     }
+
+    fn extra_mut(&mut self) -> &mut BTreeMap<String, String> {
+        &mut self.extra
+    }
 }
 
-impl Entry {
-    pub(crate) fn from_attributes(
+impl FromAttributes for Entry {
+    const ATTRIBUTES: &'static [&'static str] = &[
+        "id",
+        "entryId",
+        "reviewedBy",
+        "reviewedByUniqueId",
+        "reviewedByWhen",
+    ];
+
+    fn from_attributes(
         attrs: std::collections::HashMap<&str, &str>,
     ) -> Result<Self, crate::errors::Error> {
         let entry_id = attrs
@@ -1500,12 +2240,20 @@ impl Entry {
             reviewed_by_when,
             value: None,
             reason: None,
+            extra: BTreeMap::new(),
+            extra_elements: Vec::new(),
         })
     }
+
+    fn extra_mut(&mut self) -> &mut BTreeMap<String, String> {
+        &mut self.extra
+    }
 }
 
-impl Value {
-    pub(crate) fn from_attributes(
+impl FromAttributes for Value {
+    const ATTRIBUTES: &'static [&'static str] = &["by", "byUniqueId", "role", "when", "xml:space"];
+
+    fn from_attributes(
         attrs: std::collections::HashMap<&str, &str>,
     ) -> Result<Self, crate::errors::Error> {
         let by = attrs.get("by").copied().unwrap_or_default().to_string();
@@ -1531,12 +2279,19 @@ impl Value {
             role,
             when,
             value: String::new(),
+            extra: BTreeMap::new(),
         })
     }
+
+    fn extra_mut(&mut self) -> &mut BTreeMap<String, String> {
+        &mut self.extra
+    }
 }
 
-impl Reason {
-    pub(crate) fn from_attributes(
+impl FromAttributes for Reason {
+    const ATTRIBUTES: &'static [&'static str] = &["by", "byUniqueId", "role", "when", "xml:space"];
+
+    fn from_attributes(
         attrs: std::collections::HashMap<&str, &str>,
     ) -> Result<Self, crate::errors::Error> {
         let by = attrs.get("by").copied().unwrap_or_default().to_string();
@@ -1562,6 +2317,341 @@ impl Reason {
             role,
             when,
             value: String::new(),
+            extra: BTreeMap::new(),
+        })
+    }
+
+    fn extra_mut(&mut self) -> &mut BTreeMap<String, String> {
+        &mut self.extra
+    }
+}
+
+/// The attributes of the `<export_from_vision_EDC>` root element of a native export.
+#[cfg_attr(
+    feature = "python",
+    pyclass(set_all, eq, module = "prelude_xml_parser", from_py_object)
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash, JsonSchema)]
+pub struct ExportInfo {
+    /// When the export was taken.
+    #[serde(rename = "date")]
+    #[serde(alias = "@date")]
+    #[serde(
+        default = "default_datetime_none",
+        deserialize_with = "deserialize_empty_string_as_none_datetime"
+    )]
+    pub date: Option<DateTime<FixedOffset>>,
+
+    #[serde(rename = "createdBy")]
+    #[serde(alias = "@createdBy")]
+    #[serde(alias = "created_by")]
+    pub created_by: String,
+
+    #[serde(rename = "role")]
+    #[serde(alias = "@role")]
+    pub role: String,
+
+    #[serde(rename = "numberSubjectsProcessed")]
+    #[serde(alias = "@numberSubjectsProcessed")]
+    #[serde(alias = "number_subjects_processed")]
+    pub number_subjects_processed: usize,
+
+    /// Attributes the parser does not know, kept when parsing with `ParseOptions::keep_unknown`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, String>,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl ExportInfo {
+    #[new]
+    #[pyo3(signature = (created_by, role, number_subjects_processed=0, date=None, extra=None))]
+    fn new(
+        created_by: String,
+        role: String,
+        number_subjects_processed: usize,
+        date: Option<DateTime<FixedOffset>>,
+        extra: Option<BTreeMap<String, String>>,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            date,
+            created_by,
+            role,
+            number_subjects_processed,
+            extra: extra.unwrap_or_default(),
+        })
+    }
+
+    #[getter]
+    fn date<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDateTime>>> {
+        to_py_datetime_option(py, &self.date)
+    }
+
+    #[getter]
+    fn created_by(&self) -> PyResult<String> {
+        Ok(self.created_by.clone())
+    }
+
+    #[getter]
+    fn role(&self) -> PyResult<String> {
+        Ok(self.role.clone())
+    }
+
+    #[getter]
+    fn number_subjects_processed(&self) -> PyResult<usize> {
+        Ok(self.number_subjects_processed)
+    }
+
+    /// A copy of the export's unknown attributes; assign a changed copy back to update the export.
+    #[getter]
+    fn extra(&self) -> PyResult<BTreeMap<String, String>> {
+        Ok(self.extra.clone())
+    }
+
+    pub fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("date", to_py_datetime_option(py, &self.date)?)?;
+        dict.set_item("created_by", &self.created_by)?;
+        dict.set_item("role", &self.role)?;
+        dict.set_item("number_subjects_processed", self.number_subjects_processed)?;
+
+        dict.set_item("extra", &self.extra)?;
+
+        Ok(dict)
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(Repr::new(py, "ExportInfo")
+            .field("date", to_py_datetime_option(py, &self.date)?)?
+            .field("created_by", &self.created_by)?
+            .field("number_subjects_processed", self.number_subjects_processed)?
+            .finish())
+    }
+
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, (String,))> {
+        reduce(slf)
+    }
+
+    fn __hash__(&self) -> u64 {
+        hash(self)
+    }
+
+    /// Create an instance from a dictionary shaped like the output of `to_dict`
+    #[classmethod]
+    fn from_dict(_cls: &Bound<'_, PyType>, data: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Self::from_py_dict(data)
+    }
+
+    /// Create an instance from a JSON string
+    #[classmethod]
+    fn from_json(_cls: &Bound<'_, PyType>, json: &str) -> PyResult<Self> {
+        from_json(json)
+    }
+}
+
+#[cfg(feature = "python")]
+impl FromPyDict for ExportInfo {
+    fn from_py_dict(dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Ok(Self {
+            date: dict_get(dict, "date")?,
+            created_by: dict_get(dict, "created_by")?,
+            role: dict_get(dict, "role")?,
+            number_subjects_processed: dict_get(dict, "number_subjects_processed")?,
+            extra: dict_get_extra(dict)?,
+        })
+    }
+}
+
+impl FromAttributes for ExportInfo {
+    const ATTRIBUTES: &'static [&'static str] =
+        &["date", "createdBy", "role", "numberSubjectsProcessed"];
+
+    fn from_attributes(
+        attrs: std::collections::HashMap<&str, &str>,
+    ) -> Result<Self, crate::errors::Error> {
+        let date = match attrs.get("date") {
+            Some(d) if !d.is_empty() => Some(parse_datetime_internal(d)?),
+            _ => None,
+        };
+        let created_by = attrs
+            .get("createdBy")
+            .copied()
+            .unwrap_or_default()
+            .to_string();
+        let role = attrs.get("role").copied().unwrap_or_default().to_string();
+        let number_subjects_processed = attrs
+            .get("numberSubjectsProcessed")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0);
+
+        Ok(ExportInfo {
+            date,
+            created_by,
+            role,
+            number_subjects_processed,
+            extra: BTreeMap::new(),
+        })
+    }
+
+    fn extra_mut(&mut self) -> &mut BTreeMap<String, String> {
+        &mut self.extra
+    }
+}
+
+/// A model read from the attributes of its XML element.
+pub trait FromAttributes: Sized {
+    /// The attributes `from_attributes` reads. Any others are unknown to the parser.
+    const ATTRIBUTES: &'static [&'static str];
+
+    /// Builds the model from the unescaped attribute values of its element.
+    fn from_attributes(
+        attrs: std::collections::HashMap<&str, &str>,
+    ) -> Result<Self, crate::errors::Error>;
+
+    /// The unknown attributes of the element.
+    fn extra_mut(&mut self) -> &mut BTreeMap<String, String>;
+}
+
+/// A top level record of a native export, e.g. a `<patient>` of a subject native.
+///
+/// The parse engine reads each `<TAG ...>` element into a record with [`FromAttributes`], and the
+/// forms inside it with the shared form, category, field, and entry models. `Patient`, `Site`, and
+/// `User` implement it; implement it for other Prelude record types to parse them with
+/// [`crate::parse_native_records`] or [`crate::iter_native_records`].
+pub trait NativeRecord: FromAttributes {
+    /// The name of the record's element, e.g. `patient`.
+    const TAG: &'static str;
+
+    /// Whether to keep self-closing `<field .../>` elements, which have no entries or comments.
+    const KEEP_EMPTY_FIELDS: bool = true;
+
+    /// Sets the forms read from inside the record. Only called when there is at least one.
+    fn set_forms(&mut self, forms: Vec<Form>);
+
+    /// The unknown child elements of the record.
+    fn extra_elements_mut(&mut self) -> &mut Vec<XmlElement>;
+}
+
+impl FromAttributes for Comment {
+    const ATTRIBUTES: &'static [&'static str] = &["id"];
+
+    fn from_attributes(
+        attrs: std::collections::HashMap<&str, &str>,
+    ) -> Result<Self, crate::errors::Error> {
+        Ok(Comment {
+            comment_id: attrs.get("id").copied().unwrap_or_default().to_string(),
+            value: None,
+            replies: None,
+            reason: None,
+            extra: BTreeMap::new(),
+            extra_elements: Vec::new(),
+        })
+    }
+
+    fn extra_mut(&mut self) -> &mut BTreeMap<String, String> {
+        &mut self.extra
+    }
+}
+
+/// An XML element the parser does not know, e.g. one added by a newer version of Prelude, with
+/// everything inside it.
+#[cfg_attr(
+    feature = "python",
+    pyclass(get_all, set_all, eq, module = "prelude_xml_parser", from_py_object)
+)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq, Hash, JsonSchema)]
+pub struct XmlElement {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, String>,
+    /// The text of the element, trimmed and not unescaped like the text of a `Value`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<XmlElement>,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl XmlElement {
+    #[new]
+    #[pyo3(signature = (name, attributes=None, text=String::new(), children=None))]
+    fn new(
+        name: String,
+        attributes: Option<BTreeMap<String, String>>,
+        text: String,
+        children: Option<Vec<XmlElement>>,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            name,
+            attributes: attributes.unwrap_or_default(),
+            text,
+            children: children.unwrap_or_default(),
+        })
+    }
+
+    /// A copy of the element's attributes; assign a changed copy back to update the element.
+    #[getter]
+    fn attributes(&self) -> PyResult<BTreeMap<String, String>> {
+        Ok(self.attributes.clone())
+    }
+
+    /// A copy of the element's children; assign a changed copy back to update the element.
+    #[getter]
+    fn children(&self) -> PyResult<Vec<XmlElement>> {
+        Ok(self.children.clone())
+    }
+
+    pub fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("name", &self.name)?;
+        dict.set_item("attributes", &self.attributes)?;
+        dict.set_item("text", &self.text)?;
+        dict.set_item("children", to_dicts(py, &self.children)?)?;
+
+        Ok(dict)
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(Repr::new(py, "XmlElement")
+            .field("name", &self.name)?
+            .field("attributes", self.attributes.clone())?
+            .field("text", &self.text)?
+            .items("children", Some(self.children.len()))
+            .finish())
+    }
+
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, (String,))> {
+        reduce(slf)
+    }
+
+    fn __hash__(&self) -> u64 {
+        hash(self)
+    }
+
+    /// Create an instance from a dictionary shaped like the output of `to_dict`
+    #[classmethod]
+    fn from_dict(_cls: &Bound<'_, PyType>, data: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Self::from_py_dict(data)
+    }
+
+    /// Create an instance from a JSON string
+    #[classmethod]
+    fn from_json(_cls: &Bound<'_, PyType>, json: &str) -> PyResult<Self> {
+        from_json(json)
+    }
+}
+
+#[cfg(feature = "python")]
+impl FromPyDict for XmlElement {
+    fn from_py_dict(dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+        let attributes: Option<BTreeMap<String, String>> = dict_get(dict, "attributes")?;
+        let text: Option<String> = dict_get(dict, "text")?;
+        Ok(Self {
+            name: dict_get(dict, "name")?,
+            attributes: attributes.unwrap_or_default(),
+            text: text.unwrap_or_default(),
+            children: dict_get_models(dict, "children")?.unwrap_or_default(),
         })
     }
 }
@@ -1,16 +1,22 @@
 #[cfg(feature = "python")]
 use chrono::{Datelike, Timelike};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, FixedOffset};
 
 use serde::{Deserialize, Deserializer};
 
 #[cfg(feature = "python")]
-use pyo3::{prelude::*, types::PyDateTime};
+use chrono::Offset;
+
+#[cfg(feature = "python")]
+use pyo3::{
+    prelude::*,
+    types::{PyDateTime, PyDelta, PyTzInfo},
+};
 
 pub fn deserialize_empty_string_as_none_datetime<'de, D>(
     deserializer: D,
-) -> Result<Option<DateTime<Utc>>, D::Error>
+) -> Result<Option<DateTime<FixedOffset>>, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -20,15 +26,14 @@ where
             if v.is_empty() {
                 Ok(None)
             } else {
-                // Parse the datetime with a fixed offset, then convert it to UTC
-
-                let dt_with_offset = if v.ends_with('Z') {
-                    DateTime::parse_from_rfc3339(&v).map_err(serde::de::Error::custom)?
-                } else {
-                    DateTime::parse_from_str(&v, "%Y-%m-%d %H:%M:%S %z")
-                        .map_err(serde::de::Error::custom)?
+                // Keep the offset the timestamp was written with so site local times are not lost
+                let dt = match DateTime::parse_from_rfc3339(&v) {
+                    Ok(dt) => dt,
+                    Err(_) => DateTime::parse_from_str(&v, "%Y-%m-%d %H:%M:%S %z")
+                        .or_else(|_| DateTime::parse_from_str(&v, "%d-%b-%Y %H:%M %z"))
+                        .map_err(serde::de::Error::custom)?,
                 };
-                Ok(Some(dt_with_offset.with_timezone(&Utc)))
+                Ok(Some(dt))
             }
         }
         None => Ok(None),
@@ -47,7 +52,7 @@ where
     }
 }
 
-pub fn default_datetime_none() -> Option<DateTime<Utc>> {
+pub fn default_datetime_none() -> Option<DateTime<FixedOffset>> {
     None
 }
 
@@ -55,11 +60,27 @@ pub fn default_string_none() -> Option<String> {
     None
 }
 
+#[cfg(feature = "python")]
+fn to_py_tzinfo<'py>(
+    py: Python<'py>,
+    date_time: &DateTime<FixedOffset>,
+) -> PyResult<Bound<'py, PyTzInfo>> {
+    let offset_seconds = date_time.offset().fix().local_minus_utc();
+    if offset_seconds == 0 {
+        return Ok(PyTzInfo::utc(py)?.to_owned());
+    }
+
+    let offset = PyDelta::new(py, 0, offset_seconds, 0, true)?;
+    PyTzInfo::fixed_offset(py, offset)
+}
+
+/// Converts a timestamp to a timezone aware Python datetime with the offset it was parsed with.
 #[cfg(feature = "python")]
 pub fn to_py_datetime<'py>(
     py: Python<'py>,
-    date_time: &DateTime<Utc>,
+    date_time: &DateTime<FixedOffset>,
 ) -> PyResult<Bound<'py, PyDateTime>> {
+    let tzinfo = to_py_tzinfo(py, date_time)?;
     let py_datetime = PyDateTime::new(
         py,
         date_time.year(),
@@ -69,7 +90,7 @@ pub fn to_py_datetime<'py>(
         date_time.minute() as u8,
         date_time.second() as u8,
         date_time.timestamp_subsec_micros(),
-        None,
+        Some(&tzinfo),
     )?;
     Ok(py_datetime)
 }
@@ -77,22 +98,107 @@ pub fn to_py_datetime<'py>(
 #[cfg(feature = "python")]
 pub fn to_py_datetime_option<'py>(
     py: Python<'py>,
-    date_time: &Option<DateTime<Utc>>,
+    date_time: &Option<DateTime<FixedOffset>>,
 ) -> PyResult<Option<Bound<'py, PyDateTime>>> {
-    if let Some(d) = date_time {
-        let py_datetime = Some(PyDateTime::new(
-            py,
-            d.year(),
-            d.month() as u8,
-            d.day() as u8,
-            d.hour() as u8,
-            d.minute() as u8,
-            d.second() as u8,
-            d.timestamp_subsec_micros(),
-            None,
-        )?);
-        Ok(py_datetime)
-    } else {
-        Ok(None)
+    date_time
+        .as_ref()
+        .map(|dt| to_py_datetime(py, dt))
+        .transpose()
+}
+
+#[cfg(all(test, feature = "python"))]
+mod tests {
+    use super::*;
+
+    use pyo3::types::PyDict;
+
+    use crate::native::common::{Form, FromAttributes, Value};
+
+    fn parse(s: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S %z").unwrap()
+    }
+
+    fn utc_offset_seconds(datetime: &Bound<'_, PyAny>) -> i64 {
+        datetime
+            .call_method0("utcoffset")
+            .unwrap()
+            .call_method0("total_seconds")
+            .unwrap()
+            .extract::<f64>()
+            .unwrap() as i64
+    }
+
+    #[test]
+    fn to_py_datetime_keeps_offset() {
+        Python::initialize();
+        Python::attach(|py| {
+            let py_datetime = to_py_datetime(py, &parse("2023-04-15 17:00:00 -0400")).unwrap();
+
+            assert!(!py_datetime.getattr("tzinfo").unwrap().is_none());
+            assert_eq!(utc_offset_seconds(&py_datetime), -4 * 3600);
+            assert_eq!(
+                py_datetime
+                    .getattr("hour")
+                    .unwrap()
+                    .extract::<u8>()
+                    .unwrap(),
+                17
+            );
+        });
+    }
+
+    #[test]
+    fn to_py_datetime_uses_timezone_utc_for_zero_offset() {
+        Python::initialize();
+        Python::attach(|py| {
+            let py_datetime = to_py_datetime(py, &parse("2023-04-15 16:09:02 +0000")).unwrap();
+            let utc = py
+                .import("datetime")
+                .unwrap()
+                .getattr("timezone")
+                .unwrap()
+                .getattr("utc")
+                .unwrap();
+
+            assert!(py_datetime.getattr("tzinfo").unwrap().is(&utc));
+        });
+    }
+
+    #[test]
+    fn getters_and_to_dict_return_aware_datetimes() {
+        Python::initialize();
+        Python::attach(|py| {
+            let form = Form::from_attributes(
+                [
+                    ("name", "test.form"),
+                    ("lastModified", "2023-04-15 12:09:15 -0400"),
+                    ("dateTimeChanged", "2023-12-01 09:00:00 -0500"),
+                ]
+                .into_iter()
+                .collect(),
+            )
+            .unwrap();
+            let py_form = Bound::new(py, form).unwrap();
+
+            let last_modified = py_form.getattr("last_modified").unwrap();
+            assert_eq!(utc_offset_seconds(&last_modified), -4 * 3600);
+
+            let form_dict = py_form.call_method0("to_dict").unwrap();
+            let form_dict = form_dict.cast::<PyDict>().unwrap();
+            let date_time_changed = form_dict.get_item("date_time_changed").unwrap().unwrap();
+            assert_eq!(utc_offset_seconds(&date_time_changed), -5 * 3600);
+
+            let mut value = Value::from_attributes(
+                [("by", "Test User"), ("when", "2023-04-15 12:09:02 -0400")]
+                    .into_iter()
+                    .collect(),
+            )
+            .unwrap();
+            value.value = "Test Value".to_string();
+            let py_value = Bound::new(py, value).unwrap();
+            let when = py_value.getattr("when").unwrap();
+            assert!(!when.getattr("tzinfo").unwrap().is_none());
+            assert_eq!(utc_offset_seconds(&when), -4 * 3600);
+        });
     }
 }
@@ -0,0 +1,473 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, FixedOffset};
+use serde::Serialize;
+
+use crate::native::{
+    common::{Entry, Form, State, Value},
+    site_native::{Site, SiteNative},
+    subject_native::{Patient, SubjectNative},
+    user_native::{User, UserNative},
+};
+
+/// One difference between two exports of the same study.
+///
+/// Records are matched by `unique_id`, forms by name and `form_index`, categories and fields by
+/// name, entries by `entry_id`, and the states of a form's signature chain by value, signer, and
+/// date signed. The location fields that do not apply to a change are `None`, e.g. only
+/// `record_unique_id` is set when a record was added.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Change {
+    /// `Patient.unique_id`, `Site.unique_id`, or `User.unique_id`.
+    pub record_unique_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub form_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub form_index: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_id: Option<String>,
+    #[serde(flatten)]
+    pub kind: ChangeKind,
+}
+
+/// What changed, serialized with a `change` tag.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(
+    tag = "change",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum ChangeKind {
+    RecordAdded,
+    RecordRemoved,
+    FormAdded,
+    FormRemoved,
+    EntryAdded {
+        value: Option<Value>,
+    },
+    EntryRemoved {
+        value: Option<Value>,
+    },
+    /// The text of an entry's value changed.
+    ValueChanged {
+        old: Option<Value>,
+        new: Option<Value>,
+    },
+    /// The form moved to another state, e.g. from "In-Work" to "Complete".
+    StateChanged {
+        old: String,
+        new: String,
+    },
+    /// A state is in the form's signature chain that was not in the older export, e.g. because the
+    /// form was signed again or by another user.
+    SignatureAdded {
+        state: State,
+    },
+    /// A state of the form's signature chain in the older export is no longer in it.
+    SignatureRemoved {
+        state: State,
+    },
+    /// The form was locked or unlocked.
+    LockChanged {
+        locked: bool,
+        user: Option<String>,
+        date_time_changed: Option<DateTime<FixedOffset>>,
+    },
+}
+
+impl SubjectNative {
+    /// Lists the changes from `self` to the `newer` export of the same study.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::path::Path;
+    ///
+    /// use prelude_xml_parser::native::diff::ChangeKind;
+    /// use prelude_xml_parser::parse_subject_native_file;
+    ///
+    /// let old = parse_subject_native_file(Path::new("tests/assets/subject_native.xml")).unwrap();
+    /// let mut new = old.clone();
+    /// new.patients.remove(0);
+    ///
+    /// let changes = old.diff(&new);
+    ///
+    /// assert_eq!(changes.len(), 1);
+    /// assert_eq!(changes[0].record_unique_id, old.patients[0].unique_id);
+    /// assert_eq!(changes[0].kind, ChangeKind::RecordRemoved);
+    /// ```
+    pub fn diff(&self, newer: &SubjectNative) -> Vec<Change> {
+        diff_records(&self.patients, &newer.patients, |p: &Patient| {
+            (p.unique_id.as_str(), p.forms.as_deref())
+        })
+    }
+}
+
+impl SiteNative {
+    /// Lists the changes from `self` to the `newer` export of the same study.
+    pub fn diff(&self, newer: &SiteNative) -> Vec<Change> {
+        diff_records(&self.sites, &newer.sites, |s: &Site| {
+            (s.unique_id.as_str(), s.forms.as_deref())
+        })
+    }
+}
+
+impl UserNative {
+    /// Lists the changes from `self` to the `newer` export.
+    pub fn diff(&self, newer: &UserNative) -> Vec<Change> {
+        diff_records(&self.users, &newer.users, |u: &User| {
+            (u.unique_id.as_str(), u.forms.as_deref())
+        })
+    }
+}
+
+/// Where a change happened. Turned into the location fields of a [`Change`].
+#[derive(Clone, Copy, Default)]
+struct Location<'a> {
+    record_unique_id: &'a str,
+    form: Option<&'a Form>,
+    category_name: Option<&'a str>,
+    field_name: Option<&'a str>,
+    entry_id: Option<&'a str>,
+}
+
+impl Location<'_> {
+    fn change(self, kind: ChangeKind) -> Change {
+        Change {
+            record_unique_id: self.record_unique_id.to_string(),
+            form_name: self.form.map(|f| f.name.clone()),
+            form_index: self.form.map(|f| f.form_index),
+            category_name: self.category_name.map(str::to_string),
+            field_name: self.field_name.map(str::to_string),
+            entry_id: self.entry_id.map(str::to_string),
+            kind,
+        }
+    }
+}
+
+fn diff_records<'a, R>(
+    old: &'a [R],
+    new: &'a [R],
+    key: impl Fn(&'a R) -> (&'a str, Option<&'a [Form]>),
+) -> Vec<Change> {
+    let old_by_id: HashMap<&str, Option<&[Form]>> = old.iter().map(&key).collect();
+    let new_ids: HashSet<&str> = new.iter().map(|r| key(r).0).collect();
+    let mut changes = Vec::new();
+
+    for (unique_id, new_forms) in new.iter().map(&key) {
+        let location = Location {
+            record_unique_id: unique_id,
+            ..Default::default()
+        };
+        match old_by_id.get(unique_id) {
+            Some(old_forms) => diff_forms(
+                location,
+                old_forms.unwrap_or_default(),
+                new_forms.unwrap_or_default(),
+                &mut changes,
+            ),
+            None => changes.push(location.change(ChangeKind::RecordAdded)),
+        }
+    }
+    for (unique_id, _) in old.iter().map(&key) {
+        if !new_ids.contains(unique_id) {
+            let location = Location {
+                record_unique_id: unique_id,
+                ..Default::default()
+            };
+            changes.push(location.change(ChangeKind::RecordRemoved));
+        }
+    }
+
+    changes
+}
+
+fn diff_forms<'a>(
+    record: Location<'a>,
+    old: &'a [Form],
+    new: &'a [Form],
+    changes: &mut Vec<Change>,
+) {
+    let form_key = |f: &'a Form| (f.name.as_str(), f.form_index);
+    let old_by_key: HashMap<(&str, usize), &Form> = old.iter().map(|f| (form_key(f), f)).collect();
+    let new_keys: HashSet<(&str, usize)> = new.iter().map(form_key).collect();
+
+    for new_form in new {
+        let location = Location {
+            form: Some(new_form),
+            ..record
+        };
+        let Some(old_form) = old_by_key.get(&form_key(new_form)) else {
+            changes.push(location.change(ChangeKind::FormAdded));
+            continue;
+        };
+
+        if old_form.form_state != new_form.form_state {
+            changes.push(location.change(ChangeKind::StateChanged {
+                old: old_form.form_state.clone(),
+                new: new_form.form_state.clone(),
+            }));
+        }
+        diff_states(location, old_form, new_form, changes);
+        if is_locked(old_form) != is_locked(new_form) {
+            let lock_state = new_form.lock_state.as_ref();
+            changes.push(
+                location.change(ChangeKind::LockChanged {
+                    locked: is_locked(new_form),
+                    user: lock_state
+                        .and_then(|l| l.user.clone())
+                        .or_else(|| new_form.user.clone()),
+                    date_time_changed: lock_state
+                        .and_then(|l| l.date_time_changed)
+                        .or(new_form.date_time_changed),
+                }),
+            );
+        }
+        diff_entries(location, old_form, new_form, changes);
+    }
+    for old_form in old {
+        if !new_keys.contains(&form_key(old_form)) {
+            let location = Location {
+                form: Some(old_form),
+                ..record
+            };
+            changes.push(location.change(ChangeKind::FormRemoved));
+        }
+    }
+}
+
+/// Compares the signature chains of a form. A re-signature shows up as an added state, and a change
+/// of signer as a removed and an added state.
+fn diff_states(form: Location, old: &Form, new: &Form, changes: &mut Vec<Change>) {
+    let mut unmatched: Vec<&State> = old.states.iter().flatten().collect();
+
+    for state in new.states.iter().flatten() {
+        match unmatched
+            .iter()
+            .position(|old| signature(old) == signature(state))
+        {
+            Some(i) => {
+                unmatched.remove(i);
+            }
+            None => changes.push(form.change(ChangeKind::SignatureAdded {
+                state: state.clone(),
+            })),
+        }
+    }
+    for state in unmatched {
+        changes.push(form.change(ChangeKind::SignatureRemoved {
+            state: state.clone(),
+        }));
+    }
+}
+
+/// What identifies a state in a signature chain.
+fn signature(state: &State) -> (&str, &str, Option<DateTime<FixedOffset>>) {
+    (&state.value, &state.signer_unique_id, state.date_signed)
+}
+
+/// A form is locked when its lock state says so, or when it has no lock state and its `locked`
+/// attribute is set.
+fn is_locked(form: &Form) -> bool {
+    form.lock_state
+        .as_ref()
+        .map_or(form.locked, |lock_state| lock_state.locked)
+}
+
+pub(crate) type EntryKey<'a> = (&'a str, &'a str, &'a str);
+
+/// The entries of a form keyed by category name, field name, and entry id, in document order.
+pub(crate) fn form_entries(form: &Form) -> Vec<(EntryKey<'_>, &Entry)> {
+    let mut entries = Vec::new();
+    for category in form.categories.iter().flatten() {
+        for field in category.fields.iter().flatten() {
+            for entry in field.entries.iter().flatten() {
+                entries.push((
+                    (
+                        category.name.as_str(),
+                        field.name.as_str(),
+                        entry.entry_id.as_str(),
+                    ),
+                    entry,
+                ));
+            }
+        }
+    }
+    entries
+}
+
+fn diff_entries<'a>(form: Location<'a>, old: &'a Form, new: &'a Form, changes: &mut Vec<Change>) {
+    let old_entries = form_entries(old);
+    let new_entries = form_entries(new);
+    let old_by_key: HashMap<EntryKey, &Entry> = old_entries.iter().copied().collect();
+    let new_keys: HashSet<EntryKey> = new_entries.iter().map(|(key, _)| *key).collect();
+    let location = |(category_name, field_name, entry_id): EntryKey<'a>| Location {
+        category_name: Some(category_name),
+        field_name: Some(field_name),
+        entry_id: Some(entry_id),
+        ..form
+    };
+
+    for (key, new_entry) in &new_entries {
+        match old_by_key.get(key) {
+            Some(old_entry) => {
+                let text = |entry: &Entry| entry.value.as_ref().map(|v| v.value.clone());
+                if text(old_entry) != text(new_entry) {
+                    changes.push(location(*key).change(ChangeKind::ValueChanged {
+                        old: old_entry.value.clone(),
+                        new: new_entry.value.clone(),
+                    }));
+                }
+            }
+            None => changes.push(location(*key).change(ChangeKind::EntryAdded {
+                value: new_entry.value.clone(),
+            })),
+        }
+    }
+    for (key, old_entry) in &old_entries {
+        if !new_keys.contains(key) {
+            changes.push(location(*key).change(ChangeKind::EntryRemoved {
+                value: old_entry.value.clone(),
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use insta::assert_json_snapshot;
+
+    use super::*;
+    use crate::native::common::{Entry, LockState};
+    use crate::native::test_helpers::{first_field, site_native, subject_native, user_native};
+
+    fn first_entry(native: &mut SubjectNative) -> &mut Entry {
+        first_field(native)
+            .entries
+            .as_mut()
+            .unwrap()
+            .first_mut()
+            .unwrap()
+    }
+
+    #[test]
+    fn identical_exports_have_no_changes() {
+        let subject = subject_native();
+        let site = site_native();
+        let user = user_native();
+
+        assert!(subject.diff(&subject).is_empty());
+        assert!(site.diff(&site).is_empty());
+        assert!(user.diff(&user).is_empty());
+    }
+
+    #[test]
+    fn diffs_subject_native() {
+        let old = subject_native();
+        let mut new = old.clone();
+
+        // A patient was removed and another one enrolled.
+        new.patients.remove(1);
+        let mut enrolled = old.patients[1].clone();
+        enrolled.unique_id = "999".to_string();
+        enrolled.forms = None;
+        new.patients.push(enrolled);
+
+        // A value was changed and a new entry added.
+        let entry = first_entry(&mut new);
+        entry.value.as_mut().unwrap().value = "Poodle".to_string();
+        let mut added = entry.clone();
+        added.entry_id = "2".to_string();
+        new.patients[0].forms.as_mut().unwrap()[0]
+            .categories
+            .as_mut()
+            .unwrap()[0]
+            .fields
+            .as_mut()
+            .unwrap()[0]
+            .entries
+            .as_mut()
+            .unwrap()
+            .push(added);
+
+        // The first form was completed and unlocked, the last one removed, and a visit added.
+        let forms = new.patients[0].forms.as_mut().unwrap();
+        forms[0].form_state = "Complete".to_string();
+        forms[0].lock_state = Some(LockState {
+            locked: false,
+            user: Some("Paul Sanders".to_string()),
+            user_unique_id: Some("1681162687395".to_string()),
+            date_time_changed: None,
+            extra: Default::default(),
+        });
+        let removed = forms.pop().unwrap();
+        let mut visit = removed.clone();
+        visit.form_index = 99;
+        forms.push(visit);
+
+        assert_json_snapshot!(old.diff(&new));
+    }
+
+    #[test]
+    fn reports_resignatures_and_signer_changes() {
+        let old = subject_native();
+        let mut new = old.clone();
+        let forms = new.patients[0].forms.as_mut().unwrap();
+        // Signed into the same state again, which does not change the form state
+        let states = forms[0].states.as_mut().unwrap();
+        let mut resigned = states[0].clone();
+        resigned.date_signed = resigned
+            .date_signed
+            .map(|date| date + chrono::Duration::days(1));
+        states.insert(0, resigned.clone());
+        // Signed by another user
+        let states = forms[1].states.as_mut().unwrap();
+        let previous = states[0].clone();
+        states[0].signer = "Other User".to_string();
+        states[0].signer_unique_id = "1681162687396".to_string();
+        let signed = states[0].clone();
+        let form_indexes = [forms[0].form_index, forms[1].form_index];
+
+        let changes = old.diff(&new);
+
+        assert_eq!(
+            changes.iter().map(|c| &c.kind).collect::<Vec<_>>(),
+            vec![
+                &ChangeKind::SignatureAdded { state: resigned },
+                &ChangeKind::SignatureAdded { state: signed },
+                &ChangeKind::SignatureRemoved { state: previous },
+            ]
+        );
+        assert_eq!(changes[0].form_index, Some(form_indexes[0]));
+        assert_eq!(changes[1].form_index, Some(form_indexes[1]));
+    }
+
+    #[test]
+    fn removed_entries_are_reported() {
+        let old = subject_native();
+        let mut new = old.clone();
+        let entry_id = first_entry(&mut new).entry_id.clone();
+        new.patients[0].forms.as_mut().unwrap()[0]
+            .categories
+            .as_mut()
+            .unwrap()[0]
+            .fields
+            .as_mut()
+            .unwrap()[0]
+            .entries = None;
+
+        let changes = old.diff(&new);
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].entry_id.as_deref(), Some(entry_id.as_str()));
+        assert!(matches!(
+            changes[0].kind,
+            ChangeKind::EntryRemoved { value: Some(_) }
+        ));
+    }
+}
@@ -0,0 +1,404 @@
+use chrono::{DateTime, FixedOffset};
+use serde::Serialize;
+
+#[cfg(feature = "arrow")]
+use std::sync::Arc;
+
+#[cfg(feature = "arrow")]
+use arrow_array::{ArrayRef, RecordBatch, StringArray, TimestampMicrosecondArray, UInt64Array};
+#[cfg(feature = "arrow")]
+use arrow_schema::{ArrowError, DataType, Field as ArrowField, Schema, SchemaRef, TimeUnit};
+
+use crate::native::{
+    common::Form,
+    site_native::{Site, SiteNative},
+    subject_native::{Patient, SubjectNative},
+    user_native::{User, UserNative},
+};
+
+/// One field entry together with the record, form, category, and field it belongs to.
+///
+/// This is the row type for flat exports such as DataFrames: one row per `Entry` of every
+/// `Field`. Fields without entries produce no rows. The strings are borrowed from the native the
+/// rows were built from.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FlatEntry<'a> {
+    /// `Patient.unique_id`, `Site.unique_id`, or `User.unique_id`.
+    pub record_unique_id: &'a str,
+    pub patient_id: Option<&'a str>,
+    pub site_name: Option<&'a str>,
+    pub site_unique_id: Option<&'a str>,
+    pub form_name: &'a str,
+    pub form_title: &'a str,
+    pub form_index: usize,
+    pub form_group: Option<&'a str>,
+    pub form_state: &'a str,
+    pub category_name: &'a str,
+    pub field_name: &'a str,
+    pub field_type: &'a str,
+    pub data_type: Option<&'a str>,
+    pub error_code: &'a str,
+    pub entry_id: &'a str,
+    pub value: Option<&'a str>,
+    pub value_by: Option<&'a str>,
+    pub value_by_unique_id: Option<&'a str>,
+    pub value_role: Option<&'a str>,
+    pub value_when: Option<DateTime<FixedOffset>>,
+    pub reason: Option<&'a str>,
+    pub reviewed_by: Option<&'a str>,
+    pub reviewed_by_unique_id: Option<&'a str>,
+    pub reviewed_by_when: Option<DateTime<FixedOffset>>,
+}
+
+/// The record level columns shared by every row of one record.
+#[derive(Clone, Copy)]
+struct RecordColumns<'a> {
+    record_unique_id: &'a str,
+    patient_id: Option<&'a str>,
+    site_name: Option<&'a str>,
+    site_unique_id: Option<&'a str>,
+}
+
+fn push_form_entries<'a>(
+    record: RecordColumns<'a>,
+    forms: Option<&'a Vec<Form>>,
+    rows: &mut Vec<FlatEntry<'a>>,
+) {
+    for form in forms.into_iter().flatten() {
+        for category in form.categories.iter().flatten() {
+            for field in category.fields.iter().flatten() {
+                for entry in field.entries.iter().flatten() {
+                    let value = entry.value.as_ref();
+                    rows.push(FlatEntry {
+                        record_unique_id: record.record_unique_id,
+                        patient_id: record.patient_id,
+                        site_name: record.site_name,
+                        site_unique_id: record.site_unique_id,
+                        form_name: &form.name,
+                        form_title: &form.form_title,
+                        form_index: form.form_index,
+                        form_group: form.form_group.as_deref(),
+                        form_state: &form.form_state,
+                        category_name: &category.name,
+                        field_name: &field.name,
+                        field_type: &field.field_type,
+                        data_type: field.data_type.as_deref(),
+                        error_code: &field.error_code,
+                        entry_id: &entry.entry_id,
+                        value: value.map(|v| v.value.as_str()),
+                        value_by: value.map(|v| v.by.as_str()),
+                        value_by_unique_id: value.and_then(|v| v.by_unique_id.as_deref()),
+                        value_role: value.map(|v| v.role.as_str()),
+                        value_when: value.and_then(|v| v.when),
+                        reason: entry.reason.as_ref().map(|r| r.value.as_str()),
+                        reviewed_by: entry.reviewed_by.as_deref(),
+                        reviewed_by_unique_id: entry.reviewed_by_unique_id.as_deref(),
+                        reviewed_by_when: entry.reviewed_by_when,
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl Patient {
+    /// Flattens the patient into one row per field entry.
+    pub fn flat_entries(&self) -> Vec<FlatEntry<'_>> {
+        let mut rows = Vec::new();
+        let record = RecordColumns {
+            record_unique_id: &self.unique_id,
+            patient_id: Some(&self.patient_id),
+            site_name: Some(&self.site_name),
+            site_unique_id: Some(&self.site_unique_id),
+        };
+        push_form_entries(record, self.forms.as_ref(), &mut rows);
+        rows
+    }
+}
+
+impl Site {
+    /// Flattens the site into one row per field entry.
+    pub fn flat_entries(&self) -> Vec<FlatEntry<'_>> {
+        let mut rows = Vec::new();
+        let record = RecordColumns {
+            record_unique_id: &self.unique_id,
+            patient_id: None,
+            site_name: Some(&self.name),
+            site_unique_id: Some(&self.unique_id),
+        };
+        push_form_entries(record, self.forms.as_ref(), &mut rows);
+        rows
+    }
+}
+
+impl User {
+    /// Flattens the user into one row per field entry.
+    pub fn flat_entries(&self) -> Vec<FlatEntry<'_>> {
+        let mut rows = Vec::new();
+        let record = RecordColumns {
+            record_unique_id: &self.unique_id,
+            patient_id: None,
+            site_name: None,
+            site_unique_id: None,
+        };
+        push_form_entries(record, self.forms.as_ref(), &mut rows);
+        rows
+    }
+}
+
+impl SubjectNative {
+    /// Flattens all patients into one row per field entry.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::path::Path;
+    ///
+    /// use prelude_xml_parser::parse_subject_native_file;
+    ///
+    /// let native = parse_subject_native_file(Path::new("tests/assets/subject_native.xml")).unwrap();
+    /// let rows = native.flat_entries();
+    ///
+    /// assert_eq!(rows[0].patient_id, Some("ABC-001"));
+    /// assert_eq!(rows[0].field_name, "breed");
+    /// assert_eq!(rows[0].value, Some("Labrador"));
+    /// ```
+    pub fn flat_entries(&self) -> Vec<FlatEntry<'_>> {
+        self.patients
+            .iter()
+            .flat_map(Patient::flat_entries)
+            .collect()
+    }
+}
+
+impl SiteNative {
+    /// Flattens all sites into one row per field entry.
+    pub fn flat_entries(&self) -> Vec<FlatEntry<'_>> {
+        self.sites.iter().flat_map(Site::flat_entries).collect()
+    }
+}
+
+impl UserNative {
+    /// Flattens all users into one row per field entry.
+    pub fn flat_entries(&self) -> Vec<FlatEntry<'_>> {
+        self.users.iter().flat_map(User::flat_entries).collect()
+    }
+}
+
+/// The Arrow schema of [`flat_entries_to_record_batch`].
+///
+/// Timestamps are stored as UTC microseconds.
+#[cfg(feature = "arrow")]
+pub fn flat_entries_schema() -> SchemaRef {
+    let utf8 = |name: &str, nullable: bool| ArrowField::new(name, DataType::Utf8, nullable);
+    let timestamp = |name: &str| {
+        ArrowField::new(
+            name,
+            DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            true,
+        )
+    };
+
+    Arc::new(Schema::new(vec![
+        utf8("record_unique_id", false),
+        utf8("patient_id", true),
+        utf8("site_name", true),
+        utf8("site_unique_id", true),
+        utf8("form_name", false),
+        utf8("form_title", false),
+        ArrowField::new("form_index", DataType::UInt64, false),
+        utf8("form_group", true),
+        utf8("form_state", false),
+        utf8("category_name", false),
+        utf8("field_name", false),
+        utf8("field_type", false),
+        utf8("data_type", true),
+        utf8("error_code", false),
+        utf8("entry_id", false),
+        utf8("value", true),
+        utf8("value_by", true),
+        utf8("value_by_unique_id", true),
+        utf8("value_role", true),
+        timestamp("value_when"),
+        utf8("reason", true),
+        utf8("reviewed_by", true),
+        utf8("reviewed_by_unique_id", true),
+        timestamp("reviewed_by_when"),
+    ]))
+}
+
+/// Builds an Arrow `RecordBatch` with one row per entry, following [`flat_entries_schema`].
+#[cfg(feature = "arrow")]
+pub fn flat_entries_to_record_batch(rows: &[FlatEntry<'_>]) -> Result<RecordBatch, ArrowError> {
+    fn strings<'a>(rows: &[FlatEntry<'a>], f: impl Fn(&FlatEntry<'a>) -> &'a str) -> ArrayRef {
+        Arc::new(StringArray::from_iter_values(rows.iter().map(f)))
+    }
+
+    fn optional_strings<'a>(
+        rows: &[FlatEntry<'a>],
+        f: impl Fn(&FlatEntry<'a>) -> Option<&'a str>,
+    ) -> ArrayRef {
+        Arc::new(rows.iter().map(f).collect::<StringArray>())
+    }
+
+    fn timestamps<'a>(
+        rows: &[FlatEntry<'a>],
+        f: impl Fn(&FlatEntry<'a>) -> Option<DateTime<FixedOffset>>,
+    ) -> ArrayRef {
+        Arc::new(
+            rows.iter()
+                .map(|row| f(row).map(|dt| dt.timestamp_micros()))
+                .collect::<TimestampMicrosecondArray>()
+                .with_timezone("UTC"),
+        )
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        strings(rows, |r| r.record_unique_id),
+        optional_strings(rows, |r| r.patient_id),
+        optional_strings(rows, |r| r.site_name),
+        optional_strings(rows, |r| r.site_unique_id),
+        strings(rows, |r| r.form_name),
+        strings(rows, |r| r.form_title),
+        Arc::new(UInt64Array::from_iter_values(
+            rows.iter().map(|r| r.form_index as u64),
+        )),
+        optional_strings(rows, |r| r.form_group),
+        strings(rows, |r| r.form_state),
+        strings(rows, |r| r.category_name),
+        strings(rows, |r| r.field_name),
+        strings(rows, |r| r.field_type),
+        optional_strings(rows, |r| r.data_type),
+        strings(rows, |r| r.error_code),
+        strings(rows, |r| r.entry_id),
+        optional_strings(rows, |r| r.value),
+        optional_strings(rows, |r| r.value_by),
+        optional_strings(rows, |r| r.value_by_unique_id),
+        optional_strings(rows, |r| r.value_role),
+        timestamps(rows, |r| r.value_when),
+        optional_strings(rows, |r| r.reason),
+        optional_strings(rows, |r| r.reviewed_by),
+        optional_strings(rows, |r| r.reviewed_by_unique_id),
+        timestamps(rows, |r| r.reviewed_by_when),
+    ];
+
+    RecordBatch::try_new(flat_entries_schema(), columns)
+}
+
+#[cfg(feature = "arrow")]
+impl SubjectNative {
+    /// Flattens the native into an Arrow `RecordBatch` with one row per field entry.
+    pub fn to_record_batch(&self) -> Result<RecordBatch, ArrowError> {
+        flat_entries_to_record_batch(&self.flat_entries())
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl SiteNative {
+    /// Flattens the native into an Arrow `RecordBatch` with one row per field entry.
+    pub fn to_record_batch(&self) -> Result<RecordBatch, ArrowError> {
+        flat_entries_to_record_batch(&self.flat_entries())
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl UserNative {
+    /// Flattens the native into an Arrow `RecordBatch` with one row per field entry.
+    pub fn to_record_batch(&self) -> Result<RecordBatch, ArrowError> {
+        flat_entries_to_record_batch(&self.flat_entries())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{parse_site_native_file, parse_subject_native_file, parse_user_native_file};
+
+    #[test]
+    fn flattens_subject_native() {
+        let native =
+            parse_subject_native_file(Path::new("tests/assets/subject_native.xml")).unwrap();
+        let rows = native.flat_entries();
+
+        let entry_count: usize = native
+            .patients
+            .iter()
+            .flat_map(|p| p.forms.iter().flatten())
+            .flat_map(|f| f.categories.iter().flatten())
+            .flat_map(|c| c.fields.iter().flatten())
+            .map(|f| f.entries.as_ref().map_or(0, Vec::len))
+            .sum();
+        assert_eq!(rows.len(), entry_count);
+
+        let hidden = rows
+            .iter()
+            .find(|r| r.field_name == "hide_study_exit")
+            .unwrap();
+        assert_eq!(hidden.patient_id, Some("ABC-001"));
+        assert_eq!(hidden.site_name, Some("Some Site"));
+        assert_eq!(hidden.form_name, "day.0.form.name.demographics");
+        assert_eq!(hidden.category_name, "Demographics");
+        assert_eq!(hidden.value, Some("1 form hidden"));
+        assert_eq!(hidden.value_role, Some("System"));
+        assert_eq!(hidden.value_by_unique_id, None);
+        assert_eq!(hidden.reason, Some("calculated value"));
+    }
+
+    #[test]
+    fn flattens_site_and_user_natives() {
+        let sites = parse_site_native_file(Path::new("tests/assets/site_native.xml")).unwrap();
+        let site_rows = sites.flat_entries();
+        assert!(!site_rows.is_empty());
+        assert!(site_rows.iter().all(|r| r.patient_id.is_none()));
+        assert_eq!(site_rows[0].site_name, Some(sites.sites[0].name.as_str()));
+
+        let users = parse_user_native_file(Path::new("tests/assets/user_native.xml")).unwrap();
+        let user_rows = users.flat_entries();
+        assert!(!user_rows.is_empty());
+        assert!(user_rows.iter().all(|r| r.site_name.is_none()));
+        assert_eq!(user_rows[0].record_unique_id, users.users[0].unique_id);
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn builds_record_batch() {
+        use arrow_array::Array;
+
+        use super::*;
+
+        let native =
+            parse_subject_native_file(Path::new("tests/assets/subject_native.xml")).unwrap();
+        let rows = native.flat_entries();
+        let batch = native.to_record_batch().unwrap();
+
+        assert_eq!(batch.num_rows(), rows.len());
+        assert_eq!(batch.schema(), flat_entries_schema());
+
+        let values = batch
+            .column_by_name("value")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(values.value(0), "Labrador");
+
+        let when = batch
+            .column_by_name("value_when")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<TimestampMicrosecondArray>()
+            .unwrap();
+        assert_eq!(
+            when.value(0),
+            rows[0].value_when.unwrap().timestamp_micros()
+        );
+        assert_eq!(
+            batch
+                .column_by_name("reviewed_by_when")
+                .unwrap()
+                .null_count(),
+            rows.len()
+        );
+    }
+}
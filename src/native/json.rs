@@ -0,0 +1,167 @@
+use schemars::{generate::SchemaSettings, JsonSchema, Schema};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::errors::Error;
+
+/// Version of the JSON written by `to_json` and read by `from_json`.
+///
+/// The version changes whenever a key is renamed or removed, or a value changes type. Adding a
+/// key does not change the version.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+struct VersionedRef<'a, T> {
+    /// Version of the JSON representation. Incompatible changes increase the version.
+    schema_version: u32,
+    #[serde(flatten)]
+    native: &'a T,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Versioned<T> {
+    schema_version: Option<u32>,
+    #[serde(flatten)]
+    native: T,
+}
+
+pub(crate) fn to_versioned_json<T: Serialize>(native: &T) -> serde_json::Result<String> {
+    serde_json::to_string(&VersionedRef {
+        schema_version: JSON_SCHEMA_VERSION,
+        native,
+    })
+}
+
+/// Reads JSON written by `to_versioned_json`. Documents without a `schemaVersion` are read as the
+/// current version.
+pub(crate) fn from_versioned_json<T: DeserializeOwned>(json: &str) -> Result<T, Error> {
+    let versioned: Versioned<T> = serde_json::from_str(json)?;
+    match versioned.schema_version {
+        None | Some(JSON_SCHEMA_VERSION) => Ok(versioned.native),
+        Some(found) => Err(Error::UnsupportedSchemaVersion {
+            found,
+            supported: JSON_SCHEMA_VERSION,
+        }),
+    }
+}
+
+pub(crate) fn versioned_json_schema<T: JsonSchema + 'static>() -> Schema {
+    let mut generator = SchemaSettings::draft2020_12().into_generator();
+    let native = T::json_schema(&mut generator);
+    let mut schema = generator.into_root_schema_for::<VersionedRef<'static, T>>();
+
+    schema.insert("title".to_string(), T::schema_name().into());
+    if let Some(description) = native.get("description") {
+        schema.insert("description".to_string(), description.clone());
+    }
+    if let Some(version) = schema.pointer_mut("/properties/schemaVersion") {
+        version["const"] = JSON_SCHEMA_VERSION.into();
+    }
+
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::*;
+    use crate::native::{
+        site_native::SiteNative, subject_native::SubjectNative, user_native::UserNative,
+    };
+    use crate::{parse_site_native_file, parse_subject_native_file, parse_user_native_file};
+
+    fn schemas() -> [(&'static str, Schema); 3] {
+        [
+            (
+                "schema/subject_native.schema.json",
+                SubjectNative::json_schema(),
+            ),
+            ("schema/site_native.schema.json", SiteNative::json_schema()),
+            ("schema/user_native.schema.json", UserNative::json_schema()),
+        ]
+    }
+
+    #[test]
+    fn schema_documents_are_up_to_date() {
+        for (path, schema) in schemas() {
+            let generated = serde_json::to_string_pretty(&schema).unwrap() + "\n";
+            if std::env::var_os("UPDATE_SCHEMAS").is_some() {
+                fs::write(path, &generated).unwrap();
+            }
+
+            assert_eq!(
+                fs::read_to_string(path).unwrap(),
+                generated,
+                "{path} is out of date, regenerate it with `just schemas`"
+            );
+        }
+    }
+
+    #[test]
+    fn to_json_matches_schema() {
+        let subject =
+            parse_subject_native_file(Path::new("tests/assets/subject_native.xml")).unwrap();
+        let site = parse_site_native_file(Path::new("tests/assets/site_native.xml")).unwrap();
+        let user = parse_user_native_file(Path::new("tests/assets/user_native.xml")).unwrap();
+        let documents = [
+            subject.to_json().unwrap(),
+            site.to_json().unwrap(),
+            user.to_json().unwrap(),
+        ];
+
+        for ((path, schema), json) in schemas().into_iter().zip(documents) {
+            let instance: serde_json::Value = serde_json::from_str(&json).unwrap();
+            assert_eq!(instance["schemaVersion"], JSON_SCHEMA_VERSION);
+
+            let validator = jsonschema::validator_for(&schema.to_value()).unwrap();
+            let errors: Vec<String> = validator
+                .iter_errors(&instance)
+                .map(|e| format!("{} at {}", e, e.instance_path))
+                .collect();
+            assert!(errors.is_empty(), "{path}: {errors:#?}");
+        }
+    }
+
+    #[test]
+    fn from_json_round_trips() {
+        let subject =
+            parse_subject_native_file(Path::new("tests/assets/subject_native.xml")).unwrap();
+        let site = parse_site_native_file(Path::new("tests/assets/site_native.xml")).unwrap();
+        let user = parse_user_native_file(Path::new("tests/assets/user_native.xml")).unwrap();
+
+        assert_eq!(
+            SubjectNative::from_json(&subject.to_json().unwrap()).unwrap(),
+            subject
+        );
+        assert_eq!(
+            SiteNative::from_json(&site.to_json().unwrap()).unwrap(),
+            site
+        );
+        assert_eq!(
+            UserNative::from_json(&user.to_json().unwrap()).unwrap(),
+            user
+        );
+    }
+
+    #[test]
+    fn from_json_reads_documents_without_version() {
+        let native = SubjectNative::from_json(r#"{"patients": []}"#).unwrap();
+
+        assert!(native.patients.is_empty());
+    }
+
+    #[test]
+    fn from_json_rejects_other_versions() {
+        let result = SubjectNative::from_json(r#"{"schemaVersion": 2, "patients": []}"#);
+
+        assert!(matches!(
+            result,
+            Err(Error::UnsupportedSchemaVersion {
+                found: 2,
+                supported: JSON_SCHEMA_VERSION
+            })
+        ));
+    }
+}
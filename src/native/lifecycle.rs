@@ -0,0 +1,472 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, FixedOffset};
+use serde::Serialize;
+
+use crate::native::{
+    common::{ExportInfo, Form, SYSTEM_UNIQUE_ID},
+    site_native::SiteNative,
+    subject_native::SubjectNative,
+    user_native::UserNative,
+};
+
+/// What the lifecycle of a form is checked against.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LifecycleOptions {
+    /// States every form must have been signed into by a user, e.g. "form.state.complete".
+    /// Empty by default.
+    pub required_signatures: Vec<String>,
+
+    /// Forms currently in these states do not need to be signed. Defaults to
+    /// "form.state.new" and "form.state.not.applicable".
+    pub exempt_states: Vec<String>,
+
+    /// When the current state of each form ends. Defaults to the export date.
+    pub as_of: Option<DateTime<FixedOffset>>,
+}
+
+impl Default for LifecycleOptions {
+    fn default() -> Self {
+        Self {
+            required_signatures: Vec::new(),
+            exempt_states: vec![
+                "form.state.new".to_string(),
+                "form.state.not.applicable".to_string(),
+            ],
+            as_of: None,
+        }
+    }
+}
+
+/// The workflow history of one form.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormLifecycle {
+    /// `Patient.unique_id`, `Site.unique_id`, or `User.unique_id`.
+    pub record_unique_id: String,
+    /// The site of the patient, or the site itself. `None` for users.
+    pub site_unique_id: Option<String>,
+    pub form_name: String,
+    pub form_index: usize,
+    pub form_title: String,
+    /// The value of the latest state, or `Form.form_state` when the form has no states.
+    pub current_state: String,
+    /// The states the form went through, oldest first.
+    pub periods: Vec<StatePeriod>,
+    /// At least one state was signed by a user rather than set by the system.
+    pub signed: bool,
+    /// Whether the form needs a signature: it is not in one of the exempt states.
+    pub requires_signature: bool,
+    /// Required states the form was not signed into by a user, only set when
+    /// `requires_signature` is.
+    pub missing_signatures: Vec<String>,
+    pub locked: bool,
+    /// When the form was last locked or unlocked.
+    pub lock_changed: Option<DateTime<FixedOffset>>,
+    /// The form was locked or unlocked after its latest user signature.
+    pub lock_changed_after_signing: bool,
+}
+
+/// The time a form spent in one state.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatePeriod {
+    pub state: String,
+    pub signer: String,
+    pub signer_unique_id: String,
+    pub entered: Option<DateTime<FixedOffset>>,
+    /// Seconds until the next state, or until `as_of` for the current state. `None` when either
+    /// end is unknown.
+    pub seconds: Option<i64>,
+}
+
+/// Lifecycle counts over a group of forms, e.g. those of one patient or site.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LifecycleSummary {
+    pub forms: usize,
+    /// The number of forms in each current state.
+    pub current_states: BTreeMap<String, usize>,
+    /// The total seconds forms spent in each state.
+    pub seconds_in_state: BTreeMap<String, i64>,
+    /// Forms that need a signature but were never signed by a user.
+    pub unsigned: usize,
+    /// Forms with at least one missing required signature.
+    pub missing_signatures: usize,
+    pub locked: usize,
+    pub lock_changed_after_signing: usize,
+}
+
+impl LifecycleSummary {
+    /// Summarizes `lifecycles` per `record_unique_id`.
+    pub fn by_record(lifecycles: &[FormLifecycle]) -> BTreeMap<String, LifecycleSummary> {
+        summarize(lifecycles, |l| Some(&l.record_unique_id))
+    }
+
+    /// Summarizes `lifecycles` per `site_unique_id`, skipping forms without a site.
+    pub fn by_site(lifecycles: &[FormLifecycle]) -> BTreeMap<String, LifecycleSummary> {
+        summarize(lifecycles, |l| l.site_unique_id.as_ref())
+    }
+
+    fn add(&mut self, lifecycle: &FormLifecycle) {
+        self.forms += 1;
+        *self
+            .current_states
+            .entry(lifecycle.current_state.clone())
+            .or_default() += 1;
+        for period in &lifecycle.periods {
+            if let Some(seconds) = period.seconds {
+                *self
+                    .seconds_in_state
+                    .entry(period.state.clone())
+                    .or_default() += seconds;
+            }
+        }
+        self.unsigned += usize::from(lifecycle.requires_signature && !lifecycle.signed);
+        self.missing_signatures += usize::from(!lifecycle.missing_signatures.is_empty());
+        self.locked += usize::from(lifecycle.locked);
+        self.lock_changed_after_signing += usize::from(lifecycle.lock_changed_after_signing);
+    }
+}
+
+fn summarize<'a>(
+    lifecycles: &'a [FormLifecycle],
+    key: impl Fn(&'a FormLifecycle) -> Option<&'a String>,
+) -> BTreeMap<String, LifecycleSummary> {
+    let mut summaries: BTreeMap<String, LifecycleSummary> = BTreeMap::new();
+    for lifecycle in lifecycles {
+        if let Some(key) = key(lifecycle) {
+            summaries.entry(key.clone()).or_default().add(lifecycle);
+        }
+    }
+    summaries
+}
+
+impl SubjectNative {
+    /// Works out the workflow history of every form of every patient.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::path::Path;
+    ///
+    /// use prelude_xml_parser::native::lifecycle::{LifecycleOptions, LifecycleSummary};
+    /// use prelude_xml_parser::parse_subject_native_file;
+    ///
+    /// let native = parse_subject_native_file(Path::new("tests/assets/subject_native.xml")).unwrap();
+    /// let lifecycles = native.form_lifecycles(&LifecycleOptions::default());
+    /// let by_site = LifecycleSummary::by_site(&lifecycles);
+    ///
+    /// assert_eq!(by_site["1681574834910"].forms, lifecycles.len());
+    /// ```
+    pub fn form_lifecycles(&self, options: &LifecycleOptions) -> Vec<FormLifecycle> {
+        let as_of = as_of(options, &self.export);
+        self.patients
+            .iter()
+            .flat_map(|patient| {
+                form_lifecycles(
+                    &patient.unique_id,
+                    Some(&patient.site_unique_id),
+                    &patient.forms,
+                    options,
+                    as_of,
+                )
+            })
+            .collect()
+    }
+}
+
+impl SiteNative {
+    /// Works out the workflow history of every form of every site.
+    ///
+    /// See [`SubjectNative::form_lifecycles`].
+    pub fn form_lifecycles(&self, options: &LifecycleOptions) -> Vec<FormLifecycle> {
+        let as_of = as_of(options, &self.export);
+        self.sites
+            .iter()
+            .flat_map(|site| {
+                form_lifecycles(
+                    &site.unique_id,
+                    Some(&site.unique_id),
+                    &site.forms,
+                    options,
+                    as_of,
+                )
+            })
+            .collect()
+    }
+}
+
+impl UserNative {
+    /// Works out the workflow history of every form of every user.
+    ///
+    /// See [`SubjectNative::form_lifecycles`].
+    pub fn form_lifecycles(&self, options: &LifecycleOptions) -> Vec<FormLifecycle> {
+        let as_of = as_of(options, &self.export);
+        self.users
+            .iter()
+            .flat_map(|user| form_lifecycles(&user.unique_id, None, &user.forms, options, as_of))
+            .collect()
+    }
+}
+
+fn as_of(options: &LifecycleOptions, export: &Option<ExportInfo>) -> Option<DateTime<FixedOffset>> {
+    options
+        .as_of
+        .or_else(|| export.as_ref().and_then(|e| e.date))
+}
+
+fn form_lifecycles(
+    record_unique_id: &str,
+    site_unique_id: Option<&String>,
+    forms: &Option<Vec<Form>>,
+    options: &LifecycleOptions,
+    as_of: Option<DateTime<FixedOffset>>,
+) -> Vec<FormLifecycle> {
+    forms
+        .iter()
+        .flatten()
+        .map(|form| lifecycle(record_unique_id, site_unique_id, form, options, as_of))
+        .collect()
+}
+
+fn lifecycle(
+    record_unique_id: &str,
+    site_unique_id: Option<&String>,
+    form: &Form,
+    options: &LifecycleOptions,
+    as_of: Option<DateTime<FixedOffset>>,
+) -> FormLifecycle {
+    let mut states: Vec<_> = form.states.iter().flatten().collect();
+    // Only the dated states are sorted, among the positions they were exported at, so states
+    // without a date keep their place in the export order
+    let mut dated: Vec<_> = states
+        .iter()
+        .copied()
+        .filter(|state| state.date_signed.is_some())
+        .collect();
+    dated.sort_by_key(|state| state.date_signed);
+    let mut dated = dated.into_iter();
+    for state in states
+        .iter_mut()
+        .filter(|state| state.date_signed.is_some())
+    {
+        *state = dated.next().unwrap();
+    }
+
+    let periods: Vec<StatePeriod> = states
+        .iter()
+        .enumerate()
+        .map(|(i, state)| {
+            let left = match states.get(i + 1) {
+                Some(next) => next.date_signed,
+                None => as_of,
+            };
+            StatePeriod {
+                state: state.value.clone(),
+                signer: state.signer.clone(),
+                signer_unique_id: state.signer_unique_id.clone(),
+                entered: state.date_signed,
+                seconds: state
+                    .date_signed
+                    .zip(left)
+                    .map(|(entered, left)| (left - entered).num_seconds()),
+            }
+        })
+        .collect();
+
+    let signatures: Vec<&StatePeriod> = periods
+        .iter()
+        .filter(|p| {
+            !p.signer_unique_id.is_empty()
+                && !p.signer_unique_id.eq_ignore_ascii_case(SYSTEM_UNIQUE_ID)
+        })
+        .collect();
+    let current_state = periods
+        .last()
+        .map(|p| p.state.clone())
+        .unwrap_or_else(|| form.form_state.clone());
+    let requires_signature = !options.exempt_states.contains(&current_state);
+    let missing_signatures = if requires_signature {
+        options
+            .required_signatures
+            .iter()
+            .filter(|required| !signatures.iter().any(|s| &&s.state == required))
+            .cloned()
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let (locked, lock_changed) = match &form.lock_state {
+        Some(lock_state) => (lock_state.locked, lock_state.date_time_changed),
+        None => (form.locked, form.date_time_changed),
+    };
+    let last_signed = signatures.iter().filter_map(|s| s.entered).max();
+    let lock_changed_after_signing = matches!(
+        (lock_changed, last_signed),
+        (Some(changed), Some(signed)) if changed > signed
+    );
+
+    FormLifecycle {
+        record_unique_id: record_unique_id.to_string(),
+        site_unique_id: site_unique_id.cloned(),
+        form_name: form.name.clone(),
+        form_index: form.form_index,
+        form_title: form.form_title.clone(),
+        current_state,
+        signed: !signatures.is_empty(),
+        requires_signature,
+        missing_signatures,
+        locked,
+        lock_changed,
+        lock_changed_after_signing,
+        periods,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::native::common::State;
+    use crate::native::test_helpers::subject_native;
+
+    #[test]
+    fn follows_the_signature_chain() {
+        let mut native = subject_native();
+        let form = &mut native.patients[0].forms.as_mut().unwrap()[0];
+        let in_work = form.states.as_ref().unwrap()[0].clone();
+        let entered = in_work.date_signed.unwrap();
+        // Exported newest first, the lifecycle is sorted by date
+        form.states = Some(vec![
+            State {
+                value: "form.state.complete".to_string(),
+                date_signed: Some(entered + Duration::hours(2)),
+                ..in_work.clone()
+            },
+            in_work,
+        ]);
+        let options = LifecycleOptions {
+            required_signatures: vec![
+                "form.state.complete".to_string(),
+                "form.state.verified".to_string(),
+            ],
+            ..Default::default()
+        };
+
+        let lifecycles = native.form_lifecycles(&options);
+        let lifecycle = &lifecycles[0];
+
+        assert_eq!(lifecycle.record_unique_id, "1681574905819");
+        assert_eq!(lifecycle.site_unique_id.as_deref(), Some("1681574834910"));
+        assert_eq!(lifecycle.current_state, "form.state.complete");
+        assert_eq!(
+            lifecycle
+                .periods
+                .iter()
+                .map(|p| (p.state.as_str(), p.seconds))
+                .collect::<Vec<_>>(),
+            vec![
+                ("form.state.in.work", Some(2 * 3600)),
+                (
+                    "form.state.complete",
+                    Some(
+                        (native.export.unwrap().date.unwrap() - entered - Duration::hours(2))
+                            .num_seconds()
+                    )
+                ),
+            ]
+        );
+        assert!(lifecycle.signed);
+        assert_eq!(lifecycle.missing_signatures, vec!["form.state.verified"]);
+        // Locked in 2024, long after it was signed
+        assert!(lifecycle.locked);
+        assert!(lifecycle.lock_changed_after_signing);
+    }
+
+    #[test]
+    fn keeps_undated_states_in_place() {
+        let mut native = subject_native();
+        let form = &mut native.patients[0].forms.as_mut().unwrap()[0];
+        let in_work = form.states.as_ref().unwrap()[0].clone();
+        let entered = in_work.date_signed.unwrap();
+        form.states = Some(vec![
+            State {
+                value: "form.state.complete".to_string(),
+                date_signed: Some(entered + Duration::hours(2)),
+                ..in_work.clone()
+            },
+            State {
+                value: "form.state.verified".to_string(),
+                date_signed: None,
+                ..in_work.clone()
+            },
+            in_work,
+        ]);
+
+        let lifecycles = native.form_lifecycles(&LifecycleOptions::default());
+
+        assert_eq!(
+            lifecycles[0]
+                .periods
+                .iter()
+                .map(|p| p.state.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "form.state.in.work",
+                "form.state.verified",
+                "form.state.complete"
+            ]
+        );
+        assert_eq!(lifecycles[0].current_state, "form.state.complete");
+    }
+
+    #[test]
+    fn exempts_new_and_not_applicable_forms() {
+        let native = subject_native();
+        let options = LifecycleOptions {
+            required_signatures: vec!["form.state.complete".to_string()],
+            ..Default::default()
+        };
+
+        let lifecycles = native.form_lifecycles(&options);
+
+        for lifecycle in &lifecycles[1..] {
+            assert!(!lifecycle.signed);
+            assert!(!lifecycle.requires_signature);
+            assert!(lifecycle.missing_signatures.is_empty());
+        }
+    }
+
+    #[test]
+    fn summarizes_by_record_and_site() {
+        let native = subject_native();
+        let lifecycles = native.form_lifecycles(&LifecycleOptions {
+            required_signatures: vec!["form.state.complete".to_string()],
+            ..Default::default()
+        });
+
+        let by_record = LifecycleSummary::by_record(&lifecycles);
+        let by_site = LifecycleSummary::by_site(&lifecycles);
+
+        assert_eq!(by_record.len(), 2);
+        let first = &by_record["1681574905819"];
+        assert_eq!(first.forms, 3);
+        assert_eq!(first.current_states["form.state.in.work"], 1);
+        assert_eq!(first.current_states["form.state.new"], 1);
+        assert_eq!(first.unsigned, 0);
+        assert_eq!(first.missing_signatures, 1);
+        assert_eq!(first.locked, 1);
+        assert_eq!(by_site.len(), 1);
+        assert_eq!(by_site["1681574834910"].forms, lifecycles.len());
+        assert_eq!(
+            by_site["1681574834910"].seconds_in_state["form.state.not.applicable"],
+            by_record
+                .values()
+                .map(|s| s.seconds_in_state["form.state.not.applicable"])
+                .sum::<i64>()
+        );
+    }
+}
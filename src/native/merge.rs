@@ -0,0 +1,399 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::native::{
+    common::{Entry, ExportInfo, Form},
+    diff::{form_entries, EntryKey},
+    site_native::{Site, SiteNative},
+    subject_native::{Patient, SubjectNative},
+    user_native::{User, UserNative},
+};
+
+/// The result of merging several exports of the same study.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Merged<T> {
+    pub native: T,
+    /// Entries that differ between the versions of a form that were merged.
+    pub conflicts: Vec<Conflict>,
+}
+
+/// Two exports hold different versions of the same entry, or only the dropped form holds it.
+///
+/// The form with the newest `last_modified` is kept, so `kept` is the entry in the merged native
+/// and `discarded` the one that was dropped. `kept` is `None` when the kept form has no such entry.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Conflict {
+    /// `Patient.unique_id`, `Site.unique_id`, or `User.unique_id`.
+    pub record_unique_id: String,
+    pub form_name: String,
+    pub form_index: usize,
+    pub category_name: String,
+    pub field_name: String,
+    pub entry_id: String,
+    pub kept: Option<Entry>,
+    pub discarded: Option<Entry>,
+}
+
+impl SubjectNative {
+    /// Combines exports of the same study, e.g. exports split by site or date range, into one.
+    ///
+    /// Exports are applied from the oldest to the newest `export.date`, exports without a date
+    /// first and in the order given. Patients are de-duplicated by `unique_id`, taking their
+    /// attributes from the newest export. Forms are de-duplicated by name and `form_index`,
+    /// keeping the one with the newest `last_modified`, or the one from the newest export when
+    /// they were modified at the same time. The `number_of_forms` of a patient found in several
+    /// exports is set to the number of its merged forms. The export metadata is taken from the
+    /// newest export, with `number_subjects_processed` set to the number of merged patients.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::path::Path;
+    ///
+    /// use prelude_xml_parser::native::subject_native::SubjectNative;
+    /// use prelude_xml_parser::parse_subject_native_file;
+    ///
+    /// let native = parse_subject_native_file(Path::new("tests/assets/subject_native.xml")).unwrap();
+    /// let mut first = native.clone();
+    /// first.patients.truncate(1);
+    /// let mut second = native.clone();
+    /// second.patients.remove(0);
+    ///
+    /// let merged = SubjectNative::merge([first, second]);
+    ///
+    /// assert_eq!(merged.native.patients, native.patients);
+    /// assert!(merged.conflicts.is_empty());
+    /// ```
+    pub fn merge(natives: impl IntoIterator<Item = SubjectNative>) -> Merged<SubjectNative> {
+        let exports = natives.into_iter().map(|n| (n.export, n.patients));
+        let (export, patients, conflicts) = merge_exports(
+            exports,
+            |p: &Patient| &p.unique_id,
+            |p: &mut Patient| &mut p.forms,
+            |p: &mut Patient| &mut p.number_of_forms,
+        );
+
+        Merged {
+            native: SubjectNative { patients, export },
+            conflicts,
+        }
+    }
+}
+
+impl SiteNative {
+    /// Combines exports of the same study into one, de-duplicating sites by `unique_id`.
+    ///
+    /// See [`SubjectNative::merge`].
+    pub fn merge(natives: impl IntoIterator<Item = SiteNative>) -> Merged<SiteNative> {
+        let exports = natives.into_iter().map(|n| (n.export, n.sites));
+        let (export, sites, conflicts) = merge_exports(
+            exports,
+            |s: &Site| &s.unique_id,
+            |s: &mut Site| &mut s.forms,
+            |s: &mut Site| &mut s.number_of_forms,
+        );
+
+        Merged {
+            native: SiteNative { sites, export },
+            conflicts,
+        }
+    }
+}
+
+impl UserNative {
+    /// Combines exports into one, de-duplicating users by `unique_id`.
+    ///
+    /// See [`SubjectNative::merge`].
+    pub fn merge(natives: impl IntoIterator<Item = UserNative>) -> Merged<UserNative> {
+        let exports = natives.into_iter().map(|n| (n.export, n.users));
+        let (export, users, conflicts) = merge_exports(
+            exports,
+            |u: &User| &u.unique_id,
+            |u: &mut User| &mut u.forms,
+            |u: &mut User| &mut u.number_of_forms,
+        );
+
+        Merged {
+            native: UserNative { users, export },
+            conflicts,
+        }
+    }
+}
+
+fn merge_exports<R>(
+    exports: impl Iterator<Item = (Option<ExportInfo>, Vec<R>)>,
+    unique_id: impl Fn(&R) -> &String,
+    forms: impl Fn(&mut R) -> &mut Option<Vec<Form>>,
+    number_of_forms: impl Fn(&mut R) -> &mut usize,
+) -> (Option<ExportInfo>, Vec<R>, Vec<Conflict>) {
+    let mut exports: Vec<_> = exports.collect();
+    // Stable, so exports with the same date, or without one, keep the order they were given in
+    exports.sort_by_key(|(export, _)| export.as_ref().and_then(|e| e.date));
+
+    let mut records: Vec<R> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    let mut conflicts = Vec::new();
+    let mut newest_export = None;
+
+    for (export, incoming) in exports {
+        newest_export = export.or(newest_export);
+        for mut record in incoming {
+            let id = unique_id(&record).clone();
+            let Some(&position) = positions.get(&id) else {
+                positions.insert(id, records.len());
+                records.push(record);
+                continue;
+            };
+
+            let existing = forms(&mut records[position]).take();
+            let newer = forms(&mut record).take();
+            *forms(&mut record) = match (existing, newer) {
+                (Some(existing), Some(newer)) => {
+                    Some(merge_forms(&id, existing, newer, &mut conflicts))
+                }
+                (existing, newer) => newer.or(existing),
+            };
+            *number_of_forms(&mut record) = forms(&mut record).as_ref().map_or(0, Vec::len);
+            records[position] = record;
+        }
+    }
+
+    let export = newest_export.map(|export| ExportInfo {
+        number_subjects_processed: records.len(),
+        ..export
+    });
+
+    (export, records, conflicts)
+}
+
+/// Merges the forms of one record from an older and a newer export, keeping the order of the
+/// older export and appending forms only found in the newer one.
+fn merge_forms(
+    record_unique_id: &str,
+    existing: Vec<Form>,
+    newer: Vec<Form>,
+    conflicts: &mut Vec<Conflict>,
+) -> Vec<Form> {
+    let mut merged = existing;
+    let positions: HashMap<(String, usize), usize> = merged
+        .iter()
+        .enumerate()
+        .map(|(i, form)| ((form.name.clone(), form.form_index), i))
+        .collect();
+
+    for form in newer {
+        let Some(&position) = positions.get(&(form.name.clone(), form.form_index)) else {
+            merged.push(form);
+            continue;
+        };
+
+        let existing = &mut merged[position];
+        if form.last_modified >= existing.last_modified {
+            push_conflicts(record_unique_id, &form, existing, conflicts);
+            *existing = form;
+        } else {
+            push_conflicts(record_unique_id, existing, &form, conflicts);
+        }
+    }
+
+    merged
+}
+
+fn push_conflicts(
+    record_unique_id: &str,
+    kept: &Form,
+    discarded: &Form,
+    conflicts: &mut Vec<Conflict>,
+) {
+    let kept_entries: HashMap<EntryKey, &Entry> = form_entries(kept).into_iter().collect();
+    let discarded_entries: HashMap<EntryKey, &Entry> =
+        form_entries(discarded).into_iter().collect();
+    let mut push = |(category_name, field_name, entry_id): EntryKey,
+                    kept_entry: Option<&Entry>,
+                    discarded_entry: Option<&Entry>| {
+        conflicts.push(Conflict {
+            record_unique_id: record_unique_id.to_string(),
+            form_name: kept.name.clone(),
+            form_index: kept.form_index,
+            category_name: category_name.to_string(),
+            field_name: field_name.to_string(),
+            entry_id: entry_id.to_string(),
+            kept: kept_entry.cloned(),
+            discarded: discarded_entry.cloned(),
+        });
+    };
+
+    for (key, kept_entry) in form_entries(kept) {
+        match discarded_entries.get(&key) {
+            Some(&discarded_entry) if discarded_entry != kept_entry => {
+                push(key, Some(kept_entry), Some(discarded_entry));
+            }
+            _ => {}
+        }
+    }
+    // The rest are only in the discarded form, in export order
+    for (key, discarded_entry) in form_entries(discarded) {
+        if !kept_entries.contains_key(&key) {
+            push(key, None, Some(discarded_entry));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::native::{
+        common::Value,
+        test_helpers::{first_field, subject_native},
+    };
+
+    fn first_value(native: &mut SubjectNative) -> &mut Value {
+        first_field(native).entries.as_mut().unwrap()[0]
+            .value
+            .as_mut()
+            .unwrap()
+    }
+
+    fn entry_value(entry: &Option<Entry>) -> Option<&str> {
+        entry.as_ref()?.value.as_ref().map(|v| v.value.as_str())
+    }
+
+    #[test]
+    fn merges_exports_split_by_patient() {
+        let native = subject_native();
+        let mut first = native.clone();
+        first.patients.truncate(1);
+        let mut second = native.clone();
+        second.patients.remove(0);
+
+        let merged = SubjectNative::merge([first, second]);
+
+        assert_eq!(merged.native.patients, native.patients);
+        assert!(merged.conflicts.is_empty());
+    }
+
+    #[test]
+    fn keeps_the_newest_form_and_reports_conflicts() {
+        let old = subject_native();
+        let mut new = old.clone();
+        new.export.as_mut().unwrap().date = old
+            .export
+            .as_ref()
+            .unwrap()
+            .date
+            .map(|d| d + Duration::days(7));
+        first_value(&mut new).value = "Poodle".to_string();
+        let form = &mut new.patients[0].forms.as_mut().unwrap()[0];
+        form.last_modified = form.last_modified.map(|d| d + Duration::days(7));
+
+        // The newer export is given first, the exports are applied by date
+        let merged = SubjectNative::merge([new.clone(), old.clone()]);
+
+        let mut patients = new.patients.clone();
+        for patient in &mut patients {
+            patient.number_of_forms = patient.forms.as_ref().map_or(0, Vec::len);
+        }
+        assert_eq!(merged.native.patients, patients);
+        assert_eq!(
+            merged.native.export,
+            new.export.map(|export| ExportInfo {
+                number_subjects_processed: 2,
+                ..export
+            })
+        );
+        assert_eq!(merged.conflicts.len(), 1);
+        let conflict = &merged.conflicts[0];
+        assert_eq!(conflict.field_name, "breed");
+        assert_eq!(entry_value(&conflict.kept), Some("Poodle"));
+        assert_eq!(entry_value(&conflict.discarded), Some("Labrador"));
+    }
+
+    #[test]
+    fn keeps_a_form_modified_after_the_newer_export() {
+        let old = subject_native();
+        let mut stale = old.clone();
+        stale.export.as_mut().unwrap().date = old
+            .export
+            .as_ref()
+            .unwrap()
+            .date
+            .map(|d| d + Duration::days(7));
+        first_value(&mut stale).value = "Poodle".to_string();
+        let form = &mut stale.patients[0].forms.as_mut().unwrap()[0];
+        form.last_modified = form.last_modified.map(|d| d - Duration::days(7));
+
+        let merged = SubjectNative::merge([old.clone(), stale]);
+
+        assert_eq!(
+            merged.native.patients[0].forms.as_ref().unwrap()[0],
+            old.patients[0].forms.as_ref().unwrap()[0]
+        );
+        assert_eq!(entry_value(&merged.conflicts[0].kept), Some("Labrador"));
+    }
+
+    #[test]
+    fn counts_the_forms_of_merged_records() {
+        let native = subject_native();
+        let forms = native.patients[0].forms.clone().unwrap();
+        assert!(forms.len() > 1);
+        let mut first = native.clone();
+        first.patients[0].forms = Some(forms[..1].to_vec());
+        first.patients[0].number_of_forms = 1;
+        let mut second = native.clone();
+        second.patients[0].forms = Some(forms[1..].to_vec());
+        second.patients[0].number_of_forms = forms.len() - 1;
+
+        let merged = SubjectNative::merge([first, second]);
+
+        let patient = &merged.native.patients[0];
+        assert_eq!(patient.forms.as_ref(), Some(&forms));
+        assert_eq!(patient.number_of_forms, forms.len());
+        assert!(merged.conflicts.is_empty());
+    }
+
+    #[test]
+    fn reports_entries_only_in_the_discarded_form() {
+        let old = subject_native();
+        let mut new = old.clone();
+        first_field(&mut new).entries = None;
+        let form = &mut new.patients[0].forms.as_mut().unwrap()[0];
+        form.last_modified = form.last_modified.map(|d| d + Duration::days(7));
+
+        let merged = SubjectNative::merge([old.clone(), new]);
+
+        let dropped = first_field(&mut old.clone()).entries.clone().unwrap();
+        assert_eq!(merged.conflicts.len(), dropped.len());
+        for (conflict, entry) in merged.conflicts.iter().zip(&dropped) {
+            assert_eq!(conflict.field_name, "breed");
+            assert_eq!(conflict.entry_id, entry.entry_id);
+            assert_eq!(conflict.kept, None);
+            assert_eq!(conflict.discarded.as_ref(), Some(entry));
+        }
+    }
+
+    #[test]
+    fn reports_entries_that_differ_besides_their_value() {
+        let old = subject_native();
+        let mut new = old.clone();
+        first_field(&mut new).entries.as_mut().unwrap()[0].reviewed_by = Some("Jane".to_string());
+        let form = &mut new.patients[0].forms.as_mut().unwrap()[0];
+        form.last_modified = form.last_modified.map(|d| d + Duration::days(7));
+
+        let merged = SubjectNative::merge([old, new]);
+
+        assert_eq!(merged.conflicts.len(), 1);
+        let conflict = &merged.conflicts[0];
+        assert_eq!(
+            entry_value(&conflict.kept),
+            entry_value(&conflict.discarded)
+        );
+        assert_eq!(
+            conflict.kept.as_ref().unwrap().reviewed_by.as_deref(),
+            Some("Jane")
+        );
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, FixedOffset};
+use serde::Serialize;
+
+use crate::native::{
+    common::{Form, Value},
+    subject_native::SubjectNative,
+};
+
+/// The `role` of values Prelude calculated rather than a user entered.
+const SYSTEM_ROLE: &str = "System";
+
+/// Operational metrics over the audit trail of a subject native.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StudyMetrics {
+    pub total: MetricsSummary,
+    /// Keyed by `Patient.site_unique_id`.
+    pub by_site: BTreeMap<String, MetricsSummary>,
+    /// Keyed by `Value.by_unique_id`, or `Value.by` for values without one, e.g.
+    /// "set from calculation". Forms and entered values count towards the user who entered the
+    /// first value, changes towards the user who made them.
+    pub by_user: BTreeMap<String, MetricsSummary>,
+}
+
+/// Metrics over a group of forms and values.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsSummary {
+    pub forms: usize,
+    /// The time from `Form.when_created` to the first value a user entered on the form. Values
+    /// calculated by the system are not counted as entries.
+    pub entry_latency: EntryLatency,
+    /// Fields with at least one value.
+    pub values_entered: usize,
+    /// Fields whose value was changed after it was first entered.
+    pub values_changed: usize,
+    /// Every value after the first one of a field.
+    pub changes: usize,
+    /// The number of changes made by each `Value.role`.
+    pub changes_by_role: BTreeMap<String, usize>,
+    /// Changes calculated by the system, i.e. with the role "System".
+    pub system_changes: usize,
+}
+
+/// Statistics over the entry latencies of forms, in seconds.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryLatency {
+    /// Forms with a known creation time and at least one value entered by a user.
+    pub forms: usize,
+    pub mean_seconds: Option<f64>,
+    pub median_seconds: Option<f64>,
+    pub max_seconds: Option<i64>,
+}
+
+impl EntryLatency {
+    fn new(mut seconds: Vec<i64>) -> Self {
+        seconds.sort_unstable();
+        let forms = seconds.len();
+        let median_seconds = match forms {
+            0 => None,
+            n if n % 2 == 0 => Some((seconds[n / 2 - 1] + seconds[n / 2]) as f64 / 2.0),
+            n => Some(seconds[n / 2] as f64),
+        };
+
+        Self {
+            forms,
+            mean_seconds: (forms > 0).then(|| seconds.iter().sum::<i64>() as f64 / forms as f64),
+            median_seconds,
+            max_seconds: seconds.last().copied(),
+        }
+    }
+}
+
+/// A summary while its latencies are being collected.
+#[derive(Default)]
+struct Tally {
+    summary: MetricsSummary,
+    latencies: Vec<i64>,
+}
+
+impl Tally {
+    fn finish(self) -> MetricsSummary {
+        MetricsSummary {
+            entry_latency: EntryLatency::new(self.latencies),
+            ..self.summary
+        }
+    }
+}
+
+impl SubjectNative {
+    /// Computes entry latency and data-change metrics in total, per site and per user.
+    ///
+    /// The values of a field are ordered by `Value.when`, the earliest being the first entry and
+    /// every later one a change. Entries without a value are skipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::path::Path;
+    ///
+    /// use prelude_xml_parser::parse_subject_native_file;
+    ///
+    /// let native = parse_subject_native_file(Path::new("tests/assets/subject_native.xml")).unwrap();
+    /// let metrics = native.metrics();
+    ///
+    /// let site = &metrics.by_site["1681574834910"];
+    /// assert_eq!(site.forms, metrics.total.forms);
+    /// assert_eq!(metrics.by_user["1681162687395"].entry_latency.max_seconds, Some(36));
+    /// ```
+    pub fn metrics(&self) -> StudyMetrics {
+        let mut total = Tally::default();
+        let mut by_site: BTreeMap<String, Tally> = BTreeMap::new();
+        let mut by_user: BTreeMap<String, Tally> = BTreeMap::new();
+
+        for patient in &self.patients {
+            for form in patient.forms.iter().flatten() {
+                add_form(&mut total, form, None);
+                add_form(
+                    by_site.entry(patient.site_unique_id.clone()).or_default(),
+                    form,
+                    None,
+                );
+
+                let mut users: Vec<String> = form_values(form)
+                    .flatten()
+                    .map(|value| user_key(value).to_string())
+                    .collect();
+                users.sort_unstable();
+                users.dedup();
+                for user in users {
+                    add_form(by_user.entry(user.clone()).or_default(), form, Some(&user));
+                }
+            }
+        }
+
+        let finish = |tallies: BTreeMap<String, Tally>| {
+            tallies
+                .into_iter()
+                .map(|(key, tally)| (key, tally.finish()))
+                .collect()
+        };
+
+        StudyMetrics {
+            total: total.finish(),
+            by_site: finish(by_site),
+            by_user: finish(by_user),
+        }
+    }
+}
+
+fn user_key(value: &Value) -> &str {
+    value.by_unique_id.as_deref().unwrap_or(&value.by)
+}
+
+fn is_system(value: &Value) -> bool {
+    value.role.eq_ignore_ascii_case(SYSTEM_ROLE)
+}
+
+/// The values of each field of `form`, oldest first.
+fn form_values(form: &Form) -> impl Iterator<Item = Vec<&Value>> {
+    form.categories
+        .iter()
+        .flatten()
+        .flat_map(|category| category.fields.iter().flatten())
+        .map(|field| {
+            let mut values: Vec<&Value> = field
+                .entries
+                .iter()
+                .flatten()
+                .filter_map(|entry| entry.value.as_ref())
+                .collect();
+            // Stable, so values without a time keep their export order
+            values.sort_by_key(|value| value.when);
+            values
+        })
+}
+
+/// Adds `form` to `tally`, or only what `user` did on it when given.
+fn add_form(tally: &mut Tally, form: &Form, user: Option<&str>) {
+    let by_user = |value: &Value| user.is_none_or(|user| user_key(value) == user);
+
+    let first_entry = form_values(form)
+        .flatten()
+        .filter(|value| !is_system(value))
+        .filter_map(|value| value.when.map(|when| (when, value)))
+        .min_by_key(|(when, _)| *when);
+    let counts_form = match (user, first_entry) {
+        (None, _) => true,
+        (Some(user), Some((_, value))) => user_key(value) == user,
+        (Some(_), None) => false,
+    };
+    if counts_form {
+        tally.summary.forms += 1;
+        if let Some(((when, _), created)) = first_entry.zip(created(form)) {
+            tally.latencies.push((when - created).num_seconds());
+        }
+    }
+
+    let summary = &mut tally.summary;
+    for values in form_values(form) {
+        let Some((first, changes)) = values.split_first() else {
+            continue;
+        };
+        summary.values_entered += usize::from(by_user(first));
+
+        let changes: Vec<&Value> = changes.iter().copied().filter(|c| by_user(c)).collect();
+        summary.values_changed += usize::from(!changes.is_empty());
+        summary.changes += changes.len();
+        for change in changes {
+            *summary
+                .changes_by_role
+                .entry(change.role.clone())
+                .or_default() += 1;
+            summary.system_changes += usize::from(is_system(change));
+        }
+    }
+}
+
+/// `Form.when_created` is milliseconds since the Unix epoch, 0 when unknown.
+fn created(form: &Form) -> Option<DateTime<FixedOffset>> {
+    if form.when_created == 0 {
+        return None;
+    }
+    DateTime::from_timestamp_millis(form.when_created as i64).map(|created| created.fixed_offset())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::native::common::Entry;
+    use crate::native::test_helpers::{first_field, subject_native};
+
+    fn first_entries(native: &mut SubjectNative) -> &mut Vec<Entry> {
+        first_field(native).entries.as_mut().unwrap()
+    }
+
+    #[test]
+    fn measures_entry_latency() {
+        let metrics = subject_native().metrics();
+
+        // Created at 12:08:25.839, first entered at 12:09:02
+        let user = &metrics.by_user["1681162687395"];
+        assert_eq!(user.forms, 1);
+        assert_eq!(user.entry_latency.forms, 1);
+        assert_eq!(user.entry_latency.median_seconds, Some(36.0));
+        assert_eq!(metrics.total.forms, 4);
+        assert_eq!(
+            metrics.total.entry_latency.forms,
+            metrics
+                .by_user
+                .values()
+                .map(|u| u.entry_latency.forms)
+                .sum::<usize>()
+        );
+        // Only calculated values, so no user entered the first value
+        assert_eq!(metrics.by_user["set from calculation"].forms, 0);
+    }
+
+    #[test]
+    fn counts_changes_by_role() {
+        let mut native = subject_native();
+        let entries = first_entries(&mut native);
+        let first = entries[0].clone();
+        let first_value = first.value.clone().unwrap();
+        let change = |hours, by: &str, by_unique_id: Option<&str>, role: &str| Entry {
+            entry_id: "0".to_string(),
+            value: Some(Value {
+                by: by.to_string(),
+                by_unique_id: by_unique_id.map(str::to_string),
+                role: role.to_string(),
+                when: first_value.when.map(|when| when + Duration::hours(hours)),
+                value: "Poodle".to_string(),
+                extra: BTreeMap::new(),
+            }),
+            ..first.clone()
+        };
+        // Exported newest first
+        *entries = vec![
+            change(2, "set from calculation", None, "System"),
+            change(1, "Some User", Some("1630429016609"), "Data Manager"),
+            first,
+        ];
+
+        let metrics = native.metrics();
+
+        let total = &metrics.total;
+        assert_eq!(total.values_changed, 1);
+        assert_eq!(total.changes, 2);
+        assert_eq!(total.changes_by_role["Data Manager"], 1);
+        assert_eq!(total.changes_by_role["System"], 1);
+        assert_eq!(total.system_changes, 1);
+        assert_eq!(metrics.by_site["1681574834910"], *total);
+
+        let user = &metrics.by_user["1630429016609"];
+        assert_eq!(user.forms, 0);
+        assert_eq!(user.values_entered, 0);
+        assert_eq!(user.values_changed, 1);
+        assert_eq!(user.changes, 1);
+        assert_eq!(user.system_changes, 0);
+        let entered_by = &metrics.by_user["1681162687395"];
+        assert_eq!(entered_by.changes, 0);
+    }
+
+    #[test]
+    fn summarizes_latencies() {
+        let latency = EntryLatency::new(vec![30, 10, 20, 40]);
+
+        assert_eq!(latency.forms, 4);
+        assert_eq!(latency.mean_seconds, Some(25.0));
+        assert_eq!(latency.median_seconds, Some(25.0));
+        assert_eq!(latency.max_seconds, Some(40));
+        assert_eq!(EntryLatency::new(Vec::new()), EntryLatency::default());
+    }
+}
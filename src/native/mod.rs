@@ -1,5 +1,20 @@
+pub mod anonymize;
+pub mod comments;
 pub mod common;
-mod deserializers;
+pub(crate) mod deserializers;
+pub mod diff;
+pub mod flat;
+pub mod json;
+pub mod lifecycle;
+pub mod merge;
+pub mod metrics;
+pub mod ndjson;
+pub mod quality;
 pub mod site_native;
+pub mod study;
 pub mod subject_native;
+#[cfg(test)]
+pub(crate) mod test_helpers;
 pub mod user_native;
+pub mod visitor;
+mod xml;
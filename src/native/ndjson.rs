@@ -0,0 +1,220 @@
+use std::io::Write;
+
+use serde::Serialize;
+
+use crate::errors::Error;
+use crate::native::{
+    flat::FlatEntry, site_native::Site, subject_native::Patient, user_native::User,
+};
+use crate::{iter_site_native_sites, iter_subject_native_patients, iter_user_native_users};
+
+/// What each line of NDJSON (JSON Lines) output holds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NdjsonLines {
+    /// One object per `Patient`, `Site`, or `User`, serialized like in `to_json`.
+    #[default]
+    Records,
+
+    /// One object per field entry, with the columns of [`FlatEntry`].
+    Entries,
+}
+
+/// Writes the patients of a string of Prelude native subject XML as NDJSON.
+///
+/// Patients are parsed and written one at a time, so the full tree is never held in memory.
+/// Nothing is buffered, wrap `writer` in a `BufWriter` when writing to a file. If a patient fails
+/// to parse the lines written before it are kept and the error is returned.
+///
+/// Returns the number of lines written.
+///
+/// # Example
+///
+/// ```
+/// use prelude_xml_parser::native::ndjson::{write_subject_native_ndjson, NdjsonLines};
+///
+/// let xml = std::fs::read_to_string("tests/assets/subject_native.xml").unwrap();
+/// let mut out = Vec::new();
+/// let lines = write_subject_native_ndjson(&xml, &mut out, NdjsonLines::Records).unwrap();
+///
+/// assert_eq!(lines, 2);
+/// assert_eq!(String::from_utf8(out).unwrap().lines().count(), 2);
+/// ```
+pub fn write_subject_native_ndjson<W: Write>(
+    xml_str: &str,
+    writer: W,
+    lines: NdjsonLines,
+) -> Result<usize, Error> {
+    write_ndjson(
+        iter_subject_native_patients(xml_str),
+        Patient::flat_entries,
+        writer,
+        lines,
+    )
+}
+
+/// Writes the sites of a string of Prelude native site XML as NDJSON.
+///
+/// See [`write_subject_native_ndjson`].
+pub fn write_site_native_ndjson<W: Write>(
+    xml_str: &str,
+    writer: W,
+    lines: NdjsonLines,
+) -> Result<usize, Error> {
+    write_ndjson(
+        iter_site_native_sites(xml_str),
+        Site::flat_entries,
+        writer,
+        lines,
+    )
+}
+
+/// Writes the users of a string of Prelude native user XML as NDJSON.
+///
+/// See [`write_subject_native_ndjson`].
+pub fn write_user_native_ndjson<W: Write>(
+    xml_str: &str,
+    writer: W,
+    lines: NdjsonLines,
+) -> Result<usize, Error> {
+    write_ndjson(
+        iter_user_native_users(xml_str),
+        User::flat_entries,
+        writer,
+        lines,
+    )
+}
+
+fn write_ndjson<T, W>(
+    records: impl Iterator<Item = Result<T, Error>>,
+    flat_entries: impl Fn(&T) -> Vec<FlatEntry<'_>>,
+    mut writer: W,
+    lines: NdjsonLines,
+) -> Result<usize, Error>
+where
+    T: Serialize,
+    W: Write,
+{
+    let mut written = 0;
+    for record in records {
+        let record = record?;
+        match lines {
+            NdjsonLines::Records => {
+                write_line(&mut writer, &record)?;
+                written += 1;
+            }
+            NdjsonLines::Entries => {
+                for entry in flat_entries(&record) {
+                    write_line(&mut writer, &entry)?;
+                    written += 1;
+                }
+            }
+        }
+    }
+    writer.flush()?;
+
+    Ok(written)
+}
+
+fn write_line<W: Write, T: Serialize>(writer: &mut W, value: &T) -> Result<(), Error> {
+    serde_json::to_writer(&mut *writer, value).map_err(|e| {
+        if e.is_io() {
+            Error::IO(e.into())
+        } else {
+            Error::Json(e)
+        }
+    })?;
+    writer.write_all(b"\n")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::read_to_string, path::Path};
+
+    use super::*;
+    use crate::{parse_site_native_file, parse_subject_native_file, parse_user_native_file};
+
+    fn lines(out: &[u8]) -> Vec<serde_json::Value> {
+        std::str::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn writes_one_line_per_record() {
+        let subject_xml = read_to_string("tests/assets/subject_native.xml").unwrap();
+        let site_xml = read_to_string("tests/assets/site_native.xml").unwrap();
+        let user_xml = read_to_string("tests/assets/user_native.xml").unwrap();
+        let subject =
+            parse_subject_native_file(Path::new("tests/assets/subject_native.xml")).unwrap();
+        let site = parse_site_native_file(Path::new("tests/assets/site_native.xml")).unwrap();
+        let user = parse_user_native_file(Path::new("tests/assets/user_native.xml")).unwrap();
+
+        let mut out = Vec::new();
+        let written =
+            write_subject_native_ndjson(&subject_xml, &mut out, NdjsonLines::Records).unwrap();
+        let patients = lines(&out)
+            .into_iter()
+            .map(|line| serde_json::from_value::<Patient>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(written, subject.patients.len());
+        assert_eq!(patients, subject.patients);
+
+        let mut out = Vec::new();
+        let written = write_site_native_ndjson(&site_xml, &mut out, NdjsonLines::Records).unwrap();
+        let sites = lines(&out)
+            .into_iter()
+            .map(|line| serde_json::from_value::<Site>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(written, site.sites.len());
+        assert_eq!(sites, site.sites);
+
+        let mut out = Vec::new();
+        let written = write_user_native_ndjson(&user_xml, &mut out, NdjsonLines::Records).unwrap();
+        let users = lines(&out)
+            .into_iter()
+            .map(|line| serde_json::from_value::<User>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(written, user.users.len());
+        assert_eq!(users, user.users);
+    }
+
+    #[test]
+    fn writes_one_line_per_entry() {
+        let xml = read_to_string("tests/assets/subject_native.xml").unwrap();
+        let native =
+            parse_subject_native_file(Path::new("tests/assets/subject_native.xml")).unwrap();
+        let expected = native
+            .flat_entries()
+            .iter()
+            .map(|entry| serde_json::to_value(entry).unwrap())
+            .collect::<Vec<_>>();
+
+        let mut out = Vec::new();
+        let written = write_subject_native_ndjson(&xml, &mut out, NdjsonLines::Entries).unwrap();
+
+        assert_eq!(written, expected.len());
+        assert_eq!(lines(&out), expected);
+    }
+
+    #[test]
+    fn keeps_lines_written_before_a_parse_error() {
+        let xml = read_to_string("tests/assets/subject_native.xml").unwrap();
+        let first_end = xml.find("</patient>").unwrap() + "</patient>".len();
+        let second_start = first_end + xml[first_end..].find("<patient ").unwrap();
+        let broken = format!(
+            "{}<patient uniqueId=\"broken></patient>{}",
+            &xml[..second_start],
+            &xml[second_start..]
+        );
+
+        let mut out = Vec::new();
+        let result = write_subject_native_ndjson(&broken, &mut out, NdjsonLines::Records);
+
+        assert!(result.is_err());
+        assert_eq!(lines(&out).len(), 1);
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::native::{
+    common::Form, site_native::SiteNative, subject_native::SubjectNative, user_native::UserNative,
+};
+
+/// The `errorCode` of a field that passed its edit checks.
+const VALID_ERROR_CODE: &str = "valid";
+
+/// A form Prelude flagged with errors or warnings, or with fields that did not pass their edit
+/// checks.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormIssues {
+    /// `Patient.unique_id`, `Site.unique_id`, or `User.unique_id`.
+    pub record_unique_id: String,
+    /// The site of the patient, or the site itself. `None` for users.
+    pub site_unique_id: Option<String>,
+    pub form_name: String,
+    pub form_index: usize,
+    pub form_title: String,
+    pub has_errors: bool,
+    pub has_warnings: bool,
+    /// The fields of the form whose `error_code` is not "valid".
+    pub fields: Vec<FieldIssue>,
+}
+
+/// A field whose `error_code` is not "valid".
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldIssue {
+    pub category_name: String,
+    pub field_name: String,
+    pub error_code: String,
+}
+
+/// Data-quality counts over a group of forms, e.g. those of one patient or site.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QualitySummary {
+    pub forms_with_errors: usize,
+    pub forms_with_warnings: usize,
+    /// The number of fields whose `error_code` is not "valid".
+    pub invalid_fields: usize,
+    /// The number of invalid fields with each `error_code`.
+    pub error_codes: BTreeMap<String, usize>,
+}
+
+impl QualitySummary {
+    /// Summarizes `issues` per `record_unique_id`.
+    pub fn by_record(issues: &[FormIssues]) -> BTreeMap<String, QualitySummary> {
+        summarize(issues, |form| Some(&form.record_unique_id))
+    }
+
+    /// Summarizes `issues` per `site_unique_id`, skipping forms without a site.
+    pub fn by_site(issues: &[FormIssues]) -> BTreeMap<String, QualitySummary> {
+        summarize(issues, |form| form.site_unique_id.as_ref())
+    }
+
+    /// Summarizes `issues` per `form_name`, across records and form indexes.
+    pub fn by_form(issues: &[FormIssues]) -> BTreeMap<String, QualitySummary> {
+        summarize(issues, |form| Some(&form.form_name))
+    }
+
+    /// Summarizes the invalid fields per form and field name, keyed "form_name/field_name".
+    ///
+    /// Forms without invalid fields are skipped, and a form with errors or warnings is counted
+    /// once for each of its invalid fields.
+    pub fn by_field(issues: &[FormIssues]) -> BTreeMap<String, QualitySummary> {
+        let mut summaries: BTreeMap<String, QualitySummary> = BTreeMap::new();
+        for form in issues {
+            for field in &form.fields {
+                let key = format!("{}/{}", form.form_name, field.field_name);
+                let summary = summaries.entry(key).or_default();
+                summary.add_form(form);
+                summary.add_field(field);
+            }
+        }
+        summaries
+    }
+
+    fn add_form(&mut self, form: &FormIssues) {
+        self.forms_with_errors += usize::from(form.has_errors);
+        self.forms_with_warnings += usize::from(form.has_warnings);
+    }
+
+    fn add_field(&mut self, field: &FieldIssue) {
+        self.invalid_fields += 1;
+        *self
+            .error_codes
+            .entry(field.error_code.clone())
+            .or_default() += 1;
+    }
+}
+
+fn summarize<'a>(
+    issues: &'a [FormIssues],
+    key: impl Fn(&'a FormIssues) -> Option<&'a String>,
+) -> BTreeMap<String, QualitySummary> {
+    let mut summaries: BTreeMap<String, QualitySummary> = BTreeMap::new();
+    for form in issues {
+        if let Some(key) = key(form) {
+            let summary = summaries.entry(key.clone()).or_default();
+            summary.add_form(form);
+            form.fields
+                .iter()
+                .for_each(|field| summary.add_field(field));
+        }
+    }
+    summaries
+}
+
+impl SubjectNative {
+    /// Lists the forms of every patient with errors, warnings, or fields whose `error_code` is
+    /// not "valid", in export order. Forms without any are left out.
+    pub fn quality_issues(&self) -> Vec<FormIssues> {
+        self.patients
+            .iter()
+            .flat_map(|patient| {
+                quality_issues(
+                    &patient.unique_id,
+                    Some(&patient.site_unique_id),
+                    &patient.forms,
+                )
+            })
+            .collect()
+    }
+}
+
+impl SiteNative {
+    /// Lists the forms of every site with errors, warnings, or invalid fields.
+    ///
+    /// See [`SubjectNative::quality_issues`].
+    pub fn quality_issues(&self) -> Vec<FormIssues> {
+        self.sites
+            .iter()
+            .flat_map(|site| quality_issues(&site.unique_id, Some(&site.unique_id), &site.forms))
+            .collect()
+    }
+}
+
+impl UserNative {
+    /// Lists the forms of every user with errors, warnings, or invalid fields.
+    ///
+    /// See [`SubjectNative::quality_issues`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::path::Path;
+    ///
+    /// use prelude_xml_parser::native::quality::QualitySummary;
+    /// use prelude_xml_parser::parse_user_native_file;
+    ///
+    /// let native = parse_user_native_file(Path::new("tests/assets/user_native.xml")).unwrap();
+    /// let issues = native.quality_issues();
+    ///
+    /// for form in &issues {
+    ///     for field in &form.fields {
+    ///         println!("{} {} {}", form.record_unique_id, field.field_name, field.error_code);
+    ///     }
+    /// }
+    /// let by_field = QualitySummary::by_field(&issues);
+    /// assert_eq!(by_field["form.name.demographics/email"].error_codes["undefined"], 2);
+    /// ```
+    pub fn quality_issues(&self) -> Vec<FormIssues> {
+        self.users
+            .iter()
+            .flat_map(|user| quality_issues(&user.unique_id, None, &user.forms))
+            .collect()
+    }
+}
+
+fn quality_issues(
+    record_unique_id: &str,
+    site_unique_id: Option<&String>,
+    forms: &Option<Vec<Form>>,
+) -> Vec<FormIssues> {
+    forms
+        .iter()
+        .flatten()
+        .filter_map(|form| {
+            let fields: Vec<FieldIssue> = form
+                .categories
+                .iter()
+                .flatten()
+                .flat_map(|category| {
+                    category
+                        .fields
+                        .iter()
+                        .flatten()
+                        .filter(|field| field.error_code != VALID_ERROR_CODE)
+                        .map(|field| FieldIssue {
+                            category_name: category.name.clone(),
+                            field_name: field.name.clone(),
+                            error_code: field.error_code.clone(),
+                        })
+                })
+                .collect();
+
+            if !form.has_errors && !form.has_warnings && fields.is_empty() {
+                return None;
+            }
+
+            Some(FormIssues {
+                record_unique_id: record_unique_id.to_string(),
+                site_unique_id: site_unique_id.cloned(),
+                form_name: form.name.clone(),
+                form_index: form.form_index,
+                form_title: form.form_title.clone(),
+                has_errors: form.has_errors,
+                has_warnings: form.has_warnings,
+                fields,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native::test_helpers::subject_native;
+
+    #[test]
+    fn lists_flagged_forms_and_invalid_fields() {
+        let mut native = subject_native();
+        let form = &mut native.patients[0].forms.as_mut().unwrap()[0];
+        form.has_errors = true;
+        form.categories.as_mut().unwrap()[0]
+            .fields
+            .as_mut()
+            .unwrap()[0]
+            .error_code = "required".to_string();
+
+        let issues = native.quality_issues();
+
+        let first = &issues[0];
+        assert_eq!(first.record_unique_id, "1681574905819");
+        assert_eq!(first.form_name, "day.0.form.name.demographics");
+        assert!(first.has_errors);
+        assert_eq!(
+            first.fields[0],
+            FieldIssue {
+                category_name: "Demographics".to_string(),
+                field_name: "breed".to_string(),
+                error_code: "required".to_string(),
+            }
+        );
+        assert!(issues
+            .iter()
+            .flat_map(|form| &form.fields)
+            .all(|field| field.error_code != "valid"));
+    }
+
+    #[test]
+    fn leaves_out_forms_without_issues() {
+        let mut native = subject_native();
+        for form in native
+            .patients
+            .iter_mut()
+            .flat_map(|p| p.forms.iter_mut().flatten())
+        {
+            for category in form.categories.iter_mut().flatten() {
+                for field in category.fields.iter_mut().flatten() {
+                    field.error_code = "valid".to_string();
+                }
+            }
+        }
+        native.patients[1].forms.as_mut().unwrap()[0].has_warnings = true;
+
+        let issues = native.quality_issues();
+
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].has_warnings);
+        assert!(issues[0].fields.is_empty());
+    }
+
+    #[test]
+    fn summarizes_by_record_site_form_and_field() {
+        let mut native = subject_native();
+        native.patients[0].forms.as_mut().unwrap()[0].has_errors = true;
+        let issues = native.quality_issues();
+
+        let by_record = QualitySummary::by_record(&issues);
+        let by_site = QualitySummary::by_site(&issues);
+        let by_form = QualitySummary::by_form(&issues);
+        let by_field = QualitySummary::by_field(&issues);
+
+        let invalid_fields = issues.iter().map(|form| form.fields.len()).sum::<usize>();
+        assert_eq!(by_site.len(), 1);
+        assert_eq!(by_site["1681574834910"].forms_with_errors, 1);
+        assert_eq!(by_site["1681574834910"].invalid_fields, invalid_fields);
+        assert_eq!(
+            by_record.values().map(|s| s.invalid_fields).sum::<usize>(),
+            invalid_fields
+        );
+        assert_eq!(
+            by_form.values().map(|s| s.invalid_fields).sum::<usize>(),
+            invalid_fields
+        );
+        assert_eq!(
+            by_field.values().map(|s| s.invalid_fields).sum::<usize>(),
+            invalid_fields
+        );
+        assert!(by_field
+            .keys()
+            .all(|key| key.starts_with("day.") || key.starts_with("study.exit.")));
+    }
+}
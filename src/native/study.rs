@@ -0,0 +1,684 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, FixedOffset};
+use serde::Serialize;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+#[cfg(feature = "python")]
+use crate::python::to_py_json;
+
+use crate::native::{
+    common::{Form, SYSTEM_UNIQUE_ID},
+    site_native::{Site, SiteNative},
+    subject_native::{Patient, SubjectNative},
+    user_native::UserNative,
+};
+
+/// The subject and site natives of a study, joined on `Patient.site_unique_id`.
+///
+/// # Example
+///
+/// ```
+/// use std::path::Path;
+///
+/// use prelude_xml_parser::native::study::Study;
+/// use prelude_xml_parser::{parse_site_native_file, parse_subject_native_file};
+///
+/// let subjects = parse_subject_native_file(Path::new("tests/assets/subject_native.xml")).unwrap();
+/// let sites = parse_site_native_file(Path::new("tests/assets/site_native.xml")).unwrap();
+/// let study = Study::new(subjects, sites);
+///
+/// for site in study.sites() {
+///     println!("{}: {} patients", site.site.name, site.patients.len());
+/// }
+/// ```
+#[cfg_attr(
+    feature = "python",
+    pyclass(get_all, module = "prelude_xml_parser", skip_from_py_object)
+)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Study {
+    pub subject_native: SubjectNative,
+    pub site_native: SiteNative,
+}
+
+/// A site with the patients enrolled at it.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct StudySite<'a> {
+    pub site: &'a Site,
+    pub patients: Vec<&'a Patient>,
+}
+
+/// A difference between what a site reports about its patients and the patients exported.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnrollmentIssue {
+    /// `Site.unique_id`, or the `Patient.site_unique_id` of a patient whose site is unknown.
+    pub site_unique_id: String,
+    /// Set for issues with a single patient.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub patient_unique_id: Option<String>,
+    #[serde(flatten)]
+    pub kind: EnrollmentIssueKind,
+}
+
+/// What does not match, serialized with an `issue` tag.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(
+    tag = "issue",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum EnrollmentIssueKind {
+    /// `Site.number_of_patients` differs from the number of patients at the site.
+    PatientCount { reported: usize, actual: usize },
+    /// `Site.count_of_randomized_patients` differs from the number of randomized patients at the
+    /// site.
+    RandomizedCount { reported: usize, actual: usize },
+    /// The patient's `site_name` is not the name of its site.
+    SiteName {
+        site_name: String,
+        patient_site_name: String,
+    },
+    /// The patient's `site_unique_id` is not in the site native.
+    UnknownSite,
+}
+
+impl Study {
+    pub fn new(subject_native: SubjectNative, site_native: SiteNative) -> Self {
+        Self {
+            subject_native,
+            site_native,
+        }
+    }
+
+    /// The sites in export order, each with its patients in export order.
+    pub fn sites(&self) -> Vec<StudySite<'_>> {
+        let mut patients: HashMap<&str, Vec<&Patient>> = HashMap::new();
+        for patient in &self.subject_native.patients {
+            patients
+                .entry(patient.site_unique_id.as_str())
+                .or_default()
+                .push(patient);
+        }
+
+        self.site_native
+            .sites
+            .iter()
+            .map(|site| StudySite {
+                site,
+                patients: patients.remove(site.unique_id.as_str()).unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    /// The site of `patient`, if it is in the site native.
+    pub fn site_of(&self, patient: &Patient) -> Option<&Site> {
+        self.site_native
+            .sites
+            .iter()
+            .find(|site| site.unique_id == patient.site_unique_id)
+    }
+
+    /// Patients whose `site_unique_id` is not in the site native.
+    pub fn unassigned_patients(&self) -> Vec<&Patient> {
+        self.subject_native
+            .patients
+            .iter()
+            .filter(|patient| self.site_of(patient).is_none())
+            .collect()
+    }
+
+    /// Compares the patient counts sites report with the exported patients, and the patients'
+    /// `site_name` with the name of their site.
+    ///
+    /// A patient counts as randomized when it has a form whose name or title mentions
+    /// randomization, see [`Self::check_enrollment_with`] to decide otherwise. The counts are only
+    /// meaningful when the subject native holds every patient of the study.
+    pub fn check_enrollment(&self) -> Vec<EnrollmentIssue> {
+        self.check_enrollment_with(has_randomization_form)
+    }
+
+    /// [`Self::check_enrollment`] with `is_randomized` deciding which patients are randomized.
+    pub fn check_enrollment_with(
+        &self,
+        is_randomized: impl Fn(&Patient) -> bool,
+    ) -> Vec<EnrollmentIssue> {
+        let mut issues = Vec::new();
+        for StudySite { site, patients } in self.sites() {
+            let site_issue = |kind| EnrollmentIssue {
+                site_unique_id: site.unique_id.clone(),
+                patient_unique_id: None,
+                kind,
+            };
+            if site.number_of_patients != patients.len() {
+                issues.push(site_issue(EnrollmentIssueKind::PatientCount {
+                    reported: site.number_of_patients,
+                    actual: patients.len(),
+                }));
+            }
+            let randomized = patients.iter().filter(|p| is_randomized(p)).count();
+            if site.count_of_randomized_patients != randomized {
+                issues.push(site_issue(EnrollmentIssueKind::RandomizedCount {
+                    reported: site.count_of_randomized_patients,
+                    actual: randomized,
+                }));
+            }
+            for patient in patients {
+                if patient.site_name != site.name {
+                    issues.push(EnrollmentIssue {
+                        site_unique_id: site.unique_id.clone(),
+                        patient_unique_id: Some(patient.unique_id.clone()),
+                        kind: EnrollmentIssueKind::SiteName {
+                            site_name: site.name.clone(),
+                            patient_site_name: patient.site_name.clone(),
+                        },
+                    });
+                }
+            }
+        }
+        for patient in self.unassigned_patients() {
+            issues.push(EnrollmentIssue {
+                site_unique_id: patient.site_unique_id.clone(),
+                patient_unique_id: Some(patient.unique_id.clone()),
+                kind: EnrollmentIssueKind::UnknownSite,
+            });
+        }
+
+        issues
+    }
+
+    /// Resolves the user references of the study, see [`link_users`].
+    pub fn link_users(&self, user_native: &UserNative) -> UserLinks {
+        link_users(&self.subject_native, &self.site_native, user_native)
+    }
+}
+
+fn has_randomization_form(patient: &Patient) -> bool {
+    patient.forms.iter().flatten().any(|form| {
+        [&form.name, &form.form_title]
+            .iter()
+            .any(|name| name.to_lowercase().contains("randomi"))
+    })
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Study {
+    #[new]
+    fn py_new(subject_native: SubjectNative, site_native: SiteNative) -> Self {
+        Self::new(subject_native, site_native)
+    }
+
+    /// A copy of the study's subject native; changing it does not change the study.
+    #[getter]
+    fn subject_native(&self) -> SubjectNative {
+        self.subject_native.clone()
+    }
+
+    /// A copy of the study's site native; changing it does not change the study.
+    #[getter]
+    fn site_native(&self) -> SiteNative {
+        self.site_native.clone()
+    }
+
+    /// The sites with their patients, as `(site, patients)` tuples
+    #[pyo3(name = "sites")]
+    fn py_sites(&self) -> Vec<(Site, Vec<Patient>)> {
+        self.sites()
+            .into_iter()
+            .map(|s| (s.site.clone(), s.patients.into_iter().cloned().collect()))
+            .collect()
+    }
+
+    /// Patients whose `site_unique_id` is not in the site native
+    #[pyo3(name = "unassigned_patients")]
+    fn py_unassigned_patients(&self) -> Vec<Patient> {
+        self.unassigned_patients().into_iter().cloned().collect()
+    }
+
+    /// Compare the patient counts sites report with the exported patients, as dictionaries
+    #[pyo3(name = "check_enrollment")]
+    fn py_check_enrollment<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py_json(py, &py.detach(|| self.check_enrollment()))
+    }
+
+    /// Resolve the user references of the study to the users of `user_native`
+    #[pyo3(name = "link_users")]
+    fn py_link_users<'py>(
+        &self,
+        py: Python<'py>,
+        user_native: &UserNative,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        to_py_json(py, &py.detach(|| self.link_users(user_native)))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Study(patients=<{} items>, sites=<{} items>)",
+            self.subject_native.patients.len(),
+            self.site_native.sites.len()
+        )
+    }
+}
+
+/// The kind of record a reference was found in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordKind {
+    Patient,
+    Site,
+    User,
+}
+
+/// How a user is referenced.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceKind {
+    /// `Value.by_unique_id` of an entry.
+    ValueEntered,
+    /// `Reason.by_unique_id` of an entry or comment.
+    ReasonEntered,
+    /// `Value.by_unique_id` of a comment or one of its replies.
+    CommentEntered,
+    /// `Entry.reviewed_by_unique_id`.
+    EntryReviewed,
+    /// `State.signer_unique_id`.
+    FormSigned,
+    /// `LockState.user_unique_id`.
+    FormLocked,
+}
+
+/// A user unique id recorded somewhere in an export.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserReference {
+    pub user_unique_id: String,
+    /// The name recorded with the id, e.g. `Value.by` or `State.signer`.
+    pub recorded_name: Option<String>,
+    pub kind: ReferenceKind,
+    pub when: Option<DateTime<FixedOffset>>,
+    pub record_kind: RecordKind,
+    /// `Patient.unique_id`, `Site.unique_id`, or `User.unique_id`.
+    pub record_unique_id: String,
+    pub form_name: String,
+    pub form_index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_id: Option<String>,
+}
+
+/// A user of the user native with everything that references them.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkedUser {
+    pub unique_id: String,
+    /// From the user's demographics form, see [`crate::native::user_native::User::name`].
+    pub name: Option<String>,
+    /// From the user's demographics form.
+    pub email: Option<String>,
+    pub references: Vec<UserReference>,
+}
+
+/// The user references of a study resolved against its user native.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserLinks {
+    /// Every user of the user native, in export order.
+    pub users: Vec<LinkedUser>,
+    /// References to unique ids that are not in the user native.
+    pub dangling: Vec<UserReference>,
+}
+
+impl UserLinks {
+    /// The user with `unique_id`, if it is in the user native.
+    pub fn user(&self, unique_id: &str) -> Option<&LinkedUser> {
+        self.users.iter().find(|user| user.unique_id == unique_id)
+    }
+}
+
+/// Resolves the user unique ids recorded in the subject, site, and user natives of a study to
+/// the users of the user native.
+///
+/// References are collected from values, reasons, comments, entry reviews, form signatures, and
+/// locks, in export order. Empty ids and actions taken by the system are skipped.
+///
+/// # Example
+///
+/// ```
+/// use std::path::Path;
+///
+/// use prelude_xml_parser::native::study::link_users;
+/// use prelude_xml_parser::{parse_site_native_file, parse_subject_native_file, parse_user_native_file};
+///
+/// let subjects = parse_subject_native_file(Path::new("tests/assets/subject_native.xml")).unwrap();
+/// let sites = parse_site_native_file(Path::new("tests/assets/site_native.xml")).unwrap();
+/// let users = parse_user_native_file(Path::new("tests/assets/user_native.xml")).unwrap();
+///
+/// let links = link_users(&subjects, &sites, &users);
+/// let user = links.user("1681162687395").unwrap();
+///
+/// assert_eq!(user.email.as_deref(), Some("paul@pbsdatasolutions.com"));
+/// assert!(!user.references.is_empty());
+/// ```
+pub fn link_users(subjects: &SubjectNative, sites: &SiteNative, users: &UserNative) -> UserLinks {
+    let mut references = Vec::new();
+    for patient in &subjects.patients {
+        collect_references(
+            RecordKind::Patient,
+            &patient.unique_id,
+            &patient.forms,
+            &mut references,
+        );
+    }
+    for site in &sites.sites {
+        collect_references(
+            RecordKind::Site,
+            &site.unique_id,
+            &site.forms,
+            &mut references,
+        );
+    }
+    for user in &users.users {
+        collect_references(
+            RecordKind::User,
+            &user.unique_id,
+            &user.forms,
+            &mut references,
+        );
+    }
+
+    let mut linked: Vec<LinkedUser> = users
+        .users
+        .iter()
+        .map(|user| LinkedUser {
+            unique_id: user.unique_id.clone(),
+            name: user.name(),
+            email: user.email().map(str::to_string),
+            references: Vec::new(),
+        })
+        .collect();
+    let positions: HashMap<String, usize> = linked
+        .iter()
+        .enumerate()
+        .map(|(i, user)| (user.unique_id.clone(), i))
+        .collect();
+
+    let mut dangling = Vec::new();
+    for reference in references {
+        match positions.get(&reference.user_unique_id) {
+            Some(&position) => linked[position].references.push(reference),
+            None => dangling.push(reference),
+        }
+    }
+
+    UserLinks {
+        users: linked,
+        dangling,
+    }
+}
+
+fn collect_references(
+    record_kind: RecordKind,
+    record_unique_id: &str,
+    forms: &Option<Vec<Form>>,
+    references: &mut Vec<UserReference>,
+) {
+    for form in forms.iter().flatten() {
+        let mut push = |user_unique_id: Option<&str>,
+                        recorded_name: Option<&str>,
+                        kind: ReferenceKind,
+                        when: Option<DateTime<FixedOffset>>,
+                        location: [Option<&str>; 3]| {
+            let Some(user_unique_id) = user_unique_id
+                .filter(|id| !id.is_empty() && !id.eq_ignore_ascii_case(SYSTEM_UNIQUE_ID))
+            else {
+                return;
+            };
+            let [category_name, field_name, entry_id] = location.map(|s| s.map(str::to_string));
+            references.push(UserReference {
+                user_unique_id: user_unique_id.to_string(),
+                recorded_name: recorded_name
+                    .filter(|name| !name.is_empty())
+                    .map(str::to_string),
+                kind,
+                when,
+                record_kind,
+                record_unique_id: record_unique_id.to_string(),
+                form_name: form.name.clone(),
+                form_index: form.form_index,
+                category_name,
+                field_name,
+                entry_id,
+            });
+        };
+
+        for state in form.states.iter().flatten() {
+            push(
+                Some(&state.signer_unique_id),
+                Some(&state.signer),
+                ReferenceKind::FormSigned,
+                state.date_signed,
+                [None; 3],
+            );
+        }
+        if let Some(lock_state) = &form.lock_state {
+            push(
+                lock_state.user_unique_id.as_deref(),
+                lock_state.user.as_deref(),
+                ReferenceKind::FormLocked,
+                lock_state.date_time_changed,
+                [None; 3],
+            );
+        }
+
+        for category in form.categories.iter().flatten() {
+            for field in category.fields.iter().flatten() {
+                for entry in field.entries.iter().flatten() {
+                    let location = [
+                        Some(category.name.as_str()),
+                        Some(field.name.as_str()),
+                        Some(entry.entry_id.as_str()),
+                    ];
+                    if let Some(value) = &entry.value {
+                        push(
+                            value.by_unique_id.as_deref(),
+                            Some(&value.by),
+                            ReferenceKind::ValueEntered,
+                            value.when,
+                            location,
+                        );
+                    }
+                    if let Some(reason) = &entry.reason {
+                        push(
+                            reason.by_unique_id.as_deref(),
+                            Some(&reason.by),
+                            ReferenceKind::ReasonEntered,
+                            reason.when,
+                            location,
+                        );
+                    }
+                    push(
+                        entry.reviewed_by_unique_id.as_deref(),
+                        entry.reviewed_by.as_deref(),
+                        ReferenceKind::EntryReviewed,
+                        entry.reviewed_by_when,
+                        location,
+                    );
+                }
+                for comment in field.comments.iter().flatten() {
+                    let location = [
+                        Some(category.name.as_str()),
+                        Some(field.name.as_str()),
+                        None,
+                    ];
+                    for value in comment.values() {
+                        push(
+                            value.by_unique_id.as_deref(),
+                            Some(&value.by),
+                            ReferenceKind::CommentEntered,
+                            value.when,
+                            location,
+                        );
+                    }
+                    if let Some(reason) = &comment.reason {
+                        push(
+                            reason.by_unique_id.as_deref(),
+                            Some(&reason.by),
+                            ReferenceKind::ReasonEntered,
+                            reason.when,
+                            location,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native::test_helpers::{site_native, subject_native, user_native};
+
+    fn study() -> Study {
+        Study::new(subject_native(), site_native())
+    }
+
+    #[test]
+    fn groups_patients_by_site() {
+        let study = study();
+
+        let sites = study.sites();
+
+        assert_eq!(sites.len(), 2);
+        assert_eq!(sites[0].site.name, "Some Site");
+        assert_eq!(
+            sites[0].patients,
+            study.subject_native.patients.iter().collect::<Vec<_>>()
+        );
+        assert!(sites[1].patients.is_empty());
+        assert!(study.unassigned_patients().is_empty());
+    }
+
+    #[test]
+    fn checks_enrollment() {
+        let mut study = study();
+        study.subject_native.patients[1].site_name = "Old Name".to_string();
+        study.subject_native.patients[1].site_unique_id = "42".to_string();
+        let mut renamed = study.subject_native.patients[0].clone();
+        renamed.unique_id = "3".to_string();
+        renamed.site_name = "Some Site (old)".to_string();
+        study.subject_native.patients.push(renamed);
+
+        let issues = study.check_enrollment_with(|patient| patient.unique_id == "3");
+
+        assert_eq!(
+            issues,
+            vec![
+                EnrollmentIssue {
+                    site_unique_id: "1681574834910".to_string(),
+                    patient_unique_id: None,
+                    kind: EnrollmentIssueKind::PatientCount {
+                        reported: 4,
+                        actual: 2
+                    },
+                },
+                EnrollmentIssue {
+                    site_unique_id: "1681574834910".to_string(),
+                    patient_unique_id: None,
+                    kind: EnrollmentIssueKind::RandomizedCount {
+                        reported: 0,
+                        actual: 1
+                    },
+                },
+                EnrollmentIssue {
+                    site_unique_id: "1681574834910".to_string(),
+                    patient_unique_id: Some("3".to_string()),
+                    kind: EnrollmentIssueKind::SiteName {
+                        site_name: "Some Site".to_string(),
+                        patient_site_name: "Some Site (old)".to_string(),
+                    },
+                },
+                EnrollmentIssue {
+                    site_unique_id: "42".to_string(),
+                    patient_unique_id: Some("1681574994823".to_string()),
+                    kind: EnrollmentIssueKind::UnknownSite,
+                },
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&issues[0]).unwrap(),
+            serde_json::json!({
+                "siteUniqueId": "1681574834910",
+                "issue": "patient_count",
+                "reported": 4,
+                "actual": 2
+            })
+        );
+    }
+
+    fn links() -> UserLinks {
+        link_users(&subject_native(), &site_native(), &user_native())
+    }
+
+    #[test]
+    fn resolves_references_to_users() {
+        let links = links();
+
+        assert_eq!(links.users.len(), 2);
+        let user = links.user("1681162687395").unwrap();
+        assert_eq!(user.email.as_deref(), Some("paul@pbsdatasolutions.com"));
+        assert!(user
+            .references
+            .iter()
+            .all(|r| r.user_unique_id == "1681162687395"));
+        for kind in [
+            ReferenceKind::ValueEntered,
+            ReferenceKind::CommentEntered,
+            ReferenceKind::FormSigned,
+        ] {
+            assert!(user.references.iter().any(|r| r.kind == kind));
+        }
+        for record_kind in [RecordKind::Patient, RecordKind::Site, RecordKind::User] {
+            assert!(user.references.iter().any(|r| r.record_kind == record_kind));
+        }
+        let value = user
+            .references
+            .iter()
+            .find(|r| r.kind == ReferenceKind::ValueEntered)
+            .unwrap();
+        assert_eq!(value.recorded_name.as_deref(), Some("Paul Sanders"));
+        assert!(value.field_name.is_some());
+    }
+
+    #[test]
+    fn reports_dangling_references() {
+        let links = links();
+
+        assert!(!links.dangling.is_empty());
+        assert!(links
+            .dangling
+            .iter()
+            .all(|r| links.user(&r.user_unique_id).is_none()));
+        assert!(links
+            .dangling
+            .iter()
+            .any(|r| r.kind == ReferenceKind::FormLocked && r.user_unique_id == "1630429016609"));
+        // The administrator who created the users is not exported
+        assert!(links
+            .dangling
+            .iter()
+            .any(|r| r.user_unique_id == "1609858291483"));
+        assert!(links
+            .dangling
+            .iter()
+            .chain(links.users.iter().flat_map(|u| &u.references))
+            .all(|r| r.user_unique_id != "system" && !r.user_unique_id.is_empty()));
+    }
+}
@@ -1,48 +1,52 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, FixedOffset};
 
 #[cfg(feature = "python")]
 use pyo3::{
-    exceptions::PyValueError,
+    exceptions::{PyKeyError, PyValueError},
     prelude::*,
-    types::{PyDateTime, PyDict},
+    types::{PyCapsule, PyDateTime, PyDict, PyType},
 };
 
 #[cfg(feature = "python")]
-use crate::native::deserializers::{
-    default_string_none, deserialize_empty_string_as_none, to_py_datetime,
+use crate::native::anonymize::Anonymizer;
+#[cfg(feature = "python")]
+use crate::python::{
+    arrow_stream_capsule, dict_get, dict_get_elements, dict_get_extra, dict_get_model,
+    dict_get_models, from_json, hash, lifecycles_to_py, quality_to_py, reduce, to_dicts, to_pandas,
+    to_polars, to_py_err, to_py_json, FromPyDict, Repr,
 };
 
-use serde::{Deserialize, Serialize};
+use crate::native::deserializers::{default_string_none, deserialize_empty_string_as_none};
 
-pub use crate::native::common::{Category, Comment, Entry, Field, Form, Reason, State, Value};
+#[cfg(feature = "python")]
+use crate::native::deserializers::to_py_datetime;
 
-#[cfg(not(feature = "python"))]
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
-pub struct Patient {
-    #[serde(rename = "patientId")]
-    pub patient_id: String,
-    #[serde(rename = "uniqueId")]
-    pub unique_id: String,
-    #[serde(rename = "whenCreated")]
-    pub when_created: Option<DateTime<Utc>>,
-    pub creator: String,
-    #[serde(rename = "siteName")]
-    pub site_name: String,
-    #[serde(rename = "siteUniqueId")]
-    pub site_unique_id: String,
-    #[serde(rename = "lastLanguage")]
-    pub last_language: Option<String>,
-    #[serde(rename = "numberOfForms")]
-    pub number_of_forms: usize,
-    pub forms: Option<Vec<Form>>,
-}
+use schemars::{JsonSchema, Schema};
+use serde::{Deserialize, Serialize};
 
-impl Patient {
-    pub(crate) fn from_attributes(
-        attrs: HashMap<&str, &str>,
-    ) -> Result<Self, crate::errors::Error> {
+use crate::errors::Error;
+use crate::native::json::{from_versioned_json, to_versioned_json, versioned_json_schema};
+
+pub use crate::native::common::{
+    Category, Comment, Entry, ExportInfo, Field, Form, Reason, State, Value, XmlElement,
+};
+use crate::native::common::{FromAttributes, NativeRecord};
+
+impl FromAttributes for Patient {
+    const ATTRIBUTES: &'static [&'static str] = &[
+        "patientId",
+        "uniqueId",
+        "whenCreated",
+        "creator",
+        "siteName",
+        "siteUniqueId",
+        "lastLanguage",
+        "numberOfForms",
+    ];
+
+    fn from_attributes(attrs: HashMap<&str, &str>) -> Result<Self, crate::errors::Error> {
         let patient_id = attrs
             .get("patientId")
             .copied()
@@ -123,21 +127,36 @@ impl Patient {
             last_language,
             number_of_forms,
             forms: None,
+            extra: BTreeMap::new(),
+            extra_elements: Vec::new(),
         })
     }
 
-    pub(crate) fn set_forms(&mut self, forms: Vec<Form>) {
+    fn extra_mut(&mut self) -> &mut BTreeMap<String, String> {
+        &mut self.extra
+    }
+}
+
+impl NativeRecord for Patient {
+    const TAG: &'static str = "patient";
+    const KEEP_EMPTY_FIELDS: bool = false;
+
+    fn set_forms(&mut self, forms: Vec<Form>) {
         self.forms = if forms.is_empty() { None } else { Some(forms) };
     }
+
+    fn extra_elements_mut(&mut self) -> &mut Vec<XmlElement> {
+        &mut self.extra_elements
+    }
 }
 
-fn parse_datetime(s: &str) -> Result<DateTime<Utc>, crate::errors::Error> {
+fn parse_datetime(s: &str) -> Result<DateTime<FixedOffset>, crate::errors::Error> {
     if let Ok(dt) = chrono::DateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S %z") {
-        Ok(dt.with_timezone(&Utc))
+        Ok(dt)
     } else if let Ok(dt) = chrono::DateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%z") {
-        Ok(dt.with_timezone(&Utc))
+        Ok(dt)
     } else if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(s) {
-        Ok(dt.with_timezone(&Utc))
+        Ok(dt)
     } else {
         Err(crate::errors::Error::ParsingError(
             quick_xml::de::DeError::Custom(format!("Invalid datetime format: {}", s)),
@@ -145,10 +164,12 @@ fn parse_datetime(s: &str) -> Result<DateTime<Utc>, crate::errors::Error> {
     }
 }
 
-#[cfg(feature = "python")]
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(
+    feature = "python",
+    pyclass(set_all, eq, module = "prelude_xml_parser", from_py_object)
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash, JsonSchema)]
 #[serde(rename_all = "camelCase")]
-#[pyclass(skip_from_py_object)]
 pub struct Patient {
     #[serde(rename = "patientId")]
     #[serde(alias = "@patientId")]
@@ -161,7 +182,7 @@ pub struct Patient {
     #[serde(rename = "whenCreated")]
     #[serde(alias = "@whenCreated")]
     #[serde(alias = "whenCreated")]
-    pub when_created: Option<DateTime<Utc>>,
+    pub when_created: Option<DateTime<FixedOffset>>,
     #[serde(rename = "creator")]
     #[serde(alias = "@creator")]
     #[serde(alias = "creator")]
@@ -191,11 +212,54 @@ pub struct Patient {
 
     #[serde(alias = "form")]
     pub forms: Option<Vec<Form>>,
+
+    /// Attributes the parser does not know, kept when parsing with `ParseOptions::keep_unknown`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, String>,
+    /// Child elements the parser does not know, kept when parsing with
+    /// `ParseOptions::keep_unknown`.
+    #[serde(
+        rename = "extraElements",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub extra_elements: Vec<XmlElement>,
 }
 
 #[cfg(feature = "python")]
 #[pymethods]
 impl Patient {
+    #[new]
+    #[pyo3(signature = (patient_id, unique_id, creator, site_name, site_unique_id, number_of_forms, when_created=None, last_language=None, forms=None, extra=None, extra_elements=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        patient_id: String,
+        unique_id: String,
+        creator: String,
+        site_name: String,
+        site_unique_id: String,
+        number_of_forms: usize,
+        when_created: Option<DateTime<FixedOffset>>,
+        last_language: Option<String>,
+        forms: Option<Vec<Form>>,
+        extra: Option<BTreeMap<String, String>>,
+        extra_elements: Option<Vec<XmlElement>>,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            patient_id,
+            unique_id,
+            when_created,
+            creator,
+            site_name,
+            site_unique_id,
+            last_language,
+            number_of_forms,
+            forms,
+            extra: extra.unwrap_or_default(),
+            extra_elements: extra_elements.unwrap_or_default(),
+        })
+    }
+
     #[getter]
     fn patient_id(&self) -> PyResult<String> {
         Ok(self.patient_id.clone())
@@ -239,11 +303,26 @@ impl Patient {
         Ok(self.number_of_forms)
     }
 
+    /// A copy of the patient's forms; assign a changed copy back to update the patient.
     #[getter]
     fn forms(&self) -> PyResult<Option<Vec<Form>>> {
         Ok(self.forms.clone())
     }
 
+    /// A copy of the patient's unknown attributes; assign a changed copy back to update the
+    /// patient.
+    #[getter]
+    fn extra(&self) -> PyResult<BTreeMap<String, String>> {
+        Ok(self.extra.clone())
+    }
+
+    /// A copy of the patient's unknown child elements; assign a changed copy back to update the
+    /// patient.
+    #[getter]
+    fn extra_elements(&self) -> PyResult<Vec<XmlElement>> {
+        Ok(self.extra_elements.clone())
+    }
+
     pub fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
         let dict = PyDict::new(py);
         dict.set_item("patient_id", &self.patient_id)?;
@@ -272,20 +351,79 @@ impl Patient {
             dict.set_item("forms", py.None())?;
         }
 
+        dict.set_item("extra", &self.extra)?;
+        dict.set_item("extra_elements", to_dicts(py, &self.extra_elements)?)?;
+
         Ok(dict)
     }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(Repr::new(py, "Patient")
+            .field("patient_id", &self.patient_id)?
+            .field("unique_id", &self.unique_id)?
+            .field("site_name", &self.site_name)?
+            .items("forms", self.forms.as_ref().map(Vec::len))
+            .finish())
+    }
+
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, (String,))> {
+        reduce(slf)
+    }
+
+    fn __hash__(&self) -> u64 {
+        hash(self)
+    }
+
+    /// Create an instance from a dictionary shaped like the output of `to_dict`
+    #[classmethod]
+    fn from_dict(_cls: &Bound<'_, PyType>, data: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Self::from_py_dict(data)
+    }
+
+    /// Create an instance from a JSON string
+    #[classmethod]
+    fn from_json(_cls: &Bound<'_, PyType>, json: &str) -> PyResult<Self> {
+        from_json(json)
+    }
+}
+
+#[cfg(feature = "python")]
+impl FromPyDict for Patient {
+    fn from_py_dict(dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Ok(Self {
+            patient_id: dict_get(dict, "patient_id")?,
+            unique_id: dict_get(dict, "unique_id")?,
+            when_created: dict_get(dict, "when_created")?,
+            creator: dict_get(dict, "creator")?,
+            site_name: dict_get(dict, "site_name")?,
+            site_unique_id: dict_get(dict, "site_unique_id")?,
+            last_language: dict_get(dict, "last_language")?,
+            number_of_forms: dict_get(dict, "number_of_forms")?,
+            forms: dict_get_models(dict, "forms")?,
+            extra: dict_get_extra(dict)?,
+            extra_elements: dict_get_elements(dict)?,
+        })
+    }
 }
 
-#[cfg(not(feature = "python"))]
 /// Contains the information from the Prelude native subject XML.
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "python",
+    pyclass(get_all, set_all, eq, module = "prelude_xml_parser", from_py_object)
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash, JsonSchema)]
+#[serde(rename_all = "camelCase")]
 pub struct SubjectNative {
+    #[serde(alias = "patient")]
     pub patients: Vec<Patient>,
+
+    /// The attributes of the export's root element, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub export: Option<ExportInfo>,
 }
 
-#[cfg(not(feature = "python"))]
 impl SubjectNative {
-    /// Convert to a JSON string
+    /// Convert to a JSON string with a `schemaVersion` key, following [`Self::json_schema`]
     ///
     /// # Example
     ///
@@ -303,30 +441,57 @@ impl SubjectNative {
     /// assert!(json.contains("Labrador"));
     /// ```
     pub fn to_json(&self) -> serde_json::Result<String> {
-        let json = serde_json::to_string(&self)?;
+        to_versioned_json(self)
+    }
 
-        Ok(json)
+    /// Read JSON written by [`Self::to_json`]
+    ///
+    /// Documents without a `schemaVersion` are read as the current version, documents with
+    /// another version are rejected.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::path::Path;
+    ///
+    /// use prelude_xml_parser::{native::subject_native::SubjectNative, parse_subject_native_file};
+    ///
+    /// let native = parse_subject_native_file(Path::new("tests/assets/subject_native_small.xml")).unwrap();
+    /// let json = native.to_json().unwrap();
+    ///
+    /// assert_eq!(SubjectNative::from_json(&json).unwrap(), native);
+    /// ```
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        from_versioned_json(json)
     }
-}
 
-#[cfg(feature = "python")]
-/// Contains the information from the Prelude native subject XML.
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-#[serde(rename_all = "camelCase")]
-#[pyclass(get_all, skip_from_py_object)]
-pub struct SubjectNative {
-    #[serde(alias = "patient")]
-    pub patients: Vec<Patient>,
+    /// The JSON Schema of [`Self::to_json`] output
+    pub fn json_schema() -> Schema {
+        versioned_json_schema::<Self>()
+    }
 }
 
 #[cfg(feature = "python")]
 #[pymethods]
 impl SubjectNative {
+    #[new]
+    #[pyo3(signature = (patients, export=None))]
+    fn new(patients: Vec<Patient>, export: Option<ExportInfo>) -> PyResult<Self> {
+        Ok(Self { patients, export })
+    }
+
+    /// A copy of the native's patients; assign a changed copy back to update the native.
     #[getter]
-    fn sites(&self) -> PyResult<Vec<Patient>> {
+    fn patients(&self) -> PyResult<Vec<Patient>> {
         Ok(self.patients.clone())
     }
 
+    /// A copy of the native's export metadata; assign a changed copy back to update the native.
+    #[getter]
+    fn export(&self) -> PyResult<Option<ExportInfo>> {
+        Ok(self.export.clone())
+    }
+
     /// Convert the class instance to a dictionary
     fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
         let dict = PyDict::new(py);
@@ -336,14 +501,179 @@ impl SubjectNative {
             patient_dicts.push(patient_dict);
         }
         dict.set_item("patients", patient_dicts)?;
+        dict.set_item(
+            "export",
+            self.export.as_ref().map(|e| e.to_dict(py)).transpose()?,
+        )?;
         Ok(dict)
     }
 
     /// Convert the class instance to a JSON string
-    fn to_json(&self) -> PyResult<String> {
-        serde_json::to_string(&self)
+    #[pyo3(name = "to_json")]
+    fn py_to_json(&self) -> PyResult<String> {
+        self.to_json()
             .map_err(|_| PyErr::new::<PyValueError, _>("Error converting to JSON"))
     }
+
+    /// Combine exports of the same study into one, returning the merged native and the
+    /// conflicting entries as dictionaries
+    #[staticmethod]
+    #[pyo3(name = "merge")]
+    fn py_merge<'py>(py: Python<'py>, natives: Vec<Self>) -> PyResult<(Self, Bound<'py, PyAny>)> {
+        let merged = py.detach(|| Self::merge(natives));
+        Ok((merged.native, to_py_json(py, &merged.conflicts)?))
+    }
+
+    /// List the changes from this export to a newer export as dictionaries
+    #[pyo3(name = "diff")]
+    fn py_diff<'py>(&self, py: Python<'py>, newer: &Self) -> PyResult<Bound<'py, PyAny>> {
+        to_py_json(py, &py.detach(|| self.diff(newer)))
+    }
+
+    /// Write the native back out as Prelude native XML
+    #[pyo3(name = "to_xml")]
+    fn py_to_xml(&self, py: Python<'_>) -> PyResult<String> {
+        py.detach(|| self.to_xml()).map_err(|e| to_py_err(py, e))
+    }
+
+    /// Work out the workflow history of every form as dictionaries
+    #[pyo3(
+        name = "form_lifecycles",
+        signature = (required_signatures=None, exempt_states=None, as_of=None)
+    )]
+    fn py_form_lifecycles<'py>(
+        &self,
+        py: Python<'py>,
+        required_signatures: Option<Vec<String>>,
+        exempt_states: Option<Vec<String>>,
+        as_of: Option<DateTime<FixedOffset>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        lifecycles_to_py(
+            py,
+            |options| self.form_lifecycles(options),
+            None,
+            required_signatures,
+            exempt_states,
+            as_of,
+        )
+    }
+
+    /// Summarize the form lifecycles per record or per site
+    #[pyo3(
+        name = "lifecycle_summary",
+        signature = (by="record", required_signatures=None, exempt_states=None, as_of=None)
+    )]
+    fn py_lifecycle_summary<'py>(
+        &self,
+        py: Python<'py>,
+        by: &str,
+        required_signatures: Option<Vec<String>>,
+        exempt_states: Option<Vec<String>>,
+        as_of: Option<DateTime<FixedOffset>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        lifecycles_to_py(
+            py,
+            |options| self.form_lifecycles(options),
+            Some(by),
+            required_signatures,
+            exempt_states,
+            as_of,
+        )
+    }
+
+    /// List the forms with errors, warnings, or invalid fields as dictionaries
+    #[pyo3(name = "quality_issues")]
+    fn py_quality_issues<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        quality_to_py(py, || self.quality_issues(), None)
+    }
+
+    /// Summarize the data-quality issues per record, site, form, or field
+    #[pyo3(name = "quality_summary", signature = (by="record"))]
+    fn py_quality_summary<'py>(&self, py: Python<'py>, by: &str) -> PyResult<Bound<'py, PyAny>> {
+        quality_to_py(py, || self.quality_issues(), Some(by))
+    }
+
+    /// List the comments on every field with their location as dictionaries, oldest first
+    #[pyo3(name = "comments")]
+    fn py_comments<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let comments = py.detach(|| self.comments());
+        to_py_json(py, &comments)
+    }
+
+    /// Compute entry latency and data-change metrics in total, per site and per user as a
+    /// dictionary
+    #[pyo3(name = "metrics")]
+    fn py_metrics<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let metrics = py.detach(|| self.metrics());
+        to_py_json(py, &metrics)
+    }
+
+    /// De-identify the native with an `Anonymizer`
+    #[pyo3(name = "anonymize")]
+    fn py_anonymize(&self, py: Python<'_>, anonymizer: &Anonymizer) -> Self {
+        py.detach(|| self.anonymize(anonymizer))
+    }
+
+    /// Export the field entries as an Arrow stream, one row per entry.
+    ///
+    /// The requested schema is ignored, the stream always uses the flat entry schema.
+    #[pyo3(signature = (requested_schema=None))]
+    fn __arrow_c_stream__<'py>(
+        &self,
+        py: Python<'py>,
+        requested_schema: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyCapsule>> {
+        let _ = requested_schema;
+        arrow_stream_capsule(py, py.detach(|| self.to_record_batch()))
+    }
+
+    /// Convert the field entries to a pandas DataFrame, one row per entry. Requires pyarrow.
+    fn to_pandas<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyAny>> {
+        to_pandas(slf.as_any())
+    }
+
+    /// Convert the field entries to a Polars DataFrame, one row per entry.
+    fn to_polars<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyAny>> {
+        to_polars(slf.as_any())
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(Repr::new(py, "SubjectNative")
+            .items("patients", Some(self.patients.len()))
+            .finish())
+    }
+
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, (String,))> {
+        reduce(slf)
+    }
+
+    fn __hash__(&self) -> u64 {
+        hash(self)
+    }
+
+    /// Create an instance from a dictionary shaped like the output of `to_dict`
+    #[classmethod]
+    fn from_dict(_cls: &Bound<'_, PyType>, data: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Self::from_py_dict(data)
+    }
+
+    /// Create an instance from a JSON string written by `to_json`
+    #[classmethod]
+    #[pyo3(name = "from_json")]
+    fn py_from_json(_cls: &Bound<'_, PyType>, json: &str) -> PyResult<Self> {
+        Self::from_json(json).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+}
+
+#[cfg(feature = "python")]
+impl FromPyDict for SubjectNative {
+    fn from_py_dict(dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Ok(Self {
+            patients: dict_get_models(dict, "patients")?
+                .ok_or_else(|| PyKeyError::new_err("patients"))?,
+            export: dict_get_model(dict, "export")?,
+        })
+    }
 }
 
 #[cfg(test)]
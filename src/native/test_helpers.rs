@@ -0,0 +1,34 @@
+//! Fixtures shared by the tests of the native modules.
+
+use std::path::Path;
+
+use crate::{
+    native::{
+        common::Field, site_native::SiteNative, subject_native::SubjectNative,
+        user_native::UserNative,
+    },
+    parse_site_native_file, parse_subject_native_file, parse_user_native_file,
+};
+
+pub(crate) fn subject_native() -> SubjectNative {
+    parse_subject_native_file(Path::new("tests/assets/subject_native.xml")).unwrap()
+}
+
+pub(crate) fn site_native() -> SiteNative {
+    parse_site_native_file(Path::new("tests/assets/site_native.xml")).unwrap()
+}
+
+pub(crate) fn user_native() -> UserNative {
+    parse_user_native_file(Path::new("tests/assets/user_native.xml")).unwrap()
+}
+
+/// The first field of the first form of the first patient.
+pub(crate) fn first_field(native: &mut SubjectNative) -> &mut Field {
+    &mut native.patients[0].forms.as_mut().unwrap()[0]
+        .categories
+        .as_mut()
+        .unwrap()[0]
+        .fields
+        .as_mut()
+        .unwrap()[0]
+}
@@ -1,15 +1,41 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
+#[cfg(feature = "python")]
+use chrono::{DateTime, FixedOffset};
+
+use schemars::{JsonSchema, Schema};
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "python")]
-use pyo3::{exceptions::PyValueError, prelude::*, types::PyDict};
+use pyo3::{
+    exceptions::{PyKeyError, PyValueError},
+    prelude::*,
+    types::{PyCapsule, PyDict, PyType},
+};
 
-pub use crate::native::common::{Category, Comment, Entry, Field, Form, Reason, State, Value};
+#[cfg(feature = "python")]
+use crate::native::anonymize::Anonymizer;
+#[cfg(feature = "python")]
+use crate::python::{
+    arrow_stream_capsule, dict_get, dict_get_elements, dict_get_extra, dict_get_model,
+    dict_get_models, from_json, hash, lifecycles_to_py, quality_to_py, reduce, to_dicts, to_pandas,
+    to_polars, to_py_err, to_py_json, FromPyDict, Repr,
+};
+
+use crate::errors::Error;
+use crate::native::json::{from_versioned_json, to_versioned_json, versioned_json_schema};
+
+pub use crate::native::common::{
+    Category, Comment, Entry, ExportInfo, Field, Form, Reason, State, Value, XmlElement,
+};
+use crate::native::common::{FromAttributes, NativeRecord};
 use crate::native::deserializers::{default_string_none, deserialize_empty_string_as_none};
 
-#[cfg(not(feature = "python"))]
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(
+    feature = "python",
+    pyclass(get_all, set_all, eq, module = "prelude_xml_parser", from_py_object)
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct User {
     #[serde(rename = "uniqueId")]
@@ -36,13 +62,25 @@ pub struct User {
 
     #[serde(alias = "form")]
     pub forms: Option<Vec<Form>>,
+
+    /// Attributes the parser does not know, kept when parsing with `ParseOptions::keep_unknown`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, String>,
+    /// Child elements the parser does not know, kept when parsing with
+    /// `ParseOptions::keep_unknown`.
+    #[serde(
+        rename = "extraElements",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub extra_elements: Vec<XmlElement>,
 }
 
-#[cfg(not(feature = "python"))]
-impl User {
-    pub(crate) fn from_attributes(
-        attrs: HashMap<&str, &str>,
-    ) -> Result<Self, crate::errors::Error> {
+impl FromAttributes for User {
+    const ATTRIBUTES: &'static [&'static str] =
+        &["uniqueId", "lastLanguage", "creator", "numberOfForms"];
+
+    fn from_attributes(attrs: HashMap<&str, &str>) -> Result<Self, crate::errors::Error> {
         let unique_id = attrs
             .get("uniqueId")
             .copied()
@@ -79,97 +117,93 @@ impl User {
             creator,
             number_of_forms,
             forms: None,
+            extra: BTreeMap::new(),
+            extra_elements: Vec::new(),
         })
     }
 
-    pub(crate) fn set_forms(&mut self, forms: Vec<Form>) {
-        self.forms = if forms.is_empty() { None } else { Some(forms) };
+    fn extra_mut(&mut self) -> &mut BTreeMap<String, String> {
+        &mut self.extra
     }
 }
 
-#[cfg(feature = "python")]
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-#[serde(rename_all = "camelCase")]
-#[pyclass(get_all, skip_from_py_object)]
-pub struct User {
-    #[serde(rename = "uniqueId")]
-    #[serde(alias = "@uniqueId")]
-    #[serde(alias = "uniqueId")]
-    pub unique_id: String,
+impl NativeRecord for User {
+    const TAG: &'static str = "user";
 
-    #[serde(rename = "lastLanguage")]
-    #[serde(alias = "@lastLanguage")]
-    #[serde(alias = "lastLanguage")]
-    #[serde(
-        default = "default_string_none",
-        deserialize_with = "deserialize_empty_string_as_none"
-    )]
-    pub last_language: Option<String>,
-    #[serde(rename = "creator")]
-    #[serde(alias = "@creator")]
-    #[serde(alias = "creator")]
-    pub creator: String,
-    #[serde(rename = "numberOfForms")]
-    #[serde(alias = "@numberOfForms")]
-    #[serde(alias = "numberOfForms")]
-    pub number_of_forms: usize,
+    fn set_forms(&mut self, forms: Vec<Form>) {
+        self.forms = if forms.is_empty() { None } else { Some(forms) };
+    }
 
-    #[serde(alias = "form")]
-    pub forms: Option<Vec<Form>>,
+    fn extra_elements_mut(&mut self) -> &mut Vec<XmlElement> {
+        &mut self.extra_elements
+    }
 }
 
-#[cfg(feature = "python")]
 impl User {
-    pub(crate) fn from_attributes(
-        attrs: HashMap<&str, &str>,
-    ) -> Result<Self, crate::errors::Error> {
-        let unique_id = attrs
-            .get("uniqueId")
-            .copied()
-            .map(str::to_string)
-            .ok_or_else(|| {
-                crate::errors::Error::ParsingError(quick_xml::de::DeError::Custom(
-                    "Missing uniqueId".to_string(),
-                ))
-            })?;
+    /// The email address from the user's demographics form.
+    pub fn email(&self) -> Option<&str> {
+        self.demographic("email")
+    }
 
-        let last_language = attrs
-            .get("lastLanguage")
-            .filter(|s| !s.is_empty())
-            .map(|s| s.to_string());
+    /// The name from the user's demographics form, either its `name` field or its `first_name`
+    /// and `last_name` fields.
+    pub fn name(&self) -> Option<String> {
+        if let Some(name) = self.demographic("name") {
+            return Some(name.to_string());
+        }
 
-        let creator = attrs
-            .get("creator")
-            .copied()
-            .map(str::to_string)
-            .ok_or_else(|| {
-                crate::errors::Error::ParsingError(quick_xml::de::DeError::Custom(
-                    "Missing creator".to_string(),
-                ))
-            })?;
+        let parts: Vec<&str> = ["first_name", "last_name"]
+            .into_iter()
+            .filter_map(|field| self.demographic(field))
+            .collect();
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join(" "))
+        }
+    }
 
-        let number_of_forms = attrs
-            .get("numberOfForms")
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0);
+    /// The non-empty value of a field on the demographics form, e.g. "form.name.demographics".
+    fn demographic(&self, field_name: &str) -> Option<&str> {
+        self.forms
+            .iter()
+            .flatten()
+            .filter(|form| form.name.to_lowercase().ends_with("demographics"))
+            .flat_map(|form| form.categories.iter().flatten())
+            .flat_map(|category| category.fields.iter().flatten())
+            .filter(|field| field.name == field_name)
+            .flat_map(|field| field.entries.iter().flatten())
+            .filter_map(|entry| entry.value.as_ref())
+            .map(|value| value.value.trim())
+            .find(|value| !value.is_empty())
+    }
+}
 
-        Ok(User {
+#[cfg(feature = "python")]
+#[pymethods]
+impl User {
+    #[new]
+    #[pyo3(signature = (unique_id, creator, number_of_forms, last_language=None, forms=None, extra=None, extra_elements=None))]
+    fn new(
+        unique_id: String,
+        creator: String,
+        number_of_forms: usize,
+        last_language: Option<String>,
+        forms: Option<Vec<Form>>,
+        extra: Option<BTreeMap<String, String>>,
+        extra_elements: Option<Vec<XmlElement>>,
+    ) -> PyResult<Self> {
+        Ok(Self {
             unique_id,
             last_language,
             creator,
             number_of_forms,
-            forms: None,
+            forms,
+            extra: extra.unwrap_or_default(),
+            extra_elements: extra_elements.unwrap_or_default(),
         })
     }
 
-    pub(crate) fn set_forms(&mut self, forms: Vec<Form>) {
-        self.forms = if forms.is_empty() { None } else { Some(forms) };
-    }
-}
-
-#[cfg(feature = "python")]
-#[pymethods]
-impl User {
     #[getter]
     fn unique_id(&self) -> PyResult<String> {
         Ok(self.unique_id.clone())
@@ -185,11 +219,24 @@ impl User {
         Ok(self.creator.clone())
     }
 
+    /// A copy of the user's forms; assign a changed copy back to update the user.
     #[getter]
     fn forms(&self) -> PyResult<Option<Vec<Form>>> {
         Ok(self.forms.clone())
     }
 
+    /// A copy of the user's unknown attributes; assign a changed copy back to update the user.
+    #[getter]
+    fn extra(&self) -> PyResult<BTreeMap<String, String>> {
+        Ok(self.extra.clone())
+    }
+
+    /// A copy of the user's unknown child elements; assign a changed copy back to update the user.
+    #[getter]
+    fn extra_elements(&self) -> PyResult<Vec<XmlElement>> {
+        Ok(self.extra_elements.clone())
+    }
+
     pub fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
         let dict = PyDict::new(py);
         dict.set_item("unique_id", &self.unique_id)?;
@@ -208,22 +255,73 @@ impl User {
             dict.set_item("forms", py.None())?;
         }
 
+        dict.set_item("extra", &self.extra)?;
+        dict.set_item("extra_elements", to_dicts(py, &self.extra_elements)?)?;
+
         Ok(dict)
     }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(Repr::new(py, "User")
+            .field("unique_id", &self.unique_id)?
+            .items("forms", self.forms.as_ref().map(Vec::len))
+            .finish())
+    }
+
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, (String,))> {
+        reduce(slf)
+    }
+
+    fn __hash__(&self) -> u64 {
+        hash(self)
+    }
+
+    /// Create an instance from a dictionary shaped like the output of `to_dict`
+    #[classmethod]
+    fn from_dict(_cls: &Bound<'_, PyType>, data: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Self::from_py_dict(data)
+    }
+
+    /// Create an instance from a JSON string
+    #[classmethod]
+    fn from_json(_cls: &Bound<'_, PyType>, json: &str) -> PyResult<Self> {
+        from_json(json)
+    }
+}
+
+#[cfg(feature = "python")]
+impl FromPyDict for User {
+    fn from_py_dict(dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Ok(Self {
+            unique_id: dict_get(dict, "unique_id")?,
+            last_language: dict_get(dict, "last_language")?,
+            creator: dict_get(dict, "creator")?,
+            number_of_forms: dict_get(dict, "number_of_forms")?,
+            forms: dict_get_models(dict, "forms")?,
+            extra: dict_get_extra(dict)?,
+            extra_elements: dict_get_elements(dict)?,
+        })
+    }
 }
 
-#[cfg(not(feature = "python"))]
 /// Contains the information from the Prelude native user XML.
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(
+    feature = "python",
+    pyclass(get_all, set_all, eq, module = "prelude_xml_parser", from_py_object)
+)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct UserNative {
     #[serde(alias = "user")]
     pub users: Vec<User>,
+
+    /// The attributes of the export's root element, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub export: Option<ExportInfo>,
 }
 
-#[cfg(not(feature = "python"))]
 impl UserNative {
-    /// Convert to a JSON string
+    /// Convert to a JSON string with a `schemaVersion` key, following [`Self::json_schema`]
     ///
     /// # Example
     ///
@@ -240,30 +338,57 @@ impl UserNative {
     /// assert!(json.contains("\"value\":\"jazz@artemis.com\""));
     /// ```
     pub fn to_json(&self) -> serde_json::Result<String> {
-        let json = serde_json::to_string(&self)?;
+        to_versioned_json(self)
+    }
 
-        Ok(json)
+    /// Read JSON written by [`Self::to_json`]
+    ///
+    /// Documents without a `schemaVersion` are read as the current version, documents with
+    /// another version are rejected.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::path::Path;
+    ///
+    /// use prelude_xml_parser::{native::user_native::UserNative, parse_user_native_file};
+    ///
+    /// let native = parse_user_native_file(Path::new("tests/assets/user_native_small.xml")).unwrap();
+    /// let json = native.to_json().unwrap();
+    ///
+    /// assert_eq!(UserNative::from_json(&json).unwrap(), native);
+    /// ```
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        from_versioned_json(json)
     }
-}
 
-#[cfg(feature = "python")]
-/// Contains the information from the Prelude native user XML.
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-#[serde(rename_all = "camelCase")]
-#[pyclass(get_all, skip_from_py_object)]
-pub struct UserNative {
-    #[serde(alias = "user")]
-    pub users: Vec<User>,
+    /// The JSON Schema of [`Self::to_json`] output
+    pub fn json_schema() -> Schema {
+        versioned_json_schema::<Self>()
+    }
 }
 
 #[cfg(feature = "python")]
 #[pymethods]
 impl UserNative {
+    #[new]
+    #[pyo3(signature = (users, export=None))]
+    fn new(users: Vec<User>, export: Option<ExportInfo>) -> PyResult<Self> {
+        Ok(Self { users, export })
+    }
+
+    /// A copy of the native's users; assign a changed copy back to update the native.
     #[getter]
     fn users(&self) -> PyResult<Vec<User>> {
         Ok(self.users.clone())
     }
 
+    /// A copy of the native's export metadata; assign a changed copy back to update the native.
+    #[getter]
+    fn export(&self) -> PyResult<Option<ExportInfo>> {
+        Ok(self.export.clone())
+    }
+
     /// Convert the class instance to a dictionary
     fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
         let dict = PyDict::new(py);
@@ -273,14 +398,170 @@ impl UserNative {
             user_dicts.push(user_dict);
         }
         dict.set_item("users", user_dicts)?;
+        dict.set_item(
+            "export",
+            self.export.as_ref().map(|e| e.to_dict(py)).transpose()?,
+        )?;
         Ok(dict)
     }
 
     /// Convert the class instance to a JSON string
-    fn to_json(&self) -> PyResult<String> {
-        serde_json::to_string(&self)
+    #[pyo3(name = "to_json")]
+    fn py_to_json(&self) -> PyResult<String> {
+        self.to_json()
             .map_err(|_| PyErr::new::<PyValueError, _>("Error converting to JSON"))
     }
+
+    /// Combine exports of the same study into one, returning the merged native and the
+    /// conflicting entries as dictionaries
+    #[staticmethod]
+    #[pyo3(name = "merge")]
+    fn py_merge<'py>(py: Python<'py>, natives: Vec<Self>) -> PyResult<(Self, Bound<'py, PyAny>)> {
+        let merged = py.detach(|| Self::merge(natives));
+        Ok((merged.native, to_py_json(py, &merged.conflicts)?))
+    }
+
+    /// List the changes from this export to a newer export as dictionaries
+    #[pyo3(name = "diff")]
+    fn py_diff<'py>(&self, py: Python<'py>, newer: &Self) -> PyResult<Bound<'py, PyAny>> {
+        to_py_json(py, &py.detach(|| self.diff(newer)))
+    }
+
+    /// Write the native back out as Prelude native XML
+    #[pyo3(name = "to_xml")]
+    fn py_to_xml(&self, py: Python<'_>) -> PyResult<String> {
+        py.detach(|| self.to_xml()).map_err(|e| to_py_err(py, e))
+    }
+
+    /// Work out the workflow history of every form as dictionaries
+    #[pyo3(
+        name = "form_lifecycles",
+        signature = (required_signatures=None, exempt_states=None, as_of=None)
+    )]
+    fn py_form_lifecycles<'py>(
+        &self,
+        py: Python<'py>,
+        required_signatures: Option<Vec<String>>,
+        exempt_states: Option<Vec<String>>,
+        as_of: Option<DateTime<FixedOffset>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        lifecycles_to_py(
+            py,
+            |options| self.form_lifecycles(options),
+            None,
+            required_signatures,
+            exempt_states,
+            as_of,
+        )
+    }
+
+    /// Summarize the form lifecycles per record or per site
+    #[pyo3(
+        name = "lifecycle_summary",
+        signature = (by="record", required_signatures=None, exempt_states=None, as_of=None)
+    )]
+    fn py_lifecycle_summary<'py>(
+        &self,
+        py: Python<'py>,
+        by: &str,
+        required_signatures: Option<Vec<String>>,
+        exempt_states: Option<Vec<String>>,
+        as_of: Option<DateTime<FixedOffset>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        lifecycles_to_py(
+            py,
+            |options| self.form_lifecycles(options),
+            Some(by),
+            required_signatures,
+            exempt_states,
+            as_of,
+        )
+    }
+
+    /// List the forms with errors, warnings, or invalid fields as dictionaries
+    #[pyo3(name = "quality_issues")]
+    fn py_quality_issues<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        quality_to_py(py, || self.quality_issues(), None)
+    }
+
+    /// Summarize the data-quality issues per record, site, form, or field
+    #[pyo3(name = "quality_summary", signature = (by="record"))]
+    fn py_quality_summary<'py>(&self, py: Python<'py>, by: &str) -> PyResult<Bound<'py, PyAny>> {
+        quality_to_py(py, || self.quality_issues(), Some(by))
+    }
+
+    /// List the comments on every field with their location as dictionaries, oldest first
+    #[pyo3(name = "comments")]
+    fn py_comments<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let comments = py.detach(|| self.comments());
+        to_py_json(py, &comments)
+    }
+
+    /// De-identify the native with an `Anonymizer`
+    #[pyo3(name = "anonymize")]
+    fn py_anonymize(&self, py: Python<'_>, anonymizer: &Anonymizer) -> Self {
+        py.detach(|| self.anonymize(anonymizer))
+    }
+
+    /// Export the field entries as an Arrow stream, one row per entry.
+    ///
+    /// The requested schema is ignored, the stream always uses the flat entry schema.
+    #[pyo3(signature = (requested_schema=None))]
+    fn __arrow_c_stream__<'py>(
+        &self,
+        py: Python<'py>,
+        requested_schema: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyCapsule>> {
+        let _ = requested_schema;
+        arrow_stream_capsule(py, py.detach(|| self.to_record_batch()))
+    }
+
+    /// Convert the field entries to a pandas DataFrame, one row per entry. Requires pyarrow.
+    fn to_pandas<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyAny>> {
+        to_pandas(slf.as_any())
+    }
+
+    /// Convert the field entries to a Polars DataFrame, one row per entry.
+    fn to_polars<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyAny>> {
+        to_polars(slf.as_any())
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(Repr::new(py, "UserNative")
+            .items("users", Some(self.users.len()))
+            .finish())
+    }
+
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, (String,))> {
+        reduce(slf)
+    }
+
+    fn __hash__(&self) -> u64 {
+        hash(self)
+    }
+
+    /// Create an instance from a dictionary shaped like the output of `to_dict`
+    #[classmethod]
+    fn from_dict(_cls: &Bound<'_, PyType>, data: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Self::from_py_dict(data)
+    }
+
+    /// Create an instance from a JSON string written by `to_json`
+    #[classmethod]
+    #[pyo3(name = "from_json")]
+    fn py_from_json(_cls: &Bound<'_, PyType>, json: &str) -> PyResult<Self> {
+        Self::from_json(json).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+}
+
+#[cfg(feature = "python")]
+impl FromPyDict for UserNative {
+    fn from_py_dict(dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Ok(Self {
+            users: dict_get_models(dict, "users")?.ok_or_else(|| PyKeyError::new_err("users"))?,
+            export: dict_get_model(dict, "export")?,
+        })
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+
+use crate::{errors::Error, native::common::XmlElement};
+
+/// Callbacks for the elements of a native export, driven by the parse engine in document order.
+///
+/// Use this with [`crate::visit_native_string`] or [`crate::visit_native_file`] to read a native
+/// without building the models, e.g. to count entries or to find one field. The
+/// `parse_*_native_*` functions are built on the same engine with a visitor that builds the
+/// models.
+///
+/// Attributes and text are passed unescaped, and attributes can be read with the `from_attributes`
+/// of the models if needed. Every callback does nothing by default, and an error returned from a
+/// callback stops the parse.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use prelude_xml_parser::{
+///     errors::Error, native::visitor::NativeVisitor, visit_native_string, ParseOptions,
+/// };
+///
+/// #[derive(Default)]
+/// struct BreedCounter {
+///     in_breed: bool,
+///     labradors: usize,
+/// }
+///
+/// impl NativeVisitor for BreedCounter {
+///     fn on_field(&mut self, attributes: HashMap<&str, &str>) -> Result<(), Error> {
+///         self.in_breed = attributes.get("name") == Some(&"breed");
+///         Ok(())
+///     }
+///
+///     fn on_value(&mut self, _: HashMap<&str, &str>, value: &str) -> Result<(), Error> {
+///         if self.in_breed && value == "Labrador" {
+///             self.labradors += 1;
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let xml = std::fs::read_to_string("tests/assets/subject_native.xml").unwrap();
+/// let mut counter = BreedCounter::default();
+/// visit_native_string(&xml, "patient", &mut counter, &ParseOptions::default()).unwrap();
+///
+/// assert!(counter.labradors >= 1);
+/// ```
+#[allow(unused_variables)]
+pub trait NativeVisitor {
+    /// A record starts, e.g. a `<patient>` of a subject native.
+    fn on_record_start(&mut self, attributes: HashMap<&str, &str>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// The current record ends.
+    fn on_record_end(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// A form of the current record starts.
+    fn on_form(&mut self, attributes: HashMap<&str, &str>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// The current form ends.
+    fn on_form_end(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// A `<state>` of the current form.
+    fn on_state(&mut self, attributes: HashMap<&str, &str>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// The `<lockState>` of the current form.
+    fn on_lock_state(&mut self, attributes: HashMap<&str, &str>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// A category of the current form starts.
+    fn on_category(&mut self, attributes: HashMap<&str, &str>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// The current category ends.
+    fn on_category_end(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// A field of the current category starts.
+    fn on_field(&mut self, attributes: HashMap<&str, &str>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// The current field ends.
+    fn on_field_end(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// A self-closing `<field .../>`, which has no entries or comments. Calls `on_field` and
+    /// `on_field_end` by default.
+    fn on_empty_field(&mut self, attributes: HashMap<&str, &str>) -> Result<(), Error> {
+        self.on_field(attributes)?;
+        self.on_field_end()
+    }
+
+    /// An entry of the current field starts.
+    fn on_entry(&mut self, attributes: HashMap<&str, &str>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// The current entry ends.
+    fn on_entry_end(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// A comment of the current field starts.
+    fn on_comment(&mut self, attributes: HashMap<&str, &str>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// The current comment ends.
+    fn on_comment_end(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// A `<value>` of the current entry or comment, with its unescaped and trimmed text.
+    fn on_value(&mut self, attributes: HashMap<&str, &str>, value: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// The `<reason>` of the current entry or comment, with its unescaped and trimmed text.
+    fn on_reason(&mut self, attributes: HashMap<&str, &str>, value: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// An element the parser does not know, inside the current record. Only called when parsing
+    /// with `ParseOptions::keep_unknown`.
+    fn on_unknown_element(&mut self, element: XmlElement) -> Result<(), Error> {
+        Ok(())
+    }
+}
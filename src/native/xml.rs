@@ -0,0 +1,609 @@
+use std::{collections::BTreeMap, io};
+
+use chrono::{DateTime, FixedOffset};
+use quick_xml::{
+    events::{BytesDecl, BytesText, Event},
+    Writer,
+};
+
+use crate::errors::Error;
+use crate::native::{
+    common::{
+        Category, Comment, Entry, ExportInfo, Field, Form, LockState, Reason, State, Value,
+        XmlElement,
+    },
+    site_native::{Site, SiteNative},
+    subject_native::{Patient, SubjectNative},
+    user_native::{User, UserNative},
+};
+
+type XmlWriter = Writer<Vec<u8>>;
+
+/// The format Prelude writes timestamps in, e.g. "2023-04-15 12:09:02 -0400".
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S %z";
+
+/// The format of the export date on the root element, e.g. "30-May-2024 10:35 -0500".
+const EXPORT_DATE_FORMAT: &str = "%d-%b-%Y %H:%M %z";
+
+impl SubjectNative {
+    /// Write the patients back out as Prelude native subject XML.
+    ///
+    /// The output can be read with [`crate::parse_subject_native_string`]. Fields without
+    /// entries or comments are written with start and end tags, and missing timestamps as empty
+    /// attributes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::path::Path;
+    ///
+    /// use prelude_xml_parser::{parse_subject_native_file, parse_subject_native_string};
+    ///
+    /// let native = parse_subject_native_file(Path::new("tests/assets/subject_native.xml")).unwrap();
+    /// let xml = native.to_xml().unwrap();
+    ///
+    /// assert_eq!(parse_subject_native_string(&xml).unwrap(), native);
+    /// ```
+    pub fn to_xml(&self) -> Result<String, Error> {
+        write_document(&self.export, |writer| {
+            self.patients
+                .iter()
+                .try_for_each(|patient| write_patient(writer, patient))
+        })
+    }
+}
+
+impl SiteNative {
+    /// Write the sites back out as Prelude native site XML.
+    ///
+    /// See [`SubjectNative::to_xml`].
+    pub fn to_xml(&self) -> Result<String, Error> {
+        write_document(&self.export, |writer| {
+            self.sites
+                .iter()
+                .try_for_each(|site| write_site(writer, site))
+        })
+    }
+}
+
+impl UserNative {
+    /// Write the users back out as Prelude native user XML.
+    ///
+    /// See [`SubjectNative::to_xml`].
+    pub fn to_xml(&self) -> Result<String, Error> {
+        write_document(&self.export, |writer| {
+            self.users
+                .iter()
+                .try_for_each(|user| write_user(writer, user))
+        })
+    }
+}
+
+fn write_document(
+    export: &Option<ExportInfo>,
+    write_records: impl FnOnce(&mut XmlWriter) -> io::Result<()>,
+) -> Result<String, Error> {
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+
+    let date = export
+        .as_ref()
+        .and_then(|e| e.date)
+        .map(|date| date.format(EXPORT_DATE_FORMAT).to_string());
+    let number_subjects_processed = export
+        .as_ref()
+        .map(|e| e.number_subjects_processed.to_string());
+    let mut root = writer.create_element("export_from_vision_EDC");
+    if let Some(export) = export {
+        root = root.with_attributes([
+            ("date", date.as_deref().unwrap_or_default()),
+            ("createdBy", export.created_by.as_str()),
+            ("role", export.role.as_str()),
+            (
+                "numberSubjectsProcessed",
+                number_subjects_processed.as_deref().unwrap_or_default(),
+            ),
+        ]);
+        root = root.with_attributes(extra(&export.extra));
+    }
+    root.write_inner_content(write_records)?;
+
+    String::from_utf8(writer.into_inner()).map_err(|_| Error::Unknown)
+}
+
+fn datetime(value: &Option<DateTime<FixedOffset>>) -> String {
+    value
+        .map(|dt| dt.format(DATETIME_FORMAT).to_string())
+        .unwrap_or_default()
+}
+
+fn boolean(value: bool) -> &'static str {
+    if value {
+        "true"
+    } else {
+        "false"
+    }
+}
+
+fn write_patient(writer: &mut XmlWriter, patient: &Patient) -> io::Result<()> {
+    let when_created = datetime(&patient.when_created);
+    let number_of_forms = patient.number_of_forms.to_string();
+    writer
+        .create_element("patient")
+        .with_attributes([
+            ("patientId", patient.patient_id.as_str()),
+            ("uniqueId", patient.unique_id.as_str()),
+            ("whenCreated", when_created.as_str()),
+            ("creator", patient.creator.as_str()),
+            ("siteName", patient.site_name.as_str()),
+            ("siteUniqueId", patient.site_unique_id.as_str()),
+            (
+                "lastLanguage",
+                patient.last_language.as_deref().unwrap_or_default(),
+            ),
+            ("numberOfForms", number_of_forms.as_str()),
+        ])
+        .with_attributes(extra(&patient.extra))
+        .write_inner_content(|writer| {
+            write_forms(writer, &patient.forms)?;
+            write_elements(writer, &patient.extra_elements)
+        })?;
+
+    Ok(())
+}
+
+fn write_site(writer: &mut XmlWriter, site: &Site) -> io::Result<()> {
+    let number_of_patients = site.number_of_patients.to_string();
+    let count_of_randomized_patients = site.count_of_randomized_patients.to_string();
+    let when_created = datetime(&site.when_created);
+    let number_of_forms = site.number_of_forms.to_string();
+    writer
+        .create_element("site")
+        .with_attributes([
+            ("name", site.name.as_str()),
+            ("uniqueId", site.unique_id.as_str()),
+            ("numberOfPatients", number_of_patients.as_str()),
+            (
+                "countOfRandomizedPatients",
+                count_of_randomized_patients.as_str(),
+            ),
+            ("whenCreated", when_created.as_str()),
+            ("creator", site.creator.as_str()),
+            ("numberOfForms", number_of_forms.as_str()),
+        ])
+        .with_attributes(extra(&site.extra))
+        .write_inner_content(|writer| {
+            write_forms(writer, &site.forms)?;
+            write_elements(writer, &site.extra_elements)
+        })?;
+
+    Ok(())
+}
+
+fn write_user(writer: &mut XmlWriter, user: &User) -> io::Result<()> {
+    let number_of_forms = user.number_of_forms.to_string();
+    writer
+        .create_element("user")
+        .with_attributes([
+            ("uniqueId", user.unique_id.as_str()),
+            (
+                "lastLanguage",
+                user.last_language.as_deref().unwrap_or_default(),
+            ),
+            ("creator", user.creator.as_str()),
+            ("numberOfForms", number_of_forms.as_str()),
+        ])
+        .with_attributes(extra(&user.extra))
+        .write_inner_content(|writer| {
+            write_forms(writer, &user.forms)?;
+            write_elements(writer, &user.extra_elements)
+        })?;
+
+    Ok(())
+}
+
+fn write_forms(writer: &mut XmlWriter, forms: &Option<Vec<Form>>) -> io::Result<()> {
+    forms
+        .iter()
+        .flatten()
+        .try_for_each(|form| write_form(writer, form))
+}
+
+fn write_form(writer: &mut XmlWriter, form: &Form) -> io::Result<()> {
+    let last_modified = datetime(&form.last_modified);
+    let when_created = form.when_created.to_string();
+    let date_time_changed = datetime(&form.date_time_changed);
+    let form_index = form.form_index.to_string();
+    writer
+        .create_element("form")
+        .with_attributes([
+            ("name", form.name.as_str()),
+            ("lastModified", last_modified.as_str()),
+            (
+                "whoLastModifiedName",
+                form.who_last_modified_name.as_deref().unwrap_or_default(),
+            ),
+            (
+                "whoLastModifiedRole",
+                form.who_last_modified_role.as_deref().unwrap_or_default(),
+            ),
+            ("whenCreated", when_created.as_str()),
+            ("hasErrors", boolean(form.has_errors)),
+            ("hasWarnings", boolean(form.has_warnings)),
+            ("locked", boolean(form.locked)),
+            ("user", form.user.as_deref().unwrap_or_default()),
+            ("dateTimeChanged", date_time_changed.as_str()),
+            ("formTitle", form.form_title.as_str()),
+            ("formIndex", form_index.as_str()),
+            ("formGroup", form.form_group.as_deref().unwrap_or_default()),
+            ("formState", form.form_state.as_str()),
+        ])
+        .with_attributes(extra(&form.extra))
+        .write_inner_content(|writer| {
+            for state in form.states.iter().flatten() {
+                write_state(writer, state)?;
+            }
+            if let Some(lock_state) = &form.lock_state {
+                write_lock_state(writer, lock_state)?;
+            }
+            for category in form.categories.iter().flatten() {
+                write_category(writer, category)?;
+            }
+
+            write_elements(writer, &form.extra_elements)
+        })?;
+
+    Ok(())
+}
+
+fn write_state(writer: &mut XmlWriter, state: &State) -> io::Result<()> {
+    let date_signed = datetime(&state.date_signed);
+    writer
+        .create_element("state")
+        .with_attributes([
+            ("value", state.value.as_str()),
+            ("signer", state.signer.as_str()),
+            ("signerUniqueId", state.signer_unique_id.as_str()),
+            ("dateSigned", date_signed.as_str()),
+        ])
+        .with_attributes(extra(&state.extra))
+        .write_empty()?;
+
+    Ok(())
+}
+
+fn write_lock_state(writer: &mut XmlWriter, lock_state: &LockState) -> io::Result<()> {
+    let date_time_changed = datetime(&lock_state.date_time_changed);
+    writer
+        .create_element("lockState")
+        .with_attributes([
+            ("locked", boolean(lock_state.locked)),
+            ("user", lock_state.user.as_deref().unwrap_or_default()),
+            (
+                "userUniqueId",
+                lock_state.user_unique_id.as_deref().unwrap_or_default(),
+            ),
+            ("dateTimeChanged", date_time_changed.as_str()),
+        ])
+        .with_attributes(extra(&lock_state.extra))
+        .write_empty()?;
+
+    Ok(())
+}
+
+fn write_category(writer: &mut XmlWriter, category: &Category) -> io::Result<()> {
+    let highest_index = category.highest_index.to_string();
+    writer
+        .create_element("category")
+        .with_attributes([
+            ("name", category.name.as_str()),
+            ("type", category.category_type.as_str()),
+            ("highestIndex", highest_index.as_str()),
+        ])
+        .with_attributes(extra(&category.extra))
+        .write_inner_content(|writer| {
+            for field in category.fields.iter().flatten() {
+                write_field(writer, field)?;
+            }
+
+            write_elements(writer, &category.extra_elements)
+        })?;
+
+    Ok(())
+}
+
+fn write_field(writer: &mut XmlWriter, field: &Field) -> io::Result<()> {
+    let when_created = datetime(&field.when_created);
+    writer
+        .create_element("field")
+        .with_attributes([
+            ("name", field.name.as_str()),
+            ("type", field.field_type.as_str()),
+            ("dataType", field.data_type.as_deref().unwrap_or_default()),
+            ("errorCode", field.error_code.as_str()),
+            ("whenCreated", when_created.as_str()),
+            ("keepHistory", boolean(field.keep_history)),
+        ])
+        .with_attributes(extra(&field.extra))
+        .write_inner_content(|writer| {
+            for entry in field.entries.iter().flatten() {
+                write_entry(writer, entry)?;
+            }
+            for comment in field.comments.iter().flatten() {
+                write_comment(writer, comment)?;
+            }
+
+            write_elements(writer, &field.extra_elements)
+        })?;
+
+    Ok(())
+}
+
+fn write_entry(writer: &mut XmlWriter, entry: &Entry) -> io::Result<()> {
+    let reviewed_by_when = datetime(&entry.reviewed_by_when);
+    let mut element = writer
+        .create_element("entry")
+        .with_attribute(("id", entry.entry_id.as_str()));
+    // Only entries that were reviewed carry the review attributes
+    if entry.reviewed_by.is_some()
+        || entry.reviewed_by_unique_id.is_some()
+        || entry.reviewed_by_when.is_some()
+    {
+        element = element.with_attributes([
+            (
+                "reviewedBy",
+                entry.reviewed_by.as_deref().unwrap_or_default(),
+            ),
+            (
+                "reviewedByUniqueId",
+                entry.reviewed_by_unique_id.as_deref().unwrap_or_default(),
+            ),
+            ("reviewedByWhen", reviewed_by_when.as_str()),
+        ]);
+    }
+    element
+        .with_attributes(extra(&entry.extra))
+        .write_inner_content(|writer| {
+            if let Some(value) = &entry.value {
+                write_value(writer, value)?;
+            }
+            if let Some(reason) = &entry.reason {
+                write_reason(writer, reason)?;
+            }
+
+            write_elements(writer, &entry.extra_elements)
+        })?;
+
+    Ok(())
+}
+
+fn write_comment(writer: &mut XmlWriter, comment: &Comment) -> io::Result<()> {
+    writer
+        .create_element("comment")
+        .with_attribute(("id", comment.comment_id.as_str()))
+        .with_attributes(extra(&comment.extra))
+        .write_inner_content(|writer| {
+            for value in comment.values() {
+                write_value(writer, value)?;
+            }
+            if let Some(reason) = &comment.reason {
+                write_reason(writer, reason)?;
+            }
+
+            write_elements(writer, &comment.extra_elements)
+        })?;
+
+    Ok(())
+}
+
+fn write_value(writer: &mut XmlWriter, value: &Value) -> io::Result<()> {
+    write_text_element(
+        writer,
+        "value",
+        &value.by,
+        &value.by_unique_id,
+        &value.role,
+        &value.when,
+        &value.value,
+        &value.extra,
+    )
+}
+
+fn write_reason(writer: &mut XmlWriter, reason: &Reason) -> io::Result<()> {
+    write_text_element(
+        writer,
+        "reason",
+        &reason.by,
+        &reason.by_unique_id,
+        &reason.role,
+        &reason.when,
+        &reason.value,
+        &reason.extra,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn write_text_element(
+    writer: &mut XmlWriter,
+    name: &str,
+    by: &str,
+    by_unique_id: &Option<String>,
+    role: &str,
+    when: &Option<DateTime<FixedOffset>>,
+    text: &str,
+    extra_attributes: &BTreeMap<String, String>,
+) -> io::Result<()> {
+    let when = datetime(when);
+    writer
+        .create_element(name)
+        .with_attributes([
+            ("by", by),
+            ("byUniqueId", by_unique_id.as_deref().unwrap_or_default()),
+            ("role", role),
+            ("when", when.as_str()),
+            ("xml:space", "preserve"),
+        ])
+        .with_attributes(extra(extra_attributes))
+        .write_text_content(BytesText::new(text))?;
+
+    Ok(())
+}
+
+/// The unknown attributes of a model, as kept by the parser.
+fn extra(extra: &BTreeMap<String, String>) -> impl Iterator<Item = (&str, &str)> {
+    extra
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+}
+
+fn write_elements(writer: &mut XmlWriter, elements: &[XmlElement]) -> io::Result<()> {
+    elements
+        .iter()
+        .try_for_each(|element| write_element(writer, element))
+}
+
+/// Writes an unknown element as it was read. Its text was kept escaped, so it is written as is.
+fn write_element(writer: &mut XmlWriter, element: &XmlElement) -> io::Result<()> {
+    let start = writer
+        .create_element(element.name.as_str())
+        .with_attributes(extra(&element.attributes));
+    if element.children.is_empty() {
+        if element.text.is_empty() {
+            start.write_empty()?;
+        } else {
+            start.write_text_content(BytesText::from_escaped(element.text.as_str()))?;
+        }
+    } else {
+        start.write_inner_content(|writer| {
+            if !element.text.is_empty() {
+                writer.write_event(Event::Text(BytesText::from_escaped(element.text.as_str())))?;
+            }
+            write_elements(writer, &element.children)
+        })?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::{
+        parse_site_native_file, parse_site_native_string, parse_subject_native_file,
+        parse_subject_native_file_with_options, parse_subject_native_string,
+        parse_subject_native_string_with_options, parse_user_native_file, parse_user_native_string,
+        ParseOptions,
+    };
+
+    #[test]
+    fn round_trips_natives() {
+        let subject =
+            parse_subject_native_file(Path::new("tests/assets/subject_native.xml")).unwrap();
+        let site = parse_site_native_file(Path::new("tests/assets/site_native.xml")).unwrap();
+        let user = parse_user_native_file(Path::new("tests/assets/user_native.xml")).unwrap();
+
+        assert_eq!(
+            parse_subject_native_string(&subject.to_xml().unwrap()).unwrap(),
+            subject
+        );
+        assert_eq!(
+            parse_site_native_string(&site.to_xml().unwrap()).unwrap(),
+            site
+        );
+        assert_eq!(
+            parse_user_native_string(&user.to_xml().unwrap()).unwrap(),
+            user
+        );
+    }
+
+    #[test]
+    fn round_trips_escaped_characters() {
+        let xml = r#"<export_from_vision_EDC date="30-May-2024 10:35 -0500" createdBy="Paul &quot;P&quot; Sanders" role="Project Manager" numberSubjectsProcessed="1">
+  <patient patientId="ABC-001" uniqueId="1681574905819" whenCreated="2023-04-15 12:09:02 -0400" creator="Paul Sanders" siteName="Some &amp; Site" siteUniqueId="1681574834910" lastLanguage="" numberOfForms="1">
+    <form name="day.0.form.name.demographics" lastModified="2023-04-15 12:09:15 -0400" whoLastModifiedName="Paul Sanders" whoLastModifiedRole="Project Manager" whenCreated="1681574905839" hasErrors="false" hasWarnings="false" locked="false" user="" dateTimeChanged="" formTitle="Demographics &lt;v2&gt;" formIndex="1" formGroup="Day 0" formState="In-Work">
+      <category name="Demographics" type="normal" highestIndex="0">
+        <field name="breed" type="text" dataType="string" errorCode="valid" whenCreated="2023-04-15 12:08:26 -0400" keepHistory="true">
+          <entry id="1">
+            <value by="Paul Sanders" byUniqueId="1681162687395" role="Project Manager" when="2023-04-15 12:09:02 -0400"> Cats &amp; Dogs &lt;3 &#x2764; </value>
+            <reason by="Paul Sanders" byUniqueId="1681162687395" role="Project Manager" when="2023-04-15 12:09:02 -0400">Said &quot;both&quot;</reason>
+          </entry>
+        </field>
+      </category>
+    </form>
+  </patient>
+</export_from_vision_EDC>"#;
+        let native = parse_subject_native_string(xml).unwrap();
+
+        let patient = &native.patients[0];
+        assert_eq!(patient.site_name, "Some & Site");
+        assert_eq!(
+            native.export.as_ref().unwrap().created_by,
+            "Paul \"P\" Sanders"
+        );
+        let form = &patient.forms.as_ref().unwrap()[0];
+        assert_eq!(form.form_title, "Demographics <v2>");
+        let entry = &form.categories.as_ref().unwrap()[0]
+            .fields
+            .as_ref()
+            .unwrap()[0]
+            .entries
+            .as_ref()
+            .unwrap()[0];
+        assert_eq!(
+            entry.value.as_ref().unwrap().value,
+            "Cats & Dogs <3 \u{2764}"
+        );
+        assert_eq!(entry.reason.as_ref().unwrap().value, "Said \"both\"");
+
+        let written = native.to_xml().unwrap();
+        assert!(written.contains("siteName=\"Some &amp; Site\""));
+        assert_eq!(parse_subject_native_string(&written).unwrap(), native);
+    }
+
+    #[test]
+    fn round_trips_unknown_attributes_and_elements() {
+        let options = ParseOptions { keep_unknown: true };
+        let native = parse_subject_native_file_with_options(
+            Path::new("tests/assets/subject_native_unknown.xml"),
+            &options,
+        )
+        .unwrap();
+
+        let xml = native.to_xml().unwrap();
+
+        assert!(xml.contains("<note>Signed on &lt;paper&gt;</note>"));
+        assert_eq!(
+            parse_subject_native_string_with_options(&xml, &options).unwrap(),
+            native
+        );
+    }
+
+    #[test]
+    fn writes_the_native_layout() {
+        let native =
+            parse_subject_native_file(Path::new("tests/assets/subject_native.xml")).unwrap();
+        let xml = native.to_xml().unwrap();
+
+        assert!(xml.starts_with(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<export_from_vision_EDC date=\"30-May-2024 10:35 -0500\" createdBy=\"Paul Sanders\""
+        ));
+        assert!(xml.contains(
+            "<value by=\"Paul Sanders\" byUniqueId=\"1681162687395\" role=\"Project Manager\" when=\"2023-04-15 12:09:02 -0400\" xml:space=\"preserve\">Labrador</value>"
+        ));
+    }
+
+    #[test]
+    fn writes_records_without_an_export() {
+        let native = SubjectNative {
+            patients: Vec::new(),
+            export: None,
+        };
+
+        assert_eq!(
+            native.to_xml().unwrap(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<export_from_vision_EDC>\n</export_from_vision_EDC>"
+        );
+    }
+}
@@ -0,0 +1,1603 @@
+use std::{
+    collections::BTreeMap,
+    fs::{read_to_string, File},
+    hash::{DefaultHasher, Hash, Hasher},
+    io::BufWriter,
+    path::PathBuf,
+    str::from_utf8,
+};
+
+use arrow_array::{ffi_stream::FFI_ArrowArrayStream, RecordBatch, RecordBatchIterator};
+use arrow_schema::ArrowError;
+use chrono::{DateTime, FixedOffset};
+use pyo3::{
+    create_exception,
+    exceptions::{PyException, PyFileNotFoundError, PyKeyError, PyValueError},
+    prelude::*,
+    sync::PyOnceLock,
+    types::{PyCapsule, PyDict, PyTuple, PyType},
+    IntoPyObjectExt, PyClass,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::native::{
+    anonymize::Anonymizer,
+    common::{
+        Category, Comment, Entry, ExportInfo, Field, Form, LockState, NativeRecord, Reason, State,
+        Value, XmlElement,
+    },
+    lifecycle::{FormLifecycle, LifecycleOptions, LifecycleSummary},
+    ndjson::{
+        write_site_native_ndjson, write_subject_native_ndjson, write_user_native_ndjson,
+        NdjsonLines,
+    },
+    quality::{FormIssues, QualitySummary},
+    site_native::{Site, SiteNative},
+    study::{link_users, Study},
+    subject_native::{Patient, SubjectNative},
+    user_native::{User, UserNative},
+};
+use crate::{chunk_offset, errors::Error, parse_record, ParseOptions, RecordChunks};
+
+create_exception!(
+    prelude_xml_parser,
+    PreludeXmlParserError,
+    PyException,
+    "Base class for all errors raised by prelude_xml_parser."
+);
+create_exception!(
+    prelude_xml_parser,
+    InvalidFileTypeError,
+    PreludeXmlParserError,
+    "The file is not a XML file."
+);
+create_exception!(
+    prelude_xml_parser,
+    XmlIOError,
+    PreludeXmlParserError,
+    "An io error occurred while reading the XML."
+);
+create_exception!(
+    prelude_xml_parser,
+    ParseError,
+    PreludeXmlParserError,
+    "The XML could not be parsed. `position` holds the byte offset of the error when known."
+);
+create_exception!(
+    prelude_xml_parser,
+    UnknownError,
+    PreludeXmlParserError,
+    "An unknown error occurred."
+);
+
+/// The `FileNotFoundError` of this module, which is both a `PreludeXmlParserError` and a builtin
+/// `FileNotFoundError`. `create_exception!` only takes one base, so the class is made with `type()`.
+fn file_not_found_error(py: Python<'_>) -> PyResult<&Bound<'_, PyType>> {
+    static FILE_NOT_FOUND_ERROR: PyOnceLock<Py<PyType>> = PyOnceLock::new();
+    FILE_NOT_FOUND_ERROR
+        .get_or_try_init(py, || {
+            let bases = PyTuple::new(
+                py,
+                [
+                    py.get_type::<PreludeXmlParserError>(),
+                    py.get_type::<PyFileNotFoundError>(),
+                ],
+            )?;
+            let namespace = PyDict::new(py);
+            namespace.set_item("__module__", "prelude_xml_parser")?;
+            namespace.set_item("__doc__", "The file was not found at the specified path.")?;
+            let class = py
+                .get_type::<PyType>()
+                .call1(("FileNotFoundError", bases, namespace))?;
+            Ok(class.cast_into::<PyType>()?.unbind())
+        })
+        .map(|class| class.bind(py))
+}
+
+/// Converts a crate error into the matching Python exception.
+pub fn to_py_err(py: Python<'_>, err: Error) -> PyErr {
+    match err {
+        Error::InvalidFileType(_) => InvalidFileTypeError::new_err(err.to_string()),
+        Error::FileNotFound(_) => match file_not_found_error(py) {
+            Ok(class) => PyErr::from_type(class.clone(), err.to_string()),
+            Err(e) => e,
+        },
+        Error::IO(_) => XmlIOError::new_err(err.to_string()),
+        Error::ParsingError(_) => parse_error(py, err.to_string(), None),
+        Error::XmlError { position, .. } => parse_error(py, err.to_string(), Some(position)),
+        _ => UnknownError::new_err(err.to_string()),
+    }
+}
+
+fn parse_error(py: Python<'_>, message: String, position: Option<u64>) -> PyErr {
+    let err = ParseError::new_err(message);
+    if let Err(e) = err.value(py).setattr("position", position) {
+        return e;
+    }
+    err
+}
+
+fn decode(py: Python<'_>, xml: &[u8]) -> PyResult<String> {
+    from_utf8(xml).map(str::to_string).map_err(|e| {
+        parse_error(
+            py,
+            format!("XML is not valid UTF-8: {e}"),
+            Some(e.valid_up_to() as u64),
+        )
+    })
+}
+
+/// Implements `__reduce__` by pickling the instance as its JSON, restored with `from_json`.
+pub(crate) fn reduce<'py, T>(slf: &Bound<'py, T>) -> PyResult<(Bound<'py, PyAny>, (String,))>
+where
+    T: PyClass + Serialize,
+{
+    let json = serde_json::to_string(&*slf.borrow())
+        .map_err(|e| PyValueError::new_err(format!("Error converting to JSON: {e}")))?;
+
+    Ok((slf.as_any().get_type().getattr("from_json")?, (json,)))
+}
+
+/// Implements `__hash__` from the contents, consistent with `__eq__`.
+pub(crate) fn hash<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Deserializes an instance from the JSON its serde derives produce.
+pub(crate) fn from_json<T: DeserializeOwned>(json: &str) -> PyResult<T> {
+    serde_json::from_str(json)
+        .map_err(|e| PyValueError::new_err(format!("Error converting from JSON: {e}")))
+}
+
+/// Builds a model from a dictionary shaped like the output of its `to_dict`.
+pub(crate) trait FromPyDict: Sized {
+    fn from_py_dict(dict: &Bound<'_, PyDict>) -> PyResult<Self>;
+}
+
+/// Reads `key` from a `to_dict` style dictionary.
+///
+/// Missing keys read as `None` for optional values and raise `KeyError` otherwise.
+pub(crate) fn dict_get<'py, T>(dict: &Bound<'py, PyDict>, key: &str) -> PyResult<T>
+where
+    T: for<'a> FromPyObject<'a, 'py>,
+{
+    let py = dict.py();
+    match dict.get_item(key)? {
+        Some(value) => value.extract().map_err(Into::into),
+        None => py
+            .None()
+            .bind(py)
+            .extract()
+            .map_err(|_| PyKeyError::new_err(key.to_string())),
+    }
+}
+
+/// Reads a nested model given either as an instance or as a dictionary.
+pub(crate) fn dict_get_model<T>(dict: &Bound<'_, PyDict>, key: &str) -> PyResult<Option<T>>
+where
+    T: FromPyDict + PyClass + Clone,
+{
+    let value: Option<Bound<'_, PyAny>> = dict_get(dict, key)?;
+    value.as_ref().map(extract_model).transpose()
+}
+
+/// Reads a list of nested models given either as instances or as dictionaries.
+pub(crate) fn dict_get_models<T>(dict: &Bound<'_, PyDict>, key: &str) -> PyResult<Option<Vec<T>>>
+where
+    T: FromPyDict + PyClass + Clone,
+{
+    let values: Option<Vec<Bound<'_, PyAny>>> = dict_get(dict, key)?;
+    values
+        .map(|values| values.iter().map(extract_model).collect())
+        .transpose()
+}
+
+/// Reads the unknown attributes of a model, empty when missing.
+pub(crate) fn dict_get_extra(dict: &Bound<'_, PyDict>) -> PyResult<BTreeMap<String, String>> {
+    let extra: Option<BTreeMap<String, String>> = dict_get(dict, "extra")?;
+    Ok(extra.unwrap_or_default())
+}
+
+/// Reads the unknown child elements of a model, empty when missing.
+pub(crate) fn dict_get_elements(dict: &Bound<'_, PyDict>) -> PyResult<Vec<XmlElement>> {
+    Ok(dict_get_models(dict, "extra_elements")?.unwrap_or_default())
+}
+
+/// Converts unknown elements to the dictionaries `to_dict` nests.
+pub(crate) fn to_dicts<'py>(
+    py: Python<'py>,
+    elements: &[XmlElement],
+) -> PyResult<Vec<Bound<'py, PyDict>>> {
+    elements.iter().map(|element| element.to_dict(py)).collect()
+}
+
+fn extract_model<T>(value: &Bound<'_, PyAny>) -> PyResult<T>
+where
+    T: FromPyDict + PyClass + Clone,
+{
+    match value.cast::<PyDict>() {
+        Ok(dict) => T::from_py_dict(dict),
+        Err(_) => Ok(value.cast::<T>()?.borrow().clone()),
+    }
+}
+
+/// Builds a `ClassName(field=value, ...)` repr from the Python repr of each value.
+///
+/// Lists are summarized by their length to keep the repr of large natives readable.
+pub(crate) struct Repr<'py> {
+    py: Python<'py>,
+    repr: String,
+    has_fields: bool,
+}
+
+impl<'py> Repr<'py> {
+    pub(crate) fn new(py: Python<'py>, class_name: &str) -> Self {
+        Repr {
+            py,
+            repr: format!("{class_name}("),
+            has_fields: false,
+        }
+    }
+
+    fn name(&mut self, name: &str) {
+        if self.has_fields {
+            self.repr.push_str(", ");
+        }
+        self.repr.push_str(name);
+        self.repr.push('=');
+        self.has_fields = true;
+    }
+
+    pub(crate) fn field<T>(mut self, name: &str, value: T) -> PyResult<Self>
+    where
+        T: IntoPyObject<'py>,
+    {
+        let value = value.into_bound_py_any(self.py)?;
+        self.name(name);
+        self.repr.push_str(&value.repr()?.to_cow()?);
+
+        Ok(self)
+    }
+
+    pub(crate) fn items(mut self, name: &str, len: Option<usize>) -> Self {
+        self.name(name);
+        match len {
+            Some(len) => self.repr.push_str(&format!("<{len} items>")),
+            None => self.repr.push_str("None"),
+        }
+
+        self
+    }
+
+    pub(crate) fn finish(mut self) -> String {
+        self.repr.push(')');
+        self.repr
+    }
+}
+
+/// Exports a record batch as an `arrow_array_stream` PyCapsule for the Arrow PyCapsule interface.
+pub(crate) fn arrow_stream_capsule(
+    py: Python<'_>,
+    batch: Result<RecordBatch, ArrowError>,
+) -> PyResult<Bound<'_, PyCapsule>> {
+    let batch =
+        batch.map_err(|e| PyValueError::new_err(format!("Error converting to Arrow: {e}")))?;
+    let schema = batch.schema();
+    let reader = RecordBatchIterator::new([Ok(batch)], schema);
+    let stream = FFI_ArrowArrayStream::new(Box::new(reader));
+
+    PyCapsule::new_with_value(py, stream, c"arrow_array_stream")
+}
+
+/// Converts a serializable value to plain Python objects through `json.loads`.
+pub(crate) fn to_py_json<'py, T: Serialize>(
+    py: Python<'py>,
+    value: &T,
+) -> PyResult<Bound<'py, PyAny>> {
+    let json = serde_json::to_string(value).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    py.import("json")?.call_method1("loads", (json,))
+}
+
+/// The lifecycles of a native's forms as dictionaries, or their summaries keyed by record or
+/// site unique id when `by` is given.
+pub(crate) fn lifecycles_to_py<'py>(
+    py: Python<'py>,
+    form_lifecycles: impl FnOnce(&LifecycleOptions) -> Vec<FormLifecycle> + Send,
+    by: Option<&str>,
+    required_signatures: Option<Vec<String>>,
+    exempt_states: Option<Vec<String>>,
+    as_of: Option<DateTime<FixedOffset>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let default = LifecycleOptions::default();
+    let options = LifecycleOptions {
+        required_signatures: required_signatures.unwrap_or(default.required_signatures),
+        exempt_states: exempt_states.unwrap_or(default.exempt_states),
+        as_of,
+    };
+    let lifecycles = py.detach(|| form_lifecycles(&options));
+
+    match by {
+        None => to_py_json(py, &lifecycles),
+        Some("record") => to_py_json(py, &LifecycleSummary::by_record(&lifecycles)),
+        Some("site") => to_py_json(py, &LifecycleSummary::by_site(&lifecycles)),
+        Some(by) => Err(PyValueError::new_err(format!(
+            "by must be \"record\" or \"site\", not {by:?}"
+        ))),
+    }
+}
+
+/// The forms of a native with data-quality issues as dictionaries, or their summaries keyed by
+/// record, site, form, or field when `by` is given.
+pub(crate) fn quality_to_py<'py>(
+    py: Python<'py>,
+    quality_issues: impl FnOnce() -> Vec<FormIssues> + Send,
+    by: Option<&str>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let issues = py.detach(quality_issues);
+
+    match by {
+        None => to_py_json(py, &issues),
+        Some("record") => to_py_json(py, &QualitySummary::by_record(&issues)),
+        Some("site") => to_py_json(py, &QualitySummary::by_site(&issues)),
+        Some("form") => to_py_json(py, &QualitySummary::by_form(&issues)),
+        Some("field") => to_py_json(py, &QualitySummary::by_field(&issues)),
+        Some(by) => Err(PyValueError::new_err(format!(
+            "by must be \"record\", \"site\", \"form\" or \"field\", not {by:?}"
+        ))),
+    }
+}
+
+/// Converts an object implementing `__arrow_c_stream__` to a pandas DataFrame through pyarrow.
+pub(crate) fn to_pandas<'py>(obj: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    obj.py()
+        .import("pyarrow")?
+        .call_method1("table", (obj,))?
+        .call_method0("to_pandas")
+}
+
+/// Converts an object implementing `__arrow_c_stream__` to a Polars DataFrame.
+pub(crate) fn to_polars<'py>(obj: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    obj.py().import("polars")?.call_method1("DataFrame", (obj,))
+}
+
+/// Lazily yields the patients of a Prelude native subject XML document.
+///
+/// Each patient is parsed when it is requested, with the GIL released while parsing.
+#[pyclass(module = "prelude_xml_parser")]
+pub struct PatientIterator {
+    xml: String,
+    pos: usize,
+}
+
+#[pymethods]
+impl PatientIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<Patient>> {
+        let xml = &self.xml;
+        let pos = self.pos;
+        let (patient, next_pos) = py.detach(|| {
+            let mut chunks = RecordChunks::starting_at(xml, Patient::TAG, pos);
+            let patient = chunks.next().map(|chunk| {
+                parse_record::<Patient>(chunk, &ParseOptions::default())
+                    .map_err(|e| e.offset_by(chunk_offset(xml, chunk)))
+            });
+            (patient, chunks.pos)
+        });
+        self.pos = next_pos;
+
+        patient.transpose().map_err(|e| to_py_err(py, e))
+    }
+}
+
+/// Lazily parses the patients of a Prelude native subject XML file.
+#[pyfunction]
+#[pyo3(name = "iter_subject_native_patients_file")]
+fn py_iter_subject_native_patients_file(
+    py: Python<'_>,
+    xml_path: PathBuf,
+) -> PyResult<PatientIterator> {
+    let xml = py
+        .detach(|| {
+            crate::check_valid_xml_file(&xml_path)?;
+            Ok(read_to_string(&xml_path)?)
+        })
+        .map_err(|e| to_py_err(py, e))?;
+
+    Ok(PatientIterator { xml, pos: 0 })
+}
+
+/// Lazily parses the patients of a string of Prelude native subject XML.
+#[pyfunction]
+#[pyo3(name = "iter_subject_native_patients")]
+fn py_iter_subject_native_patients(xml_str: String) -> PatientIterator {
+    PatientIterator {
+        xml: xml_str,
+        pos: 0,
+    }
+}
+
+/// Parses a Prelude native subject XML file into a `SubjectNative`.
+#[pyfunction]
+#[pyo3(name = "parse_subject_native_file", signature = (xml_path, *, keep_unknown = false))]
+fn py_parse_subject_native_file(
+    py: Python<'_>,
+    xml_path: PathBuf,
+    keep_unknown: bool,
+) -> PyResult<SubjectNative> {
+    let options = ParseOptions { keep_unknown };
+    py.detach(|| crate::parse_subject_native_file_with_options(&xml_path, &options))
+        .map_err(|e| to_py_err(py, e))
+}
+
+/// Parses a string of Prelude native subject XML into a `SubjectNative`.
+#[pyfunction]
+#[pyo3(name = "parse_subject_native_string", signature = (xml_str, *, keep_unknown = false))]
+fn py_parse_subject_native_string(
+    py: Python<'_>,
+    xml_str: &str,
+    keep_unknown: bool,
+) -> PyResult<SubjectNative> {
+    let options = ParseOptions { keep_unknown };
+    py.detach(|| crate::parse_subject_native_string_with_options(xml_str, &options))
+        .map_err(|e| to_py_err(py, e))
+}
+
+/// Parses UTF-8 encoded Prelude native subject XML into a `SubjectNative`.
+#[pyfunction]
+#[pyo3(name = "parse_subject_native_bytes", signature = (xml_bytes, *, keep_unknown = false))]
+fn py_parse_subject_native_bytes(
+    py: Python<'_>,
+    xml_bytes: &[u8],
+    keep_unknown: bool,
+) -> PyResult<SubjectNative> {
+    let options = ParseOptions { keep_unknown };
+    let xml_str = decode(py, xml_bytes)?;
+    py.detach(|| crate::parse_subject_native_string_with_options(&xml_str, &options))
+        .map_err(|e| to_py_err(py, e))
+}
+
+/// Parses a Prelude native site XML file into a `SiteNative`.
+#[pyfunction]
+#[pyo3(name = "parse_site_native_file", signature = (xml_path, *, keep_unknown = false))]
+fn py_parse_site_native_file(
+    py: Python<'_>,
+    xml_path: PathBuf,
+    keep_unknown: bool,
+) -> PyResult<SiteNative> {
+    let options = ParseOptions { keep_unknown };
+    py.detach(|| crate::parse_site_native_file_with_options(&xml_path, &options))
+        .map_err(|e| to_py_err(py, e))
+}
+
+/// Parses a string of Prelude native site XML into a `SiteNative`.
+#[pyfunction]
+#[pyo3(name = "parse_site_native_string", signature = (xml_str, *, keep_unknown = false))]
+fn py_parse_site_native_string(
+    py: Python<'_>,
+    xml_str: &str,
+    keep_unknown: bool,
+) -> PyResult<SiteNative> {
+    let options = ParseOptions { keep_unknown };
+    py.detach(|| crate::parse_site_native_string_with_options(xml_str, &options))
+        .map_err(|e| to_py_err(py, e))
+}
+
+/// Parses UTF-8 encoded Prelude native site XML into a `SiteNative`.
+#[pyfunction]
+#[pyo3(name = "parse_site_native_bytes", signature = (xml_bytes, *, keep_unknown = false))]
+fn py_parse_site_native_bytes(
+    py: Python<'_>,
+    xml_bytes: &[u8],
+    keep_unknown: bool,
+) -> PyResult<SiteNative> {
+    let options = ParseOptions { keep_unknown };
+    let xml_str = decode(py, xml_bytes)?;
+    py.detach(|| crate::parse_site_native_string_with_options(&xml_str, &options))
+        .map_err(|e| to_py_err(py, e))
+}
+
+/// Parses a Prelude native user XML file into a `UserNative`.
+#[pyfunction]
+#[pyo3(name = "parse_user_native_file", signature = (xml_path, *, keep_unknown = false))]
+fn py_parse_user_native_file(
+    py: Python<'_>,
+    xml_path: PathBuf,
+    keep_unknown: bool,
+) -> PyResult<UserNative> {
+    let options = ParseOptions { keep_unknown };
+    py.detach(|| crate::parse_user_native_file_with_options(&xml_path, &options))
+        .map_err(|e| to_py_err(py, e))
+}
+
+/// Parses a string of Prelude native user XML into a `UserNative`.
+#[pyfunction]
+#[pyo3(name = "parse_user_native_string", signature = (xml_str, *, keep_unknown = false))]
+fn py_parse_user_native_string(
+    py: Python<'_>,
+    xml_str: &str,
+    keep_unknown: bool,
+) -> PyResult<UserNative> {
+    let options = ParseOptions { keep_unknown };
+    py.detach(|| crate::parse_user_native_string_with_options(xml_str, &options))
+        .map_err(|e| to_py_err(py, e))
+}
+
+/// Parses UTF-8 encoded Prelude native user XML into a `UserNative`.
+#[pyfunction]
+#[pyo3(name = "parse_user_native_bytes", signature = (xml_bytes, *, keep_unknown = false))]
+fn py_parse_user_native_bytes(
+    py: Python<'_>,
+    xml_bytes: &[u8],
+    keep_unknown: bool,
+) -> PyResult<UserNative> {
+    let options = ParseOptions { keep_unknown };
+    let xml_str = decode(py, xml_bytes)?;
+    py.detach(|| crate::parse_user_native_string_with_options(&xml_str, &options))
+        .map_err(|e| to_py_err(py, e))
+}
+
+/// Streams the records of a Prelude native XML file to an NDJSON file, one record or one field
+/// entry per line.
+fn write_ndjson_file(
+    py: Python<'_>,
+    xml_path: PathBuf,
+    ndjson_path: PathBuf,
+    entries: bool,
+    write: fn(&str, BufWriter<File>, NdjsonLines) -> Result<usize, Error>,
+) -> PyResult<usize> {
+    let lines = if entries {
+        NdjsonLines::Entries
+    } else {
+        NdjsonLines::Records
+    };
+    py.detach(|| {
+        crate::check_valid_xml_file(&xml_path)?;
+        let xml = read_to_string(&xml_path)?;
+        write(&xml, BufWriter::new(File::create(&ndjson_path)?), lines)
+    })
+    .map_err(|e| to_py_err(py, e))
+}
+
+/// Writes the patients of a Prelude native subject XML file as NDJSON.
+#[pyfunction]
+#[pyo3(name = "write_subject_native_ndjson", signature = (xml_path, ndjson_path, *, entries = false))]
+fn py_write_subject_native_ndjson(
+    py: Python<'_>,
+    xml_path: PathBuf,
+    ndjson_path: PathBuf,
+    entries: bool,
+) -> PyResult<usize> {
+    write_ndjson_file(
+        py,
+        xml_path,
+        ndjson_path,
+        entries,
+        write_subject_native_ndjson,
+    )
+}
+
+/// Writes the sites of a Prelude native site XML file as NDJSON.
+#[pyfunction]
+#[pyo3(name = "write_site_native_ndjson", signature = (xml_path, ndjson_path, *, entries = false))]
+fn py_write_site_native_ndjson(
+    py: Python<'_>,
+    xml_path: PathBuf,
+    ndjson_path: PathBuf,
+    entries: bool,
+) -> PyResult<usize> {
+    write_ndjson_file(py, xml_path, ndjson_path, entries, write_site_native_ndjson)
+}
+
+/// Writes the users of a Prelude native user XML file as NDJSON.
+#[pyfunction]
+#[pyo3(name = "write_user_native_ndjson", signature = (xml_path, ndjson_path, *, entries = false))]
+fn py_write_user_native_ndjson(
+    py: Python<'_>,
+    xml_path: PathBuf,
+    ndjson_path: PathBuf,
+    entries: bool,
+) -> PyResult<usize> {
+    write_ndjson_file(py, xml_path, ndjson_path, entries, write_user_native_ndjson)
+}
+
+/// Resolves the user unique ids recorded in a study's natives to the users of its user native.
+#[pyfunction]
+#[pyo3(name = "link_users")]
+fn py_link_users<'py>(
+    py: Python<'py>,
+    subject_native: &SubjectNative,
+    site_native: &SiteNative,
+    user_native: &UserNative,
+) -> PyResult<Bound<'py, PyAny>> {
+    let links = py.detach(|| link_users(subject_native, site_native, user_native));
+    to_py_json(py, &links)
+}
+
+/// Adds the classes, functions, and exceptions of this crate to `m`.
+///
+/// This is exposed so crates that embed this one can build their own Python module with it.
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+
+    m.add_class::<Value>()?;
+    m.add_class::<Reason>()?;
+    m.add_class::<Entry>()?;
+    m.add_class::<Comment>()?;
+    m.add_class::<Field>()?;
+    m.add_class::<Category>()?;
+    m.add_class::<State>()?;
+    m.add_class::<LockState>()?;
+    m.add_class::<ExportInfo>()?;
+    m.add_class::<XmlElement>()?;
+    m.add_class::<Form>()?;
+    m.add_class::<Patient>()?;
+    m.add_class::<SubjectNative>()?;
+    m.add_class::<Site>()?;
+    m.add_class::<SiteNative>()?;
+    m.add_class::<User>()?;
+    m.add_class::<UserNative>()?;
+    m.add_class::<PatientIterator>()?;
+    m.add_class::<Anonymizer>()?;
+    m.add_class::<Study>()?;
+
+    m.add(
+        "JSON_SCHEMA_VERSION",
+        crate::native::json::JSON_SCHEMA_VERSION,
+    )?;
+
+    m.add_function(wrap_pyfunction!(py_parse_subject_native_file, m)?)?;
+    m.add_function(wrap_pyfunction!(py_parse_subject_native_string, m)?)?;
+    m.add_function(wrap_pyfunction!(py_parse_subject_native_bytes, m)?)?;
+    m.add_function(wrap_pyfunction!(py_iter_subject_native_patients_file, m)?)?;
+    m.add_function(wrap_pyfunction!(py_iter_subject_native_patients, m)?)?;
+    m.add_function(wrap_pyfunction!(py_parse_site_native_file, m)?)?;
+    m.add_function(wrap_pyfunction!(py_parse_site_native_string, m)?)?;
+    m.add_function(wrap_pyfunction!(py_parse_site_native_bytes, m)?)?;
+    m.add_function(wrap_pyfunction!(py_parse_user_native_file, m)?)?;
+    m.add_function(wrap_pyfunction!(py_parse_user_native_string, m)?)?;
+    m.add_function(wrap_pyfunction!(py_parse_user_native_bytes, m)?)?;
+    m.add_function(wrap_pyfunction!(py_write_subject_native_ndjson, m)?)?;
+    m.add_function(wrap_pyfunction!(py_write_site_native_ndjson, m)?)?;
+    m.add_function(wrap_pyfunction!(py_write_user_native_ndjson, m)?)?;
+    m.add_function(wrap_pyfunction!(py_link_users, m)?)?;
+
+    m.add(
+        "PreludeXmlParserError",
+        py.get_type::<PreludeXmlParserError>(),
+    )?;
+    m.add(
+        "InvalidFileTypeError",
+        py.get_type::<InvalidFileTypeError>(),
+    )?;
+    m.add("FileNotFoundError", file_not_found_error(py)?)?;
+    m.add("XmlIOError", py.get_type::<XmlIOError>())?;
+    m.add("ParseError", py.get_type::<ParseError>())?;
+    m.add("UnknownError", py.get_type::<UnknownError>())?;
+
+    Ok(())
+}
+
+#[pymodule]
+fn prelude_xml_parser(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    register(m)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Write;
+
+    use pyo3::types::PySet;
+    use tempfile::Builder;
+
+    fn module(py: Python<'_>) -> Bound<'_, PyModule> {
+        let m = PyModule::new(py, "prelude_xml_parser").unwrap();
+        register(&m).unwrap();
+        m
+    }
+
+    #[test]
+    fn parses_file_string_and_bytes() {
+        Python::initialize();
+        Python::attach(|py| {
+            let m = module(py);
+            let xml = std::fs::read_to_string("tests/assets/subject_native_small.xml").unwrap();
+
+            let from_file = m
+                .getattr("parse_subject_native_file")
+                .unwrap()
+                .call1(("tests/assets/subject_native_small.xml",))
+                .unwrap();
+            let from_string = m
+                .getattr("parse_subject_native_string")
+                .unwrap()
+                .call1((xml.as_str(),))
+                .unwrap();
+            let from_bytes = m
+                .getattr("parse_subject_native_bytes")
+                .unwrap()
+                .call1((xml.as_bytes(),))
+                .unwrap();
+
+            let expected = crate::parse_subject_native_string(&xml).unwrap();
+            assert_eq!(
+                from_file
+                    .extract::<PyRef<SubjectNative>>()
+                    .unwrap()
+                    .patients,
+                expected.patients
+            );
+            assert_eq!(
+                from_string
+                    .extract::<PyRef<SubjectNative>>()
+                    .unwrap()
+                    .patients,
+                expected.patients
+            );
+            assert_eq!(
+                from_bytes
+                    .extract::<PyRef<SubjectNative>>()
+                    .unwrap()
+                    .patients,
+                expected.patients
+            );
+        });
+    }
+
+    #[test]
+    fn missing_file_raises_file_not_found_error() {
+        Python::initialize();
+        Python::attach(|py| {
+            let m = module(py);
+            let err = m
+                .getattr("parse_site_native_file")
+                .unwrap()
+                .call1(("does/not/exist.xml",))
+                .unwrap_err();
+
+            assert!(err.is_instance(py, &m.getattr("FileNotFoundError").unwrap()));
+            assert!(err.is_instance_of::<PreludeXmlParserError>(py));
+            assert!(err.is_instance_of::<PyFileNotFoundError>(py));
+        });
+    }
+
+    #[test]
+    fn wrong_extension_raises_invalid_file_type_error() {
+        Python::initialize();
+        Python::attach(|py| {
+            let m = module(py);
+            let mut file = Builder::new().suffix(".csv").tempfile().unwrap();
+            file.write_all(b"a,b").unwrap();
+            let err = m
+                .getattr("parse_user_native_file")
+                .unwrap()
+                .call1((file.path(),))
+                .unwrap_err();
+
+            assert!(err.is_instance_of::<InvalidFileTypeError>(py));
+            assert!(err.is_instance_of::<PreludeXmlParserError>(py));
+        });
+    }
+
+    #[test]
+    fn malformed_xml_raises_parse_error_with_position() {
+        Python::initialize();
+        Python::attach(|py| {
+            let m = module(py);
+            let xml = r#"<export_from_vision_EDC>
+  <patient patientId="A" uniqueId="1" creator="c" siteName="s" siteUniqueId="2">
+    <form name="f"></category>
+  </patient>
+</export_from_vision_EDC>"#;
+            let err = m
+                .getattr("parse_subject_native_string")
+                .unwrap()
+                .call1((xml,))
+                .unwrap_err();
+
+            assert!(err.is_instance_of::<ParseError>(py));
+            let position: u64 = err
+                .value(py)
+                .getattr("position")
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(position as usize, xml.find("</category>").unwrap());
+        });
+    }
+
+    #[test]
+    fn invalid_utf8_raises_parse_error() {
+        Python::initialize();
+        Python::attach(|py| {
+            let m = module(py);
+            let err = m
+                .getattr("parse_site_native_bytes")
+                .unwrap()
+                .call1((&b"<site \xff"[..],))
+                .unwrap_err();
+
+            assert!(err.is_instance_of::<ParseError>(py));
+            let position: u64 = err
+                .value(py)
+                .getattr("position")
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(position, 6);
+        });
+    }
+
+    #[test]
+    fn iterates_patients_lazily() {
+        Python::initialize();
+        Python::attach(|py| {
+            let m = module(py);
+            let expected = crate::parse_subject_native_file(std::path::Path::new(
+                "tests/assets/subject_native.xml",
+            ))
+            .unwrap();
+
+            let iterator = m
+                .getattr("iter_subject_native_patients_file")
+                .unwrap()
+                .call1(("tests/assets/subject_native.xml",))
+                .unwrap();
+            let patients = iterator
+                .try_iter()
+                .unwrap()
+                .map(|patient| {
+                    patient
+                        .unwrap()
+                        .extract::<PyRef<Patient>>()
+                        .unwrap()
+                        .clone()
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(patients, expected.patients);
+        });
+    }
+
+    #[test]
+    fn patient_iterator_raises_parse_error_for_bad_patient() {
+        Python::initialize();
+        Python::attach(|py| {
+            let m = module(py);
+            let xml = r#"<export_from_vision_EDC>
+  <patient patientId="A" uniqueId="1" creator="c" siteName="s" siteUniqueId="2"></patient>
+  <patient patientId="B" uniqueId="3" creator="c" siteName="s" siteUniqueId="2">
+    <form name="f"></category>
+  </patient>
+</export_from_vision_EDC>"#;
+            let iterator = m
+                .getattr("iter_subject_native_patients")
+                .unwrap()
+                .call1((xml,))
+                .unwrap();
+            let mut iterator = iterator.try_iter().unwrap();
+
+            let first = iterator.next().unwrap().unwrap();
+            assert_eq!(
+                first
+                    .getattr("patient_id")
+                    .unwrap()
+                    .extract::<String>()
+                    .unwrap(),
+                "A"
+            );
+            let err = iterator.next().unwrap().unwrap_err();
+            assert!(err.is_instance_of::<ParseError>(py));
+        });
+    }
+
+    #[test]
+    fn diffs_natives() {
+        Python::initialize();
+        Python::attach(|py| {
+            let old = crate::parse_subject_native_file(std::path::Path::new(
+                "tests/assets/subject_native.xml",
+            ))
+            .unwrap();
+            let mut new = old.clone();
+            new.patients.remove(0);
+
+            let changes = Bound::new(py, old.clone())
+                .unwrap()
+                .call_method1("diff", (new,))
+                .unwrap()
+                .extract::<Vec<Bound<PyDict>>>()
+                .unwrap();
+
+            assert_eq!(changes.len(), 1);
+            let change = &changes[0];
+            assert_eq!(
+                dict_get::<String>(change, "recordUniqueId").unwrap(),
+                old.patients[0].unique_id
+            );
+            assert_eq!(
+                dict_get::<String>(change, "change").unwrap(),
+                "record_removed"
+            );
+        });
+    }
+
+    #[test]
+    fn merges_natives() {
+        Python::initialize();
+        Python::attach(|py| {
+            let native = crate::parse_subject_native_file(std::path::Path::new(
+                "tests/assets/subject_native.xml",
+            ))
+            .unwrap();
+            let mut first = native.clone();
+            first.patients.truncate(1);
+            let mut second = native.clone();
+            second.patients.remove(0);
+
+            let (merged, conflicts) = py
+                .get_type::<SubjectNative>()
+                .call_method1("merge", (vec![first, second],))
+                .unwrap()
+                .extract::<(SubjectNative, Vec<Bound<PyDict>>)>()
+                .unwrap();
+
+            assert_eq!(merged.patients, native.patients);
+            assert!(conflicts.is_empty());
+        });
+    }
+
+    #[test]
+    fn anonymizes_natives() {
+        Python::initialize();
+        Python::attach(|py| {
+            let locals = PyDict::new(py);
+            locals.set_item("m", module(py)).unwrap();
+            py.run(
+                cr#"
+anonymizer = m.Anonymizer("salt", max_date_shift=30)
+anonymizer.rule("^(first|last)_name$", "drop")
+anonymizer.rule("^dob$", "year")
+native = m.parse_subject_native_file("tests/assets/subject_native.xml")
+anonymized = native.anonymize(anonymizer)
+xml = anonymized.to_xml()
+restored = m.parse_subject_native_string(xml)
+pseudonym = anonymizer.pseudonym("Paul Sanders")
+"#,
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+
+            let get = |name: &str| locals.get_item(name).unwrap().unwrap();
+            let xml = get("xml").extract::<String>().unwrap();
+            assert!(!xml.contains("Paul Sanders"));
+            assert!(xml.contains(&get("pseudonym").extract::<String>().unwrap()));
+            assert!(get("restored").eq(get("anonymized")).unwrap());
+
+            let err = py
+                .run(
+                    c"anonymizer.rule('age', 'number_range')",
+                    None,
+                    Some(&locals),
+                )
+                .unwrap_err();
+            assert!(err.is_instance_of::<PyValueError>(py));
+        });
+    }
+
+    #[test]
+    fn joins_patients_to_sites() {
+        Python::initialize();
+        Python::attach(|py| {
+            let m = module(py);
+            let parse =
+                |function: &str, path: &str| m.getattr(function).unwrap().call1((path,)).unwrap();
+            let study = m
+                .getattr("Study")
+                .unwrap()
+                .call1((
+                    parse(
+                        "parse_subject_native_file",
+                        "tests/assets/subject_native.xml",
+                    ),
+                    parse("parse_site_native_file", "tests/assets/site_native.xml"),
+                ))
+                .unwrap();
+
+            let sites = study
+                .call_method0("sites")
+                .unwrap()
+                .extract::<Vec<(Site, Vec<Patient>)>>()
+                .unwrap();
+            assert_eq!(sites.len(), 2);
+            assert_eq!(sites[0].1.len(), 2);
+            let issues = study
+                .call_method0("check_enrollment")
+                .unwrap()
+                .extract::<Vec<Bound<PyDict>>>()
+                .unwrap();
+            assert_eq!(issues.len(), 1);
+            assert_eq!(
+                dict_get::<String>(&issues[0], "issue").unwrap(),
+                "patient_count"
+            );
+        });
+    }
+
+    #[test]
+    fn links_users() {
+        Python::initialize();
+        Python::attach(|py| {
+            let m = module(py);
+            let parse =
+                |function: &str, path: &str| m.getattr(function).unwrap().call1((path,)).unwrap();
+            let links = m
+                .getattr("link_users")
+                .unwrap()
+                .call1((
+                    parse(
+                        "parse_subject_native_file",
+                        "tests/assets/subject_native.xml",
+                    ),
+                    parse("parse_site_native_file", "tests/assets/site_native.xml"),
+                    parse("parse_user_native_file", "tests/assets/user_native.xml"),
+                ))
+                .unwrap()
+                .extract::<Bound<PyDict>>()
+                .unwrap();
+
+            let users: Vec<Bound<PyDict>> = dict_get(&links, "users").unwrap();
+            assert_eq!(users.len(), 2);
+            assert_eq!(
+                dict_get::<Option<String>>(&users[1], "email").unwrap(),
+                Some("paul@pbsdatasolutions.com".to_string())
+            );
+            let dangling: Vec<Bound<PyDict>> = dict_get(&links, "dangling").unwrap();
+            assert!(!dangling.is_empty());
+        });
+    }
+
+    #[test]
+    fn reports_form_lifecycles() {
+        Python::initialize();
+        Python::attach(|py| {
+            let native = Bound::new(
+                py,
+                crate::parse_subject_native_file(std::path::Path::new(
+                    "tests/assets/subject_native.xml",
+                ))
+                .unwrap(),
+            )
+            .unwrap();
+
+            let lifecycles = native
+                .call_method0("form_lifecycles")
+                .unwrap()
+                .extract::<Vec<Bound<PyDict>>>()
+                .unwrap();
+            assert_eq!(lifecycles.len(), 4);
+            assert_eq!(
+                dict_get::<String>(&lifecycles[0], "currentState").unwrap(),
+                "form.state.in.work"
+            );
+
+            let kwargs = PyDict::new(py);
+            kwargs.set_item("by", "site").unwrap();
+            let summary = native
+                .call_method("lifecycle_summary", (), Some(&kwargs))
+                .unwrap()
+                .extract::<Bound<PyDict>>()
+                .unwrap();
+            let site: Bound<PyDict> = dict_get(&summary, "1681574834910").unwrap();
+            assert_eq!(dict_get::<usize>(&site, "forms").unwrap(), 4);
+
+            kwargs.set_item("by", "form").unwrap();
+            let err = native
+                .call_method("lifecycle_summary", (), Some(&kwargs))
+                .unwrap_err();
+            assert!(err.is_instance_of::<PyValueError>(py));
+        });
+    }
+
+    #[test]
+    fn reports_quality_issues() {
+        Python::initialize();
+        Python::attach(|py| {
+            let native = Bound::new(
+                py,
+                crate::parse_user_native_file(std::path::Path::new("tests/assets/user_native.xml"))
+                    .unwrap(),
+            )
+            .unwrap();
+
+            let issues = native
+                .call_method0("quality_issues")
+                .unwrap()
+                .extract::<Vec<Bound<PyDict>>>()
+                .unwrap();
+            let fields: Vec<Bound<PyDict>> = dict_get(&issues[0], "fields").unwrap();
+            assert_eq!(
+                dict_get::<String>(&fields[0], "errorCode").unwrap(),
+                "undefined"
+            );
+
+            let summary = native
+                .call_method1("quality_summary", ("form",))
+                .unwrap()
+                .extract::<Bound<PyDict>>()
+                .unwrap();
+            let form: Bound<PyDict> = dict_get(&summary, "form.name.demographics").unwrap();
+            assert_eq!(dict_get::<usize>(&form, "formsWithErrors").unwrap(), 0);
+
+            let err = native
+                .call_method1("quality_summary", ("category",))
+                .unwrap_err();
+            assert!(err.is_instance_of::<PyValueError>(py));
+        });
+    }
+
+    #[test]
+    fn computes_metrics() {
+        Python::initialize();
+        Python::attach(|py| {
+            let native = Bound::new(
+                py,
+                crate::parse_subject_native_file(std::path::Path::new(
+                    "tests/assets/subject_native.xml",
+                ))
+                .unwrap(),
+            )
+            .unwrap();
+
+            let metrics = native
+                .call_method0("metrics")
+                .unwrap()
+                .extract::<Bound<PyDict>>()
+                .unwrap();
+            let by_user: Bound<PyDict> = dict_get(&metrics, "byUser").unwrap();
+            let user: Bound<PyDict> = dict_get(&by_user, "1681162687395").unwrap();
+            let latency: Bound<PyDict> = dict_get(&user, "entryLatency").unwrap();
+            assert_eq!(dict_get::<i64>(&latency, "maxSeconds").unwrap(), 36);
+            let total: Bound<PyDict> = dict_get(&metrics, "total").unwrap();
+            assert_eq!(dict_get::<usize>(&total, "forms").unwrap(), 4);
+        });
+    }
+
+    #[test]
+    fn lists_comments() {
+        Python::initialize();
+        Python::attach(|py| {
+            let module = module(py);
+            let locals = PyDict::new(py);
+            locals.set_item("m", &module).unwrap();
+            py.run(
+                cr#"
+native = m.parse_subject_native_file("tests/assets/subject_native.xml")
+comments = native.comments()
+assert len(comments) == 1
+assert comments[0]["recordUniqueId"] == "1681574905819"
+assert comments[0]["fieldName"] == "breed"
+assert comments[0]["value"]["value"] == "Some Comment"
+assert "replies" not in comments[0]
+
+value = m.Value(by="Someone", role="Reviewer", value="Reply")
+comment = m.Comment(comment_id="1", value=value, replies=[value])
+assert m.Comment.from_dict(comment.to_dict()) == comment
+assert comment.replies[0].value == "Reply"
+"#,
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+        });
+    }
+
+    #[test]
+    fn groups_repeating_fields() {
+        Python::initialize();
+        Python::attach(|py| {
+            let locals = PyDict::new(py);
+            locals.set_item("m", module(py)).unwrap();
+            py.run(
+                cr#"
+native = m.parse_subject_native_file("tests/assets/subject_native_repeating.xml")
+adverse_events = native.patients[0].forms[0].categories[1]
+assert adverse_events.is_repeating()
+instances = adverse_events.instances()
+assert [index for index, _ in instances] == [1, 2, 3]
+index, fields = instances[1]
+assert [f.base_name for f in fields] == ["ae_term", "ae_start_date"]
+assert fields[0].repeat_index == 2
+assert instances[2][1] == []
+summary = native.patients[0].forms[0].categories[0]
+assert not summary.is_repeating()
+assert summary.instances()[0][0] is None
+"#,
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+        });
+    }
+
+    #[test]
+    fn keeps_unknown_attributes_and_elements() {
+        Python::initialize();
+        Python::attach(|py| {
+            let locals = PyDict::new(py);
+            locals.set_item("m", module(py)).unwrap();
+            py.run(
+                cr#"
+path = "tests/assets/subject_native_unknown.xml"
+assert m.parse_subject_native_file(path).patients[0].extra == {}
+native = m.parse_subject_native_file(path, keep_unknown=True)
+patient = native.patients[0]
+assert patient.extra == {"enrollmentStatus": "Enrolled"}
+assert native.export.extra == {"exportVersion": "2"}
+signature = patient.forms[0].extra_elements[0]
+assert signature.name == "signature"
+assert signature.attributes["meaning"] == "Author"
+assert [child.name for child in signature.children] == ["note", "witness"]
+assert patient.extra_elements == [m.XmlElement("visitPlan", {"name": "Standard"})]
+form = patient.forms[0].to_dict()
+assert form["extra"] == {"visitWindow": "0"}
+assert form["extra_elements"][0]["children"][0]["text"] == "Signed on &lt;paper&gt;"
+assert m.Form.from_dict(form) == patient.forms[0]
+assert m.Value("Paul Sanders", "Project Manager", "Labrador").extra == {}
+"#,
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+        });
+    }
+
+    #[test]
+    fn writes_ndjson_files() {
+        Python::initialize();
+        Python::attach(|py| {
+            let m = module(py);
+            let dir = tempfile::tempdir().unwrap();
+            let records_path = dir.path().join("patients.ndjson");
+            let entries_path = dir.path().join("entries.ndjson");
+            let native = crate::parse_subject_native_file(std::path::Path::new(
+                "tests/assets/subject_native.xml",
+            ))
+            .unwrap();
+
+            let records: usize = m
+                .getattr("write_subject_native_ndjson")
+                .unwrap()
+                .call1(("tests/assets/subject_native.xml", &records_path))
+                .unwrap()
+                .extract()
+                .unwrap();
+            let kwargs = PyDict::new(py);
+            kwargs.set_item("entries", true).unwrap();
+            let entries: usize = m
+                .getattr("write_subject_native_ndjson")
+                .unwrap()
+                .call(
+                    ("tests/assets/subject_native.xml", &entries_path),
+                    Some(&kwargs),
+                )
+                .unwrap()
+                .extract()
+                .unwrap();
+
+            assert_eq!(records, native.patients.len());
+            assert_eq!(
+                read_to_string(&records_path).unwrap().lines().count(),
+                records
+            );
+            assert_eq!(entries, native.flat_entries().len());
+            assert_eq!(
+                read_to_string(&entries_path).unwrap().lines().count(),
+                entries
+            );
+        });
+    }
+
+    #[test]
+    fn exports_arrow_c_stream() {
+        use arrow_array::ffi_stream::ArrowArrayStreamReader;
+        use pyo3::types::PyCapsuleMethods;
+
+        Python::initialize();
+        Python::attach(|py| {
+            let m = module(py);
+            let native = m
+                .getattr("parse_subject_native_file")
+                .unwrap()
+                .call1(("tests/assets/subject_native.xml",))
+                .unwrap();
+            let expected = native
+                .cast::<SubjectNative>()
+                .unwrap()
+                .borrow()
+                .to_record_batch()
+                .unwrap();
+
+            let capsule = native.call_method0("__arrow_c_stream__").unwrap();
+            let capsule = capsule.cast::<PyCapsule>().unwrap();
+            let pointer = capsule
+                .pointer_checked(Some(c"arrow_array_stream"))
+                .unwrap();
+            let reader = unsafe {
+                ArrowArrayStreamReader::from_raw(pointer.as_ptr() as *mut FFI_ArrowArrayStream)
+            }
+            .unwrap();
+            let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+
+            assert_eq!(batches, vec![expected]);
+        });
+    }
+
+    #[test]
+    fn pickles_and_compares_natives() {
+        Python::initialize();
+        Python::attach(|py| {
+            let m = module(py);
+            py.import("sys")
+                .unwrap()
+                .getattr("modules")
+                .unwrap()
+                .set_item("prelude_xml_parser", &m)
+                .unwrap();
+            let pickle = py.import("pickle").unwrap();
+
+            for (function, path) in [
+                (
+                    "parse_subject_native_file",
+                    "tests/assets/subject_native.xml",
+                ),
+                ("parse_site_native_file", "tests/assets/site_native.xml"),
+                ("parse_user_native_file", "tests/assets/user_native.xml"),
+            ] {
+                let native = m.getattr(function).unwrap().call1((path,)).unwrap();
+                let pickled = pickle.call_method1("dumps", (&native,)).unwrap();
+                let restored = pickle.call_method1("loads", (pickled,)).unwrap();
+
+                assert!(restored.eq(&native).unwrap());
+                assert!(!restored.is(&native));
+            }
+
+            let native = m
+                .getattr("parse_subject_native_file")
+                .unwrap()
+                .call1(("tests/assets/subject_native.xml",))
+                .unwrap();
+            let patients = native.getattr("patients").unwrap();
+            let first = patients.get_item(0).unwrap();
+            assert!(first.eq(patients.get_item(0).unwrap()).unwrap());
+            assert!(first.ne(patients.get_item(1).unwrap()).unwrap());
+            let copy = patients.get_item(0).unwrap();
+            assert_eq!(first.hash().unwrap(), copy.hash().unwrap());
+            assert_eq!(PySet::new(py, [&first, &copy]).unwrap().len(), 1);
+        });
+    }
+
+    #[test]
+    fn reprs_are_readable() {
+        Python::initialize();
+        Python::attach(|py| {
+            let m = module(py);
+            let native = m
+                .getattr("parse_subject_native_file")
+                .unwrap()
+                .call1(("tests/assets/subject_native.xml",))
+                .unwrap();
+            let patient = native.getattr("patients").unwrap().get_item(0).unwrap();
+            let entry = patient
+                .getattr("forms")
+                .unwrap()
+                .get_item(0)
+                .unwrap()
+                .getattr("categories")
+                .unwrap()
+                .get_item(0)
+                .unwrap()
+                .getattr("fields")
+                .unwrap()
+                .get_item(0)
+                .unwrap()
+                .getattr("entries")
+                .unwrap()
+                .get_item(0)
+                .unwrap();
+
+            assert_eq!(
+                native.repr().unwrap().to_cow().unwrap(),
+                "SubjectNative(patients=<2 items>)"
+            );
+            assert_eq!(
+                patient.repr().unwrap().to_cow().unwrap(),
+                "Patient(patient_id='ABC-001', unique_id='1681574905819', site_name='Some Site', forms=<3 items>)"
+            );
+            assert!(entry
+                .repr()
+                .unwrap()
+                .to_cow()
+                .unwrap()
+                .starts_with("Entry(entry_id='1', value=Value(value='Labrador', by='Paul Sanders', role='Project Manager', when=datetime.datetime(2023, 4, 15, 12, 9, 2, tzinfo="));
+        });
+    }
+
+    #[test]
+    fn builds_and_mutates_models_from_python() {
+        Python::initialize();
+        Python::attach(|py| {
+            let locals = PyDict::new(py);
+            locals.set_item("m", module(py)).unwrap();
+            py.run(
+                cr#"
+from datetime import datetime, timedelta, timezone
+
+when = datetime(2023, 4, 15, 12, 9, 2, tzinfo=timezone(timedelta(hours=-4)))
+value = m.Value(by="Paul Sanders", role="Project Manager", value="Labrador", when=when)
+field = m.Field(
+    name="breed",
+    field_type="combo-box",
+    error_code="valid",
+    data_type="string",
+    keep_history=True,
+    entries=[m.Entry(entry_id="1", value=value)],
+)
+form = m.Form(
+    name="demographics",
+    when_created=1681574905839,
+    form_title="Demographics",
+    form_index=1,
+    form_state="In-Work",
+    categories=[m.Category(name="Demographics", category_type="normal", highest_index=0, fields=[field])],
+)
+patient = m.Patient(
+    patient_id="ABC-001",
+    unique_id="1681574905819",
+    creator="Paul Sanders",
+    site_name="Some Site",
+    site_unique_id="1681574834910",
+    number_of_forms=1,
+    forms=[form],
+)
+native = m.SubjectNative(patients=[patient])
+
+patient.site_name = "Other Site"
+value.when = None
+
+# Nested objects are copies, so changing one in place does not change its parent
+native.patients[0].forms[0].form_state = "Complete"
+unchanged_state = native.patients[0].forms[0].form_state
+copy = native.patients[0]
+form = copy.forms[0]
+form.form_state = "Complete"
+copy.forms = [form]
+native.patients = [copy]
+json = native.to_json()
+"#,
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+
+            let native: SubjectNative = serde_json::from_str(
+                &locals
+                    .get_item("json")
+                    .unwrap()
+                    .unwrap()
+                    .extract::<String>()
+                    .unwrap(),
+            )
+            .unwrap();
+            let patient = &native.patients[0];
+            assert_eq!(patient.patient_id, "ABC-001");
+            assert_eq!(patient.site_name, "Some Site");
+            let forms = patient.forms.as_ref().unwrap();
+            assert_eq!(forms[0].form_state, "Complete");
+            assert_eq!(
+                locals
+                    .get_item("unchanged_state")
+                    .unwrap()
+                    .unwrap()
+                    .extract::<String>()
+                    .unwrap(),
+                "In-Work"
+            );
+            let field = &forms[0].categories.as_ref().unwrap()[0]
+                .fields
+                .as_ref()
+                .unwrap()[0];
+            let value = field.entries.as_ref().unwrap()[0].value.as_ref().unwrap();
+            assert_eq!(value.value, "Labrador");
+            assert_eq!(
+                value.when.unwrap().to_rfc3339(),
+                "2023-04-15T12:09:02-04:00"
+            );
+
+            let patient = locals.get_item("patient").unwrap().unwrap();
+            assert_eq!(
+                patient
+                    .getattr("site_name")
+                    .unwrap()
+                    .extract::<String>()
+                    .unwrap(),
+                "Other Site"
+            );
+            let native = locals.get_item("native").unwrap().unwrap();
+            assert!(!native.hasattr("sites").unwrap());
+            let m = locals.get_item("m").unwrap().unwrap();
+            for (class, attribute) in [("SubjectNative", "patients"), ("Patient", "forms")] {
+                let doc = m
+                    .getattr(class)
+                    .unwrap()
+                    .getattr(attribute)
+                    .unwrap()
+                    .getattr("__doc__")
+                    .unwrap()
+                    .extract::<String>()
+                    .unwrap();
+                assert!(doc.contains("copy back"), "{class}.{attribute}: {doc}");
+            }
+            let value = locals.get_item("value").unwrap().unwrap();
+            assert!(value.getattr("when").unwrap().is_none());
+
+            let err = py
+                .run(c"value.when = datetime(2023, 4, 15)", None, Some(&locals))
+                .unwrap_err();
+            assert!(err.is_instance_of::<pyo3::exceptions::PyTypeError>(py));
+        });
+    }
+
+    #[test]
+    fn round_trips_natives_through_dicts_and_json() {
+        Python::initialize();
+        Python::attach(|py| {
+            let m = module(py);
+
+            for (class, function, path) in [
+                (
+                    "SubjectNative",
+                    "parse_subject_native_file",
+                    "tests/assets/subject_native.xml",
+                ),
+                (
+                    "SiteNative",
+                    "parse_site_native_file",
+                    "tests/assets/site_native.xml",
+                ),
+                (
+                    "UserNative",
+                    "parse_user_native_file",
+                    "tests/assets/user_native.xml",
+                ),
+            ] {
+                let class = m.getattr(class).unwrap();
+                let native = m.getattr(function).unwrap().call1((path,)).unwrap();
+
+                let dict = native.call_method0("to_dict").unwrap();
+                let from_dict = class.call_method1("from_dict", (dict,)).unwrap();
+                assert!(from_dict.eq(&native).unwrap());
+
+                let json = native.call_method0("to_json").unwrap();
+                let from_json = class.call_method1("from_json", (json,)).unwrap();
+                assert!(from_json.eq(&native).unwrap());
+            }
+
+            let dict = PyDict::new(py);
+            dict.set_item("by", "Paul Sanders").unwrap();
+            let err = m
+                .getattr("Value")
+                .unwrap()
+                .call_method1("from_dict", (dict,))
+                .unwrap_err();
+            assert!(err.is_instance_of::<PyKeyError>(py));
+        });
+    }
+}